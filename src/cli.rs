@@ -1,6 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 
+use crate::download::RetentionPolicy;
+use crate::writer::{ConvertOptions, ExportFormat, Naming, OnConflict, OutputFormat};
+
 #[derive(Parser, Debug)]
 #[command(name = "eve-sde-to-sqlite")]
 #[command(version, about = "Convert EVE Online SDE to SQLite database")]
@@ -9,6 +13,15 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub quiet: bool,
 
+    /// Exit with code 2 if the run completed with warnings (e.g. skipped files)
+    #[arg(long, global = true)]
+    pub fail_on_warning: bool,
+
+    /// Write diagnostics to stderr: -v for info, -vv for debug, -vvv for trace
+    /// (implies --quiet, since the TUI would draw over them)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -20,13 +33,8 @@ pub enum Commands {
         /// Output SQLite database path
         output_db: PathBuf,
 
-        /// Only include these tables (comma-separated)
-        #[arg(short, long, value_delimiter = ',')]
-        include: Option<Vec<String>>,
-
-        /// Exclude these tables (comma-separated)
-        #[arg(short, long, value_delimiter = ',')]
-        exclude: Option<Vec<String>>,
+        #[command(flatten)]
+        tables: TableArgs,
 
         /// Force re-download even if cached
         #[arg(short, long)]
@@ -35,42 +43,438 @@ pub enum Commands {
         /// Custom cache directory
         #[arg(short, long)]
         cache_dir: Option<PathBuf>,
+
+        /// Convert the newest cached build without contacting the server (also the
+        /// fallback, with a warning, when the server can't be reached)
+        #[arg(long, conflicts_with = "force")]
+        offline: bool,
+
+        /// Only sync if the latest build is newer than N; without N, use the build
+        /// recorded in the existing output database
+        #[arg(long, value_name = "N", num_args = 0..=1)]
+        since_build: Option<Option<u64>>,
+
+        /// Exit with code 3 instead of 0 when --since-build skipped the sync
+        #[arg(long, requires = "since_build")]
+        exit_code_on_skip: bool,
+
+        #[command(flatten)]
+        retention: RetentionArgs,
+
+        /// Keep the downloaded SDE zip in the cache after extracting it; it is pruned
+        /// together with its build
+        #[arg(long)]
+        keep_zip: bool,
+
+        /// Download (if needed) and print the tables that would be imported, without converting
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        options: ConvertArgs,
+
+        #[command(flatten)]
+        merge: MergeArgs,
     },
 
-    /// Download latest SDE zip file
+    /// Download the latest SDE zip file, without extracting it
     Download {
-        /// Output directory
+        /// Directory the zip is saved to (default: the cache directory)
         #[arg(short, long)]
         output: Option<PathBuf>,
 
         /// Force re-download even if cached
         #[arg(short, long)]
         force: bool,
+
+        #[command(flatten)]
+        retention: RetentionArgs,
     },
 
     /// Convert local JSONL files to SQLite database
     Convert {
-        /// Directory containing JSONL files
+        /// Directory containing JSONL files (or a zip/tar.gz archive with --archive)
         input_dir: PathBuf,
 
         /// Output SQLite database path
         output_db: PathBuf,
 
-        /// Only include these tables (comma-separated)
-        #[arg(short, long, value_delimiter = ',')]
-        include: Option<Vec<String>>,
+        #[command(flatten)]
+        tables: TableArgs,
+
+        /// Print the tables that would be imported, without creating the database
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Read the JSONL files straight out of a zip or tar.gz archive at INPUT_DIR,
+        /// without extracting it
+        #[arg(long)]
+        archive: bool,
 
-        /// Exclude these tables (comma-separated)
-        #[arg(short, long, value_delimiter = ',')]
-        exclude: Option<Vec<String>>,
+        #[command(flatten)]
+        options: ConvertArgs,
+
+        #[command(flatten)]
+        merge: MergeArgs,
     },
 
     /// List all available table names
-    ListTables,
+    ListTables {
+        /// Also show each table's source file, column count (localized columns expanded
+        /// per language), foreign keys, indexes and whether it is a junction table
+        #[arg(short, long)]
+        long: bool,
+    },
+
+    /// Print the CREATE TABLE and CREATE INDEX statements for the selected tables
+    PrintSchema {
+        #[command(flatten)]
+        tables: TableArgs,
+    },
+
+    /// Export a table of an existing database to a flat file
+    Export {
+        /// SQLite database to read
+        db: PathBuf,
+
+        /// Table to export
+        table: String,
+
+        /// Output file format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Check an existing database for rows with dangling foreign keys
+    Verify {
+        /// SQLite database to check
+        db: PathBuf,
+    },
+
+    /// Compare two generated databases, e.g. before and after a new SDE build
+    Diff {
+        /// Database built from the older SDE
+        old: PathBuf,
+
+        /// Database built from the newer SDE
+        new: PathBuf,
+
+        /// Only compare this table, listing every changed column value
+        #[arg(long)]
+        table: Option<String>,
+    },
+}
+
+/// Table selection shared by `sync` and `convert`
+#[derive(Args, Debug, Clone, Default)]
+pub struct TableArgs {
+    /// Only include these tables (comma-separated; globs like `map_*` allowed)
+    #[arg(short, long, value_delimiter = ',')]
+    pub include: Option<Vec<String>>,
+
+    /// Exclude these tables (comma-separated; globs like `map_*` allowed)
+    #[arg(short, long, value_delimiter = ',')]
+    pub exclude: Option<Vec<String>>,
+
+    /// Import a curated set of commonly used tables (see README)
+    #[arg(long, conflicts_with_all = ["include", "exclude"])]
+    pub only_core: bool,
+
+    /// Refuse to import more than N tables unless confirmed (or --yes is given)
+    #[arg(long, value_name = "N")]
+    pub limit_tables: Option<usize>,
+
+    /// Fail before importing if a table named in --include has no source file
+    #[arg(long)]
+    pub require_requested: bool,
+
+    /// Skip confirmation prompts
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+/// Conversion options shared by `sync` and `convert`
+#[derive(Args, Debug, Clone, Default)]
+pub struct ConvertArgs {
+    /// Store each record's original JSON line in a `_raw` column for these tables (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub raw_json: Option<Vec<String>>,
+
+    /// Add a search_text column joining the localized names for these tables (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub search_text_tables: Option<Vec<String>>,
+
+    /// Also join localized descriptions into search_text (with --search-text-tables)
+    #[arg(long)]
+    pub search_text_descriptions: bool,
+
+    /// Only create localized columns for these languages (comma-separated, must include en)
+    #[arg(long, value_delimiter = ',')]
+    pub languages: Option<Vec<String>>,
+
+    /// Column naming convention: snake_case (default) or CCP's camelCase field names
+    #[arg(long, value_enum, default_value_t = Naming::Snake)]
+    pub naming: Naming,
+
+    /// Create convenience views (e.g. manufacturing_sources) over the imported tables
+    #[arg(long)]
+    pub views: bool,
+
+    /// Create `<table>_fts` FTS5 tables indexing the localized names of each imported table
+    /// with a `name` (e.g. `types_fts`), for fast name search
+    #[arg(long)]
+    pub fts: bool,
+
+    /// Warn about source lines that repeat a record key, listing the offending ids; rows
+    /// repeating a primary key are skipped, so the first line wins
+    #[arg(long)]
+    pub warn_duplicate_keys: bool,
+
+    /// Keep only the last line for each repeated record key (implies --warn-duplicate-keys)
+    #[arg(long)]
+    pub dedupe_keys: bool,
+
+    /// Fail on source lines with an unexpected shape instead of skipping them with a warning
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Add indexed has_icon/has_graphic/has_sound columns to types
+    #[arg(long)]
+    pub derive_presence_flags: bool,
+
+    /// Add region_name_en and constellation_name_en columns to map_solar_systems
+    #[arg(long)]
+    pub denormalize: bool,
+
+    /// Parse up to N tables concurrently on worker threads (rows are still written by one thread)
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    pub jobs: usize,
+
+    /// Import at most N rows per table (for quick schema iteration)
+    #[arg(long, value_name = "N")]
+    pub limit: Option<u64>,
+
+    /// Insert rows in batches of N (default 1000, 0 is treated as 1); larger batches
+    /// import faster, smaller ones help when debugging
+    #[arg(long, value_name = "N")]
+    pub batch_size: Option<usize>,
+
+    /// Report time and rows/sec for each table, slowest first, with the time spent
+    /// reading, parsing and inserting
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Import only these ids of a base table, e.g. `types=587,588`; junction tables whose
+    /// parent references it keep only matching rows (repeatable)
+    #[arg(long, value_name = "TABLE=IDS", value_parser = parse_id_filter)]
+    pub id_filter: Vec<(String, BTreeSet<i64>)>,
+
+    /// Skip records whose `published` flag is explicitly false (records without one are
+    /// kept), and the junction rows (e.g. dogma attributes) of unpublished types; foreign
+    /// keys to unpublished rows are left dangling
+    #[arg(long)]
+    pub published_only: bool,
+
+    /// Set a PRAGMA on the output database before tables are created, e.g. `page_size=8192`
+    /// (repeatable)
+    #[arg(long, value_name = "NAME=VALUE", value_parser = parse_pragma)]
+    pub pragma: Vec<(String, String)>,
+
+    /// SQLite page cache size in MiB while importing (default 64); zero or negative uses
+    /// SQLite's default
+    #[arg(long, value_name = "N", allow_negative_numbers = true)]
+    pub cache_size_mb: Option<i64>,
+
+    /// Memory-map up to N MiB of the output database; zero or negative leaves mmap off
+    /// (SQLite's default)
+    #[arg(long, value_name = "N", allow_negative_numbers = true)]
+    pub mmap_size_mb: Option<i64>,
+
+    /// Skip source lines that fail to parse, logging each, instead of aborting the import;
+    /// the run still fails after the database is written if any line was skipped
+    #[arg(long)]
+    pub continue_on_error: bool,
+
+    /// Add a `map_jumps(from_system_id, to_system_id)` table of solar systems directly
+    /// connected by a stargate, derived from `map_stargates`
+    #[arg(long)]
+    pub build_jumps: bool,
+
+    /// Drop junction rows that repeat an existing primary key (e.g. the same material
+    /// listed twice for a type) instead of failing the import, logging how many
+    #[arg(long)]
+    pub dedup_junctions: bool,
+
+    /// Skip gathering query planner statistics (ANALYZE) for a slightly faster build
+    #[arg(long)]
+    pub no_analyze: bool,
+
+    /// Rebuild the finished database with VACUUM to produce the smallest file, e.g. for
+    /// distribution
+    #[arg(long)]
+    pub vacuum: bool,
+
+    /// After importing, log every row whose foreign key has no parent row
+    /// (e.g. because `--exclude` dropped the parent table)
+    #[arg(long)]
+    pub check_fk: bool,
+
+    /// Fail the run, after the database is written, if `--check-fk` found violations
+    #[arg(long, requires = "check_fk")]
+    pub strict_fk: bool,
+
+    /// Warn about JSON fields in the source files that no column imports, with an example key
+    #[arg(long)]
+    pub report_unknown_fields: bool,
+
+    /// Replace only the selected tables in an existing database, leaving the others intact,
+    /// instead of rebuilding the whole file
+    #[arg(long)]
+    pub incremental: bool,
+
+    /// After converting, fail unless TABLE has at least MIN rows, e.g. `types=50000`
+    /// (repeatable)
+    #[arg(long, value_name = "TABLE=MIN", value_parser = parse_expectation)]
+    pub expect: Vec<(String, u64)>,
+
+    /// Print the final summary to stdout as one JSON object (output, records, build,
+    /// elapsed_secs, tables, converted) instead of a sentence, even with --quiet
+    #[arg(long)]
+    pub json_summary: bool,
+
+    /// Treat the output path as a directory and write each table to its own `<table>.db`
+    /// there, without foreign keys (they cannot reach into other files)
+    #[arg(long)]
+    pub split_by_table: bool,
+
+    /// Write a SQLite database, a directory of Parquet files (needs the `parquet` feature)
+    /// or a DuckDB database (needs the `duckdb` feature)
+    #[arg(long, visible_alias = "backend", value_enum, default_value_t = OutputFormat::Sqlite)]
+    pub output_format: OutputFormat,
+}
+
+impl ConvertArgs {
+    /// Build writer options from the parsed arguments
+    pub fn to_options(&self) -> ConvertOptions {
+        ConvertOptions {
+            raw_json_tables: self.raw_json.clone().unwrap_or_default(),
+            search_text_tables: self.search_text_tables.clone().unwrap_or_default(),
+            search_text_descriptions: self.search_text_descriptions,
+            languages: self.languages.clone().unwrap_or_default(),
+            naming: self.naming,
+            views: self.views,
+            fts: self.fts,
+            warn_duplicate_keys: self.warn_duplicate_keys,
+            dedupe_keys: self.dedupe_keys,
+            strict: self.strict,
+            derive_presence_flags: self.derive_presence_flags,
+            denormalize: self.denormalize,
+            row_limit: self.limit,
+            batch_size: self.batch_size,
+            jobs: self.jobs,
+            profile: self.profile,
+            output_format: self.output_format,
+            id_filters: self.id_filter.clone(),
+            published_only: self.published_only,
+            pragmas: self.pragma.clone(),
+            cache_size_mb: self.cache_size_mb,
+            mmap_size_mb: self.mmap_size_mb,
+            incremental: self.incremental,
+            report_unknown_fields: self.report_unknown_fields,
+            continue_on_error: self.continue_on_error,
+            skip_analyze: self.no_analyze,
+            dedup_junctions: self.dedup_junctions,
+            build_jumps: self.build_jumps,
+            vacuum: self.vacuum,
+            split_by_table: self.split_by_table,
+            row_expectations: self.expect.clone(),
+            check_fk: self.check_fk,
+            strict_fk: self.strict_fk,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parse an `--id-filter` value: `table=id,id,...`
+fn parse_id_filter(value: &str) -> Result<(String, BTreeSet<i64>), String> {
+    let (table, ids) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected TABLE=IDS, got {:?}", value))?;
+    let ids = ids
+        .split(',')
+        .map(|id| {
+            id.trim()
+                .parse()
+                .map_err(|_| format!("invalid id {:?} in --id-filter", id))
+        })
+        .collect::<Result<BTreeSet<i64>, String>>()?;
+    Ok((table.to_string(), ids))
+}
+
+/// Parse a `--pragma` value: `name=value` (checked by `ConvertOptions::validate`)
+fn parse_pragma(value: &str) -> Result<(String, String), String> {
+    let (name, pragma_value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got {:?}", value))?;
+    Ok((name.trim().to_string(), pragma_value.trim().to_string()))
+}
+
+/// Parse an `--expect` value: `table=min_rows`
+fn parse_expectation(value: &str) -> Result<(String, u64), String> {
+    let (table, min) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected TABLE=MIN, got {:?}", value))?;
+    let min = min
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid row count {:?} for {}", min.trim(), table.trim()))?;
+    Ok((table.trim().to_string(), min))
+}
+
+/// Options for copying the built tables into another database
+#[derive(Args, Debug, Clone, Default)]
+pub struct MergeArgs {
+    /// After building, copy the selected tables into this existing database
+    #[arg(long, value_name = "TARGET_DB")]
+    pub merge_into: Option<PathBuf>,
+
+    /// What to do when a table already exists in the --merge-into target
+    #[arg(long, value_enum, default_value_t = OnConflict::Fail, requires = "merge_into")]
+    pub on_conflict: OnConflict,
+}
+
+/// Cache retention options shared by `sync` and `download`
+#[derive(Args, Debug, Clone)]
+pub struct RetentionArgs {
+    /// Number of most recent SDE builds to keep in the cache
+    #[arg(long, visible_alias = "keep-builds", default_value_t = 1)]
+    pub cache_limit: usize,
+
+    /// Maximum total cache size in bytes; oldest builds are evicted first
+    #[arg(long)]
+    pub cache_max_size: Option<u64>,
+}
+
+impl RetentionArgs {
+    /// Build the cache retention policy from the parsed arguments
+    pub fn to_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_builds: self.cache_limit,
+            max_size: self.cache_max_size,
+            ..Default::default()
+        }
+    }
 }
 
 impl Cli {
     pub fn parse_args() -> Self {
-        Cli::parse()
+        let mut cli = Cli::parse();
+        // Logs on stderr would corrupt the TUI, so verbose runs are always quiet
+        cli.quiet |= cli.verbose > 0;
+        cli
     }
 }
@@ -3,6 +3,29 @@ use directories::ProjectDirs;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::client::partial_path;
+
+/// Which cached builds to keep after a download
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// Keep at most this many builds (newest by build number)
+    pub max_builds: usize,
+    /// Evict oldest builds until the cache is at most this many bytes
+    pub max_size: Option<u64>,
+    /// Keep the downloaded zip next to its extracted build instead of deleting it
+    pub keep_zip: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_builds: 1,
+            max_size: None,
+            keep_zip: false,
+        }
+    }
+}
+
 pub struct CacheManager {
     cache_dir: PathBuf,
 }
@@ -39,27 +62,239 @@ impl CacheManager {
         build_dir.exists() && build_dir.join("types.jsonl").exists()
     }
 
+    /// Newest build that is fully extracted in the cache, if any
+    pub fn latest_cached_build(&self) -> Option<u64> {
+        self.cached_builds()
+            .ok()?
+            .into_iter()
+            .map(|(build, _)| build)
+            .filter(|&build| self.is_cached(build))
+            .max()
+    }
+
     /// Get path to zip file for a build
     pub fn zip_path(&self, build_number: u64) -> PathBuf {
         self.cache_dir.join(format!("{}.zip", build_number))
     }
 
-    /// Clean up old cached builds, keeping only the specified one
-    pub fn cleanup_old_builds(&self, keep_build: u64) -> Result<()> {
+    /// Prune cached builds according to the retention policy.
+    /// The current build is never removed, and entries not named after a build
+    /// number are left alone. Returns the removed build numbers.
+    pub fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        current_build: u64,
+    ) -> Result<Vec<u64>> {
+        // The current build always counts toward the limit, then the newest others
+        let mut builds = self.cached_builds()?;
+        builds.sort_by_key(|&(build, _)| (build != current_build, std::cmp::Reverse(build)));
+
+        let limit = policy.max_builds.max(1);
+        let mut kept: Vec<(u64, u64)> = Vec::new();
+        let mut removed = Vec::new();
+
+        for (build, size) in builds {
+            if kept.len() < limit {
+                kept.push((build, size));
+            } else {
+                removed.push(build);
+            }
+        }
+
+        if let Some(max_size) = policy.max_size {
+            let mut total: u64 = kept.iter().map(|(_, size)| size).sum();
+            // Evict oldest first; the current build (if cached) stays at index 0
+            while total > max_size && kept.len() > 1 {
+                let (build, size) = kept.pop().expect("kept is not empty");
+                total -= size;
+                removed.push(build);
+            }
+        }
+
+        for build in &removed {
+            self.remove_build(*build)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// List cached build numbers with their size on disk (extracted dir plus zip)
+    fn cached_builds(&self) -> Result<Vec<(u64, u64)>> {
+        let mut builds: Vec<(u64, u64)> = Vec::new();
+
         for entry in fs::read_dir(&self.cache_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if let Ok(build) = name.parse::<u64>() {
-                        if build != keep_build {
-                            fs::remove_dir_all(&path).ok();
-                        }
-                    }
+            let path = entry?.path();
+            let name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let build = if path.is_dir() {
+                name.parse::<u64>().ok()
+            } else {
+                name.strip_suffix(".zip")
+                    .or_else(|| name.strip_suffix(".zip.part"))
+                    .and_then(|n| n.parse::<u64>().ok())
+            };
+
+            if let Some(build) = build {
+                let size = path_size(&path)?;
+                match builds.iter_mut().find(|(b, _)| *b == build) {
+                    Some((_, total)) => *total += size,
+                    None => builds.push((build, size)),
                 }
             }
         }
+
+        Ok(builds)
+    }
+
+    /// Remove a build's extracted directory and zip file (complete or partial)
+    fn remove_build(&self, build_number: u64) -> Result<()> {
+        let build_dir = self.build_dir(build_number);
+        if build_dir.exists() {
+            fs::remove_dir_all(&build_dir)
+                .with_context(|| format!("Failed to remove {:?}", build_dir))?;
+        }
+
+        let zip_path = self.zip_path(build_number);
+        for path in [partial_path(&zip_path), zip_path] {
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+            }
+        }
+
         Ok(())
     }
 }
+
+/// Total size in bytes of a file or directory tree
+fn path_size(path: &Path) -> Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        total += path_size(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a fake build with an extracted dir holding `size` bytes
+    fn fake_build(cache: &CacheManager, build: u64, size: usize) {
+        let dir = cache.build_dir(build);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("types.jsonl"), vec![b'x'; size]).unwrap();
+    }
+
+    fn remaining(cache: &CacheManager) -> Vec<u64> {
+        let mut builds: Vec<u64> = cache.cached_builds().unwrap().iter().map(|b| b.0).collect();
+        builds.sort();
+        builds
+    }
+
+    #[test]
+    fn test_retention_keeps_newest_builds() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = CacheManager::new(Some(tmp.path().to_path_buf())).unwrap();
+        for build in [100, 200, 300, 400] {
+            fake_build(&cache, build, 10);
+        }
+        fs::write(cache.zip_path(100), b"zip").unwrap();
+
+        let policy = RetentionPolicy {
+            max_builds: 2,
+            ..Default::default()
+        };
+        let mut removed = cache.apply_retention(&policy, 400).unwrap();
+        removed.sort();
+
+        assert_eq!(removed, vec![100, 200]);
+        assert_eq!(remaining(&cache), vec![300, 400]);
+        assert!(!cache.zip_path(100).exists());
+    }
+
+    #[test]
+    fn test_latest_cached_build() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = CacheManager::new(Some(tmp.path().to_path_buf())).unwrap();
+        assert_eq!(cache.latest_cached_build(), None);
+
+        fake_build(&cache, 100, 10);
+        fake_build(&cache, 200, 10);
+        // A newer download that was never extracted doesn't count
+        fs::write(cache.zip_path(300), b"zip").unwrap();
+
+        assert_eq!(cache.latest_cached_build(), Some(200));
+    }
+
+    #[test]
+    fn test_retention_never_removes_current_build() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = CacheManager::new(Some(tmp.path().to_path_buf())).unwrap();
+        for build in [100, 200, 300] {
+            fake_build(&cache, build, 10);
+        }
+
+        // Current build is older than the cached ones (e.g. a pinned build)
+        let policy = RetentionPolicy {
+            max_builds: 1,
+            ..Default::default()
+        };
+        let removed = cache.apply_retention(&policy, 100).unwrap();
+
+        assert_eq!(removed, vec![300, 200]);
+        assert_eq!(remaining(&cache), vec![100]);
+    }
+
+    #[test]
+    fn test_retention_leaves_unrelated_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = CacheManager::new(Some(tmp.path().to_path_buf())).unwrap();
+        for build in [100, 200, 300, 400] {
+            fake_build(&cache, build, 10);
+        }
+        fs::create_dir(tmp.path().join("notes")).unwrap();
+
+        let policy = RetentionPolicy {
+            max_builds: 3,
+            ..Default::default()
+        };
+        cache.apply_retention(&policy, 400).unwrap();
+
+        assert_eq!(remaining(&cache), vec![200, 300, 400]);
+        assert!(tmp.path().join("notes").exists());
+    }
+
+    #[test]
+    fn test_retention_size_cap_evicts_oldest_first() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = CacheManager::new(Some(tmp.path().to_path_buf())).unwrap();
+        for build in [100, 200, 300, 400] {
+            fake_build(&cache, build, 100);
+        }
+
+        let policy = RetentionPolicy {
+            max_builds: 10,
+            max_size: Some(250),
+            ..Default::default()
+        };
+        cache.apply_retention(&policy, 400).unwrap();
+        assert_eq!(remaining(&cache), vec![300, 400]);
+
+        // The current build is kept even if it alone exceeds the cap
+        let policy = RetentionPolicy {
+            max_builds: 10,
+            max_size: Some(50),
+            ..Default::default()
+        };
+        cache.apply_retention(&policy, 400).unwrap();
+        assert_eq!(remaining(&cache), vec![400]);
+    }
+}
@@ -1,16 +1,23 @@
-use anyhow::{Context, Result};
-use reqwest::blocking::Client;
+use anyhow::{bail, Context, Result};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
 use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::OpenOptions;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use crate::ui::Ui;
+use crate::parser::normalize_timestamp;
+use crate::ui::{format_size, Ui};
 
 const LATEST_URL: &str = "https://developers.eveonline.com/static-data/tranquility/latest.jsonl";
 const ZIP_URL: &str =
     "https://developers.eveonline.com/static-data/eve-online-static-data-latest-jsonl.zip";
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SdeInfo {
     #[serde(rename = "_key")]
     pub key: String,
@@ -18,10 +25,67 @@ pub struct SdeInfo {
     pub build_number: u64,
     #[serde(rename = "releaseDate")]
     pub release_date: String,
+    /// Any further fields CCP publishes (release name, notes, ...), kept by their original names
+    #[serde(flatten, default)]
+    pub extra: BTreeMap<String, Value>,
 }
 
+impl SdeInfo {
+    /// Parse a line of the latest-build metadata, normalizing the release date to UTC
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut info: SdeInfo = serde_json::from_str(text).context("Failed to parse SDE info")?;
+
+        // Release dates are compared across machines, so keep them in UTC
+        if let Some(utc) = normalize_timestamp(&info.release_date) {
+            info.release_date = utc;
+        }
+
+        Ok(info)
+    }
+
+    /// Key/value pairs describing this build, for the `sde_meta` table
+    pub fn metadata(&self) -> Vec<(String, String)> {
+        let mut pairs = vec![
+            ("build_number".to_string(), self.build_number.to_string()),
+            ("release_date".to_string(), self.release_date.clone()),
+        ];
+
+        for (key, value) in &self.extra {
+            let text = match value {
+                Value::Null => continue,
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            pairs.push((key.clone(), text));
+        }
+
+        pairs
+    }
+
+    /// Extra string fields for display (e.g. `releaseName: Uprising`)
+    pub fn describe_extra(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .extra
+            .iter()
+            .filter_map(|(key, value)| value.as_str().map(|s| format!("{}: {}", key, s)))
+            .collect();
+
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// Attempts made for each HTTP request before giving up
+pub const DEFAULT_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled for each further one
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
 pub struct SdeClient {
     client: Client,
+    /// Attempts made for each request; connection errors and 5xx responses are
+    /// retried with exponential backoff (1 disables retries)
+    pub attempts: u32,
+    retry_delay: Duration,
 }
 
 impl SdeClient {
@@ -30,38 +94,117 @@ impl SdeClient {
             .user_agent("eve-sde-to-sqlite")
             .build()
             .context("Failed to create HTTP client")?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            attempts: DEFAULT_ATTEMPTS,
+            retry_delay: RETRY_DELAY,
+        })
+    }
+
+    /// Send a request built by `request`, retrying connection errors and 5xx responses.
+    /// After the last attempt the final response (or error) is returned as is.
+    fn send(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+        ui: &mut impl Ui,
+    ) -> reqwest::Result<Response> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 1;
+
+        loop {
+            let result = request().send();
+            let reason = match &result {
+                Ok(response) if response.status().is_server_error() => {
+                    response.status().to_string()
+                }
+                Err(e) if e.is_connect() || e.is_timeout() || e.is_request() => e.to_string(),
+                _ => return result,
+            };
+            if attempt >= self.attempts {
+                return result;
+            }
+
+            ui.log(format!(
+                "Retry {}/{} in {:.1}s ({})",
+                attempt + 1,
+                self.attempts,
+                delay.as_secs_f64(),
+                reason
+            ));
+            std::thread::sleep(delay);
+            delay *= 2;
+            attempt += 1;
+        }
     }
 
     /// Fetch the latest SDE build info
-    pub fn fetch_latest_info(&self) -> Result<SdeInfo> {
+    pub fn fetch_latest_info(&self, ui: &mut impl Ui) -> Result<SdeInfo> {
         let response = self
-            .client
-            .get(LATEST_URL)
-            .send()
+            .send(|| self.client.get(LATEST_URL), ui)
+            .and_then(|r| r.error_for_status())
             .context("Failed to fetch latest SDE info")?;
 
         let text = response.text().context("Failed to read response")?;
-        let info: SdeInfo = serde_json::from_str(&text).context("Failed to parse SDE info")?;
-
-        Ok(info)
+        SdeInfo::parse(&text)
     }
 
-    /// Download the SDE zip file to the given path
+    /// Download the SDE zip file to the given path, resuming a partial download if one exists
     pub fn download_zip(&self, dest: &Path, ui: &mut impl Ui) -> Result<()> {
-        let response = self
-            .client
-            .get(ZIP_URL)
-            .send()
+        self.download_url(ZIP_URL, dest, ui)
+    }
+
+    /// Download a URL via `<dest>.part`, renaming it into place once complete
+    fn download_url(&self, url: &str, dest: &Path, ui: &mut impl Ui) -> Result<()> {
+        let part_path = partial_path(dest);
+        let existing = std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+
+        let request = || {
+            let request = self.client.get(url);
+            if existing > 0 {
+                request.header(RANGE, format!("bytes={}-", existing))
+            } else {
+                request
+            }
+        };
+        let response = self.send(request, ui).context("Failed to start download")?;
+
+        if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            // The partial file doesn't match what the server has; start over
+            std::fs::remove_file(&part_path).context("Failed to remove partial download")?;
+            return self.download_url(url, dest, ui);
+        }
+        let response = response
+            .error_for_status()
             .context("Failed to start download")?;
 
-        let total_size = response.content_length().unwrap_or(0);
+        // A server without range support answers 200 with the whole file
+        let resumed = if response.status() == StatusCode::PARTIAL_CONTENT {
+            existing
+        } else {
+            0
+        };
+        let expected_len = response.content_length().map(|len| resumed + len);
+        let total_size = expected_len.unwrap_or(0);
 
-        let mut file = std::fs::File::create(dest).context("Failed to create destination file")?;
+        let mut file = if resumed > 0 {
+            ui.log(format!(
+                "Resuming download at {}",
+                format_bytes(resumed, total_size)
+            ));
+            OpenOptions::new().append(true).open(&part_path)
+        } else {
+            std::fs::File::create(&part_path)
+        }
+        .context("Failed to create destination file")?;
 
-        let mut downloaded: u64 = 0;
+        let mut downloaded: u64 = resumed;
         let mut buffer = [0u8; 8192];
         let mut reader = response;
+        let mut rate = TransferRate::new();
+        let mut label = format_bytes(downloaded, total_size);
+        let mut label_updated = Instant::now();
+        rate.sample(label_updated, downloaded);
+        ui.set_progress(downloaded, total_size, label.clone());
 
         loop {
             let bytes_read = reader
@@ -76,28 +219,114 @@ impl SdeClient {
                 .context("Failed to write to file")?;
 
             downloaded += bytes_read as u64;
-            ui.set_progress(downloaded, total_size, format_bytes(downloaded, total_size));
+            let now = Instant::now();
+            if now.duration_since(label_updated) >= LABEL_INTERVAL {
+                let bytes_per_sec = rate.sample(now, downloaded);
+                label = format_transfer(downloaded, total_size, bytes_per_sec);
+                label_updated = now;
+            }
+            ui.set_progress(downloaded, total_size, label.clone());
         }
 
+        drop(file);
+        verify_download(&part_path, expected_len)?;
+        std::fs::rename(&part_path, dest).context("Failed to move download into place")?;
+
         ui.log("Download complete");
         Ok(())
     }
 }
 
+/// Check that a finished download has the length the server announced, so a
+/// truncated archive fails here rather than during extraction
+pub fn verify_download(path: &Path, expected_len: Option<u64>) -> Result<()> {
+    let Some(expected) = expected_len else {
+        return Ok(());
+    };
+
+    let actual = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read {:?}", path))?
+        .len();
+    if actual != expected {
+        bail!(
+            "Download incomplete: {:?} has {} bytes, expected {}. Re-run with --force to download it again",
+            path,
+            actual,
+            expected
+        );
+    }
+
+    Ok(())
+}
+
+/// Path a download is written to until it completes (`<dest>.part`)
+pub fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// How often the download progress label (rate and ETA) is recomputed
+const LABEL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Span of recent progress the transfer rate is averaged over
+const RATE_WINDOW: Duration = Duration::from_secs(3);
+
+/// Transfer rate over a rolling window of recent progress samples
+struct TransferRate {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl TransferRate {
+    fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record the bytes transferred so far at `now`, returning the rate in bytes/sec
+    /// over the window (`None` until samples span some time)
+    fn sample(&mut self, now: Instant, bytes: u64) -> Option<f64> {
+        self.samples.push_back((now, bytes));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+
+        let (start, start_bytes) = *self.samples.front()?;
+        let elapsed = now.duration_since(start).as_secs_f64();
+        (elapsed > 0.0).then(|| (bytes - start_bytes) as f64 / elapsed)
+    }
+}
+
 /// Format bytes as human-readable string
 fn format_bytes(current: u64, total: u64) -> String {
-    fn fmt(bytes: u64) -> String {
-        if bytes >= 1_000_000_000 {
-            format!("{:.1} GB", bytes as f64 / 1_000_000_000.0)
-        } else if bytes >= 1_000_000 {
-            format!("{:.1} MB", bytes as f64 / 1_000_000.0)
-        } else if bytes >= 1_000 {
-            format!("{:.1} KB", bytes as f64 / 1_000.0)
-        } else {
-            format!("{} B", bytes)
-        }
+    format!("{} / {}", format_size(current), format_size(total))
+}
+
+/// Format download progress with its rate and the time left, e.g.
+/// `45.2 MB / 120.0 MB — 8.1 MB/s — ETA 9s`
+fn format_transfer(current: u64, total: u64, bytes_per_sec: Option<f64>) -> String {
+    let progress = format_bytes(current, total);
+    let Some(rate) = bytes_per_sec.filter(|r| *r >= 1.0) else {
+        return progress;
+    };
+
+    let speed = format!("{}/s", format_size(rate as u64));
+    if total <= current {
+        return format!("{} — {}", progress, speed);
     }
-    format!("{} / {}", fmt(current), fmt(total))
+
+    let eta = ((total - current) as f64 / rate).ceil() as u64;
+    let eta = if eta >= 60 {
+        format!("{}m {:02}s", eta / 60, eta % 60)
+    } else {
+        format!("{}s", eta)
+    };
+    format!("{} — {} — ETA {}", progress, speed, eta)
 }
 
 impl Default for SdeClient {
@@ -116,4 +345,203 @@ mod tests {
         assert_eq!(format_bytes(1500, 3000), "1.5 KB / 3.0 KB");
         assert_eq!(format_bytes(1_500_000, 3_000_000), "1.5 MB / 3.0 MB");
     }
+
+    #[test]
+    fn test_format_transfer() {
+        assert_eq!(
+            format_transfer(45_200_000, 120_000_000, Some(8_100_000.0)),
+            "45.2 MB / 120.0 MB — 8.1 MB/s — ETA 10s"
+        );
+        assert_eq!(
+            format_transfer(0, 120_000_000, Some(1_000_000.0)),
+            "0 B / 120.0 MB — 1.0 MB/s — ETA 2m 00s"
+        );
+        assert_eq!(format_transfer(500, 999, None), "500 B / 999 B");
+    }
+
+    #[test]
+    fn test_transfer_rate_uses_recent_window() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut rate = TransferRate::new();
+
+        assert_eq!(rate.sample(at(0), 0), None);
+        assert_eq!(rate.sample(at(1000), 1_000_000), Some(1_000_000.0));
+
+        // Once the early samples leave the window, only recent progress counts
+        rate.sample(at(3000), 2_000_000);
+        assert_eq!(rate.sample(at(5000), 10_000_000), Some(4_000_000.0));
+    }
+
+    /// Serve `body` once per connection, honouring `Range` only if `ranges` is set
+    fn serve(body: &'static [u8], ranges: bool, requests: usize) -> String {
+        use std::io::BufRead;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sde.zip", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut start = 0;
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(range) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                        start = range.trim().trim_end_matches('-').parse().unwrap();
+                    }
+                }
+
+                let (status, content) = if ranges && start > 0 {
+                    ("206 Partial Content", &body[start..])
+                } else {
+                    ("200 OK", body)
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    content.len()
+                )
+                .unwrap();
+                stream.write_all(content).unwrap();
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn test_download_resumes_partial_file() {
+        let body: &'static [u8] = b"0123456789abcdef";
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("100.zip");
+        let client = SdeClient::new().unwrap();
+
+        // Server honours the range: only the missing tail is appended
+        std::fs::write(partial_path(&dest), &body[..6]).unwrap();
+        let url = serve(body, true, 1);
+        client
+            .download_url(&url, &dest, &mut crate::ui::SilentUi::new())
+            .unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert!(!partial_path(&dest).exists());
+
+        // Server ignores the range: the partial file is overwritten
+        std::fs::remove_file(&dest).unwrap();
+        std::fs::write(partial_path(&dest), b"stale").unwrap();
+        let url = serve(body, false, 1);
+        client
+            .download_url(&url, &dest, &mut crate::ui::SilentUi::new())
+            .unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+    }
+
+    /// Answer one request per status in `statuses` (then close), counting requests
+    fn serve_statuses(
+        statuses: &'static [&'static str],
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::BufRead;
+        use std::net::TcpListener;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sde.zip", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = requests.clone();
+        std::thread::spawn(move || {
+            for (stream, status) in listener.incoming().zip(statuses) {
+                let mut stream = stream.unwrap();
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    status
+                )
+                .unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    #[test]
+    fn test_retries_server_errors_but_not_client_errors() {
+        use std::sync::atomic::Ordering;
+
+        let mut client = SdeClient::new().unwrap();
+        client.retry_delay = Duration::from_millis(1);
+        let mut ui = crate::ui::SilentUi::new();
+
+        let (url, requests) = serve_statuses(&["503 Service Unavailable", "200 OK"]);
+        let response = client.send(|| client.client.get(&url), &mut ui).unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        let (url, requests) = serve_statuses(&["404 Not Found", "200 OK"]);
+        let response = client.send(|| client.client.get(&url), &mut ui).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        client.attempts = 2;
+        let (url, requests) = serve_statuses(&["500 Internal Server Error"; 3]);
+        let response = client.send(|| client.client.get(&url), &mut ui).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_verify_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("100.zip");
+        std::fs::write(&path, b"0123456789").unwrap();
+
+        assert!(verify_download(&path, Some(10)).is_ok());
+        assert!(verify_download(&path, None).is_ok());
+
+        let err = verify_download(&path, Some(16)).unwrap_err().to_string();
+        assert!(err.contains("10 bytes, expected 16"), "{}", err);
+        assert!(err.contains("--force"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_sde_info() {
+        let info = SdeInfo::parse(
+            r#"{"_key": "sde", "buildNumber": 3064089, "releaseDate": "2025-10-21T11:12:13+02:00"}"#,
+        )
+        .unwrap();
+        assert_eq!(info.build_number, 3064089);
+        assert_eq!(info.release_date, "2025-10-21T09:12:13Z");
+        assert!(info.extra.is_empty());
+        assert_eq!(info.metadata().len(), 2);
+        assert_eq!(info.describe_extra(), None);
+
+        let info = SdeInfo::parse(
+            r#"{"_key": "sde", "buildNumber": 3064089, "releaseDate": "2025-10-21", "releaseName": "Legion", "patchNotes": null, "hotfix": 2}"#,
+        )
+        .unwrap();
+        let metadata = info.metadata();
+        assert!(metadata.contains(&("releaseName".to_string(), "Legion".to_string())));
+        assert!(metadata.contains(&("hotfix".to_string(), "2".to_string())));
+        assert!(!metadata.iter().any(|(key, _)| key == "patchNotes"));
+        assert_eq!(
+            info.describe_extra().as_deref(),
+            Some("releaseName: Legion")
+        );
+    }
 }
@@ -1,50 +1,634 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{self, BufReader};
-use std::path::Path;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use zip::ZipArchive;
 
 use crate::ui::Ui;
+use crate::writer::{ReadProgress, SourceProvider};
 
-/// Extract a zip file to the destination directory
-pub fn extract_zip(zip_path: &Path, dest_dir: &Path, ui: &mut impl Ui) -> Result<()> {
-    let file = File::open(zip_path).context("Failed to open zip file")?;
-    let reader = BufReader::new(file);
-    let mut archive = ZipArchive::new(reader).context("Failed to read zip archive")?;
+/// Callback invoked for each archive entry with its path, contents and (done, total) progress
+pub type EntryVisitor<'a> = dyn FnMut(&str, &mut dyn Read, (u64, u64)) -> Result<()> + 'a;
 
-    fs::create_dir_all(dest_dir).context("Failed to create destination directory")?;
+/// An archive containing SDE JSONL files
+pub trait JsonlArchive {
+    /// Visit every entry in archive order
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<()>;
+}
 
-    let total_files = archive.len() as u64;
+/// Zip archive (the format CCP publishes)
+pub struct ZipJsonlArchive {
+    archive: ZipArchive<BufReader<File>>,
+}
 
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .context("Failed to read file from archive")?;
+impl ZipJsonlArchive {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).context("Failed to open zip file")?;
+        let archive =
+            ZipArchive::new(BufReader::new(file)).context("Failed to read zip archive")?;
+        Ok(Self { archive })
+    }
+}
 
-        // Get the file name, stripping any directory prefix
-        let name = file.name();
-        let file_name = Path::new(name)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or(name)
-            .to_string();
+impl JsonlArchive for ZipJsonlArchive {
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<()> {
+        let total = self.archive.len() as u64;
 
-        // Only extract .jsonl files
-        if !file_name.ends_with(".jsonl") {
-            ui.set_progress((i + 1) as u64, total_files, "Extracting files");
-            continue;
+        for i in 0..self.archive.len() {
+            let mut file = self
+                .archive
+                .by_index(i)
+                .context("Failed to read file from archive")?;
+            let name = file.name().to_string();
+            visit(&name, &mut file, (i as u64 + 1, total))?;
         }
 
-        let dest_path = dest_dir.join(&file_name);
-        let mut dest_file = File::create(&dest_path)
-            .with_context(|| format!("Failed to create file: {:?}", dest_path))?;
+        Ok(())
+    }
+}
 
-        io::copy(&mut file, &mut dest_file)
-            .with_context(|| format!("Failed to extract: {}", file_name))?;
+/// Gzip-compressed tarball, as used by some mirrors and archival snapshots.
+/// Progress is reported in compressed bytes read since the entry count is unknown up front.
+pub struct TarGzJsonlArchive {
+    archive: tar::Archive<GzDecoder<CountingReader<BufReader<File>>>>,
+    bytes_read: Rc<Cell<u64>>,
+    total_bytes: u64,
+}
 
-        ui.set_progress((i + 1) as u64, total_files, "Extracting files");
+impl TarGzJsonlArchive {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).context("Failed to open tar.gz file")?;
+        let total_bytes = file.metadata()?.len();
+        let bytes_read = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: BufReader::new(file),
+            count: Rc::clone(&bytes_read),
+        };
+
+        Ok(Self {
+            archive: tar::Archive::new(GzDecoder::new(reader)),
+            bytes_read,
+            total_bytes,
+        })
     }
+}
+
+impl JsonlArchive for TarGzJsonlArchive {
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<()> {
+        let entries = self
+            .archive
+            .entries()
+            .context("Failed to read tar.gz archive")?;
+
+        for entry in entries {
+            let mut entry = entry.context("Failed to read file from archive")?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let progress = (self.bytes_read.get(), self.total_bytes);
+            visit(&name, &mut entry, progress)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reader wrapper that tracks how many bytes have been read
+struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// Extract `.jsonl` entries from an archive into the destination directory,
+/// stripping any directory prefix from entry names (two entries with the same
+/// bare name are an error rather than one overwriting the other)
+pub fn extract_jsonl(
+    archive: &mut impl JsonlArchive,
+    dest_dir: &Path,
+    ui: &mut impl Ui,
+) -> Result<()> {
+    fs::create_dir_all(dest_dir).context("Failed to create destination directory")?;
+    let mut claimed = HashMap::new();
+
+    archive.for_each_entry(&mut |name, reader, (done, total)| {
+        if let Some(file_name) = jsonl_file_name(name) {
+            claim_file_name(&mut claimed, file_name, name)?;
+            write_entry(reader, dest_dir, file_name)?;
+        }
+
+        ui.set_progress(done, total, "Extracting files");
+        Ok(())
+    })?;
 
     ui.log("Extraction complete");
     Ok(())
 }
+
+/// Extract `.jsonl` entries, writing the files named in `priority` first and in that order.
+/// `on_extracted` is called with each file name once it is fully written.
+/// Zip archives honor the priority; tar.gz archives can only be read sequentially.
+pub fn extract_jsonl_prioritized(
+    archive_path: &Path,
+    dest_dir: &Path,
+    priority: &[String],
+    mut on_extracted: impl FnMut(&str),
+) -> Result<()> {
+    fs::create_dir_all(dest_dir).context("Failed to create destination directory")?;
+
+    match ArchiveKind::detect(archive_path)? {
+        ArchiveKind::Zip => {
+            let mut zip = ZipJsonlArchive::open(archive_path)?;
+
+            // Map bare file names to archive indices
+            let mut claimed = HashMap::new();
+            let mut indices: Vec<(String, usize)> = Vec::new();
+            for i in 0..zip.archive.len() {
+                let Some(name) = zip.archive.name_for_index(i) else {
+                    continue;
+                };
+                if let Some(file_name) = jsonl_file_name(name) {
+                    claim_file_name(&mut claimed, file_name, name)?;
+                    indices.push((file_name.to_string(), i));
+                }
+            }
+            indices.sort_by_key(|(name, i)| {
+                let rank = priority.iter().position(|p| p == name);
+                (rank.unwrap_or(usize::MAX), *i)
+            });
+
+            for (file_name, i) in indices {
+                let mut entry = zip
+                    .archive
+                    .by_index(i)
+                    .context("Failed to read file from archive")?;
+                write_entry(&mut entry, dest_dir, &file_name)?;
+                on_extracted(&file_name);
+            }
+        }
+        ArchiveKind::TarGz => {
+            let mut claimed = HashMap::new();
+            TarGzJsonlArchive::open(archive_path)?.for_each_entry(&mut |name, reader, _| {
+                if let Some(file_name) = jsonl_file_name(name) {
+                    claim_file_name(&mut claimed, file_name, name)?;
+                    write_entry(reader, dest_dir, file_name)?;
+                    on_extracted(file_name);
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Bare names of the `.jsonl` files in an archive, without extracting them
+pub fn list_jsonl(archive_path: &Path) -> Result<HashSet<String>> {
+    let mut names = HashSet::new();
+
+    match ArchiveKind::detect(archive_path)? {
+        ArchiveKind::Zip => {
+            let zip = ZipJsonlArchive::open(archive_path)?;
+            for name in zip.archive.file_names() {
+                if let Some(file_name) = jsonl_file_name(name) {
+                    names.insert(file_name.to_string());
+                }
+            }
+        }
+        ArchiveKind::TarGz => {
+            TarGzJsonlArchive::open(archive_path)?.for_each_entry(&mut |name, _, _| {
+                if let Some(file_name) = jsonl_file_name(name) {
+                    names.insert(file_name.to_string());
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(names)
+}
+
+/// Number of chunks buffered between an archive reader thread and its consumer
+const STREAM_CHUNKS: usize = 16;
+
+/// Size of the chunks streamed out of an archive entry
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// SDE JSONL files read straight out of a zip or tar.gz archive, without extracting it
+/// (`convert --archive`). Each opened file is streamed from its own thread; for a
+/// tar.gz that thread decompresses the archive from the start up to the entry.
+pub struct ArchiveSource {
+    path: PathBuf,
+    kind: ArchiveKind,
+    /// Bare file name -> (entry name in the archive, uncompressed size)
+    entries: HashMap<String, (String, u64)>,
+}
+
+impl ArchiveSource {
+    /// Index the `.jsonl` entries of the archive at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let kind = ArchiveKind::detect(path)?;
+        let mut entries = HashMap::new();
+
+        match kind {
+            ArchiveKind::Zip => {
+                let mut zip = ZipJsonlArchive::open(path)?;
+                for i in 0..zip.archive.len() {
+                    let file = zip
+                        .archive
+                        .by_index(i)
+                        .context("Failed to read file from archive")?;
+                    if let Some(file_name) = jsonl_file_name(file.name()) {
+                        index_entry(&mut entries, file_name, file.name(), file.size())?;
+                    }
+                }
+            }
+            ArchiveKind::TarGz => {
+                let mut tar = TarGzJsonlArchive::open(path)?;
+                let archive_entries = tar
+                    .archive
+                    .entries()
+                    .context("Failed to read tar.gz archive")?;
+                for entry in archive_entries {
+                    let entry = entry.context("Failed to read file from archive")?;
+                    if !entry.header().entry_type().is_file() {
+                        continue;
+                    }
+                    let name = entry.path()?.to_string_lossy().into_owned();
+                    if let Some(file_name) = jsonl_file_name(&name) {
+                        index_entry(&mut entries, file_name, &name, entry.header().size()?)?;
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            kind,
+            entries,
+        })
+    }
+}
+
+impl SourceProvider for ArchiveSource {
+    fn has_source(&self, source_file: &str) -> bool {
+        self.entries.contains_key(source_file)
+    }
+
+    fn open(&self, source_file: &str) -> Result<(Box<dyn BufRead + Send>, ReadProgress)> {
+        let (entry_name, size) = self
+            .entries
+            .get(source_file)
+            .with_context(|| format!("{} not found in {:?}", source_file, self.path))?;
+
+        let (tx, chunks) = sync_channel(STREAM_CHUNKS);
+        let (path, kind, entry_name) = (self.path.clone(), self.kind, entry_name.clone());
+        std::thread::spawn(move || {
+            if let Err(e) = stream_entry(&path, kind, &entry_name, &tx) {
+                tx.send(Err(io::Error::other(format!("{:#}", e)))).ok();
+            }
+        });
+
+        let reader = ChannelReader {
+            chunks,
+            chunk: Vec::new(),
+            pos: 0,
+        };
+        let (reader, progress) = ReadProgress::track(reader, *size);
+        Ok((Box::new(BufReader::new(reader)), progress))
+    }
+}
+
+/// Send the contents of the archive entry `entry_name` over `tx` in chunks
+fn stream_entry(
+    path: &Path,
+    kind: ArchiveKind,
+    entry_name: &str,
+    tx: &SyncSender<io::Result<Vec<u8>>>,
+) -> Result<()> {
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = ZipJsonlArchive::open(path)?;
+            let mut file = zip
+                .archive
+                .by_name(entry_name)
+                .with_context(|| format!("Failed to read {} from archive", entry_name))?;
+            send_chunks(&mut file, tx)
+        }
+        ArchiveKind::TarGz => {
+            let mut tar = TarGzJsonlArchive::open(path)?;
+            let entries = tar
+                .archive
+                .entries()
+                .context("Failed to read tar.gz archive")?;
+            for entry in entries {
+                let mut entry = entry.context("Failed to read file from archive")?;
+                if entry.path()?.to_string_lossy() == entry_name {
+                    return send_chunks(&mut entry, tx);
+                }
+            }
+            bail!("{} not found in {:?}", entry_name, path)
+        }
+    }
+}
+
+/// Copy `reader` into `tx`, stopping early once the receiving side hangs up
+fn send_chunks(reader: &mut dyn Read, tx: &SyncSender<io::Result<Vec<u8>>>) -> Result<()> {
+    let mut buf = vec![0u8; STREAM_CHUNK_SIZE];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 || tx.send(Ok(buf[..n].to_vec())).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Reader over the chunks sent by an archive reader thread
+struct ChannelReader {
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+                // The sender is done with the entry
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Bare file name of a `.jsonl` archive entry, `None` for other entries
+fn jsonl_file_name(name: &str) -> Option<&str> {
+    let file_name = Path::new(name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(name);
+    file_name.ends_with(".jsonl").then_some(file_name)
+}
+
+/// Record that archive entry `entry_name` is written as `file_name`, failing if another
+/// entry already is (e.g. `a/types.jsonl` and `b/types.jsonl`) since one would clobber the other
+fn claim_file_name(
+    claimed: &mut HashMap<String, String>,
+    file_name: &str,
+    entry_name: &str,
+) -> Result<()> {
+    if let Some(previous) = claimed.get(file_name) {
+        bail!(duplicate_entry_message(file_name, previous, entry_name));
+    }
+    claimed.insert(file_name.to_string(), entry_name.to_string());
+    Ok(())
+}
+
+/// Add an entry to an `ArchiveSource` index, failing on a duplicate bare file name
+fn index_entry(
+    entries: &mut HashMap<String, (String, u64)>,
+    file_name: &str,
+    entry_name: &str,
+    size: u64,
+) -> Result<()> {
+    if let Some((previous, _)) = entries.get(file_name) {
+        bail!(duplicate_entry_message(file_name, previous, entry_name));
+    }
+    entries.insert(file_name.to_string(), (entry_name.to_string(), size));
+    Ok(())
+}
+
+fn duplicate_entry_message(file_name: &str, first: &str, second: &str) -> String {
+    format!(
+        "Archive contains more than one {}: {} and {}",
+        file_name, first, second
+    )
+}
+
+/// Write an entry to `dest_dir/file_name`, via a temporary file so readers never see partial data
+fn write_entry(reader: &mut dyn Read, dest_dir: &Path, file_name: &str) -> Result<()> {
+    let dest_path = dest_dir.join(file_name);
+    let part_path = dest_dir.join(format!("{}.part", file_name));
+
+    let mut dest_file = File::create(&part_path)
+        .with_context(|| format!("Failed to create file: {:?}", part_path))?;
+    io::copy(reader, &mut dest_file)
+        .with_context(|| format!("Failed to extract: {}", file_name))?;
+    fs::rename(&part_path, &dest_path)
+        .with_context(|| format!("Failed to create file: {:?}", dest_path))?;
+
+    Ok(())
+}
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Detect the archive format from magic bytes, falling back to the file extension
+    pub fn detect(path: &Path) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        let mut file = File::open(path).with_context(|| format!("Failed to open: {:?}", path))?;
+        let n = file.read(&mut magic)?;
+
+        if n >= 4 && magic == *b"PK\x03\x04" {
+            return Ok(ArchiveKind::Zip);
+        }
+        if n >= 2 && magic[..2] == [0x1f, 0x8b] {
+            return Ok(ArchiveKind::TarGz);
+        }
+
+        let name = path.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Ok(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(ArchiveKind::TarGz)
+        } else {
+            bail!("Unsupported archive format: {:?}", path)
+        }
+    }
+}
+
+/// Extract a zip or tar.gz archive to the destination directory
+pub fn extract_archive(archive_path: &Path, dest_dir: &Path, ui: &mut impl Ui) -> Result<()> {
+    match ArchiveKind::detect(archive_path)? {
+        ArchiveKind::Zip => extract_zip(archive_path, dest_dir, ui),
+        ArchiveKind::TarGz => extract_tar_gz(archive_path, dest_dir, ui),
+    }
+}
+
+/// Extract a zip file to the destination directory
+pub fn extract_zip(zip_path: &Path, dest_dir: &Path, ui: &mut impl Ui) -> Result<()> {
+    extract_jsonl(&mut ZipJsonlArchive::open(zip_path)?, dest_dir, ui)
+}
+
+/// Extract a tar.gz file to the destination directory
+pub fn extract_tar_gz(tar_gz_path: &Path, dest_dir: &Path, ui: &mut impl Ui) -> Result<()> {
+    extract_jsonl(&mut TarGzJsonlArchive::open(tar_gz_path)?, dest_dir, ui)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::SilentUi;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    const ENTRIES: &[(&str, &str)] = &[
+        ("sde/types.jsonl", "{\"_key\": 1}\n"),
+        ("sde/readme.txt", "not data"),
+        ("groups.jsonl", "{\"_key\": 2}\n"),
+    ];
+
+    fn assert_extracted(dest: &Path) {
+        assert_eq!(
+            fs::read_to_string(dest.join("types.jsonl")).unwrap(),
+            "{\"_key\": 1}\n"
+        );
+        assert!(dest.join("groups.jsonl").exists());
+        assert!(!dest.join("readme.txt").exists());
+    }
+
+    fn assert_listed(archive_path: &Path) {
+        let mut names: Vec<String> = list_jsonl(archive_path).unwrap().into_iter().collect();
+        names.sort();
+        assert_eq!(names, vec!["groups.jsonl", "types.jsonl"]);
+    }
+
+    fn assert_read_in_place(archive_path: &Path) {
+        let source = ArchiveSource::open(archive_path).unwrap();
+        assert!(source.has_source("types.jsonl"));
+        assert!(!source.has_source("readme.txt"));
+        assert!(source.open("missing.jsonl").is_err());
+
+        let (mut reader, progress) = source.open("types.jsonl").unwrap();
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "{\"_key\": 1}\n");
+        assert_eq!(progress.read(), progress.total());
+    }
+
+    #[test]
+    fn test_extract_zip_archive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive_path = tmp.path().join("sde.bin");
+
+        let mut writer = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        for (name, contents) in ENTRIES {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            ArchiveKind::detect(&archive_path).unwrap(),
+            ArchiveKind::Zip
+        );
+
+        assert_listed(&archive_path);
+        assert_read_in_place(&archive_path);
+        let dest = tmp.path().join("out");
+        extract_archive(&archive_path, &dest, &mut SilentUi::new()).unwrap();
+        assert_extracted(&dest);
+
+        let dest = tmp.path().join("prioritized");
+        let mut order = Vec::new();
+        let priority = vec!["groups.jsonl".to_string()];
+        extract_jsonl_prioritized(&archive_path, &dest, &priority, |name| {
+            order.push(name.to_string())
+        })
+        .unwrap();
+        assert_eq!(order, vec!["groups.jsonl", "types.jsonl"]);
+        assert_extracted(&dest);
+    }
+
+    #[test]
+    fn test_duplicate_bare_names_are_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive_path = tmp.path().join("sde.zip");
+
+        let mut writer = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        for (name, contents) in [
+            ("foo/types.jsonl", "{\"_key\": 1}\n"),
+            ("bar/types.jsonl", "{\"_key\": 2}\n"),
+        ] {
+            writer
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let dest = tmp.path().join("out");
+        let err = extract_archive(&archive_path, &dest, &mut SilentUi::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Archive contains more than one types.jsonl: foo/types.jsonl and bar/types.jsonl"
+        );
+
+        let err = extract_jsonl_prioritized(&archive_path, &dest, &[], |_| {}).unwrap_err();
+        assert!(err.to_string().contains("more than one types.jsonl"));
+        assert!(ArchiveSource::open(&archive_path).is_err());
+    }
+
+    #[test]
+    fn test_extract_tar_gz_archive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive_path = tmp.path().join("sde.bin");
+
+        let encoder = GzEncoder::new(
+            File::create(&archive_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for (name, contents) in ENTRIES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        assert_eq!(
+            ArchiveKind::detect(&archive_path).unwrap(),
+            ArchiveKind::TarGz
+        );
+
+        assert_listed(&archive_path);
+        assert_read_in_place(&archive_path);
+        let dest = tmp.path().join("out");
+        extract_archive(&archive_path, &dest, &mut SilentUi::new()).unwrap();
+        assert_extracted(&dest);
+    }
+}
@@ -6,24 +6,91 @@ pub use cache::*;
 pub use client::*;
 pub use extract::*;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::path::PathBuf;
 
 use crate::ui::{Phase, Ui};
 
-/// Download the SDE if not cached, return path to extracted directory
-pub fn ensure_sde_downloaded(
-    cache_dir: Option<PathBuf>,
+/// The latest SDE build, either already extracted in the cache or
+/// downloaded as an archive that still needs extracting
+pub struct FetchedSde {
+    pub info: SdeInfo,
+    /// Directory the build is (or will be) extracted to
+    pub build_dir: PathBuf,
+    /// Downloaded archive, if the build wasn't already cached
+    pub archive: Option<PathBuf>,
+}
+
+/// Check for the latest SDE build and download its archive unless it is cached
+/// (see `latest_or_cached_build` for `offline`)
+pub fn fetch_sde(
+    cache: &CacheManager,
     force: bool,
+    offline: bool,
     ui: &mut impl Ui,
-) -> Result<(PathBuf, u64)> {
-    let cache = CacheManager::new(cache_dir)?;
+) -> Result<FetchedSde> {
+    let info = latest_or_cached_build(cache, force, offline, ui)?;
+    fetch_build(cache, info, force, ui)
+}
+
+/// The latest SDE build, or the newest cached one when `offline` or when the server
+/// can't be reached (unless `force`, which needs a download anyway)
+pub fn latest_or_cached_build(
+    cache: &CacheManager,
+    force: bool,
+    offline: bool,
+    ui: &mut impl Ui,
+) -> Result<SdeInfo> {
+    if offline {
+        let build = cache.latest_cached_build().with_context(|| {
+            format!(
+                "--offline needs a cached SDE build in {:?}",
+                cache.cache_dir()
+            )
+        })?;
+        ui.log(format!("Offline: using cached build {}", build));
+        return Ok(cached_build_info(cache, build));
+    }
+
+    match check_latest_build(ui) {
+        Ok(info) => Ok(info),
+        Err(e) => match cache.latest_cached_build().filter(|_| !force) {
+            Some(build) => {
+                ui.warn(format!(
+                    "{:#}; using cached build {}, which may be out of date",
+                    e, build
+                ));
+                Ok(cached_build_info(cache, build))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Build info of a cached build, read from the `_sde.jsonl` file the SDE ships with.
+/// Older builds without it only get their build number.
+fn cached_build_info(cache: &CacheManager, build_number: u64) -> SdeInfo {
+    let path = cache.build_dir(build_number).join("_sde.jsonl");
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| SdeInfo::parse(text.lines().next()?).ok())
+        .filter(|info| info.build_number == build_number)
+        .unwrap_or_else(|| SdeInfo {
+            key: "sde".to_string(),
+            build_number,
+            release_date: "unknown".to_string(),
+            extra: Default::default(),
+        })
+}
+
+/// Fetch information about the latest SDE build
+pub fn check_latest_build(ui: &mut impl Ui) -> Result<SdeInfo> {
     let client = SdeClient::new()?;
 
     // Get latest build info
     ui.set_phase(Phase::Checking);
     ui.log("Checking for latest SDE version...");
-    let info = client.fetch_latest_info()?;
+    let info = client.fetch_latest_info(ui)?;
     ui.set_info(format!(
         "Build {} ({})",
         info.build_number, info.release_date
@@ -32,31 +99,178 @@ pub fn ensure_sde_downloaded(
         "Latest build: {} ({})",
         info.build_number, info.release_date
     ));
+    if let Some(extra) = info.describe_extra() {
+        ui.log(format!("Release info: {}", extra));
+    }
+
+    Ok(info)
+}
 
+/// Download the archive for a build unless it is already cached
+pub fn fetch_build(
+    cache: &CacheManager,
+    info: SdeInfo,
+    force: bool,
+    ui: &mut impl Ui,
+) -> Result<FetchedSde> {
     let build_dir = cache.build_dir(info.build_number);
 
     // Check if already cached
     if !force && cache.is_cached(info.build_number) {
         ui.log(format!("Using cached SDE from {:?}", build_dir));
-        return Ok((build_dir, info.build_number));
+        return Ok(FetchedSde {
+            info,
+            build_dir,
+            archive: None,
+        });
     }
 
     // Download zip
     ui.set_phase(Phase::Downloading);
     let zip_path = cache.zip_path(info.build_number);
+    if force {
+        // Don't resume a partial download that may be what failed verification
+        std::fs::remove_file(partial_path(&zip_path)).ok();
+    }
     ui.log(format!("Downloading SDE build {}...", info.build_number));
-    client.download_zip(&zip_path, ui)?;
+    SdeClient::new()?.download_zip(&zip_path, ui)?;
 
-    // Extract zip
-    ui.set_phase(Phase::Extracting);
-    ui.log(format!("Extracting to {:?}...", build_dir));
-    extract_zip(&zip_path, &build_dir, ui)?;
+    Ok(FetchedSde {
+        info,
+        build_dir,
+        archive: Some(zip_path),
+    })
+}
 
+/// Remove the downloaded archive (unless `retention.keep_zip`) and prune old builds
+/// after a fresh extraction
+pub fn finish_download(
+    cache: &CacheManager,
+    fetched: &FetchedSde,
+    retention: &RetentionPolicy,
+    ui: &mut impl Ui,
+) {
     // Clean up zip file
-    std::fs::remove_file(&zip_path).ok();
+    if let Some(archive) = &fetched.archive {
+        if retention.keep_zip {
+            ui.log(format!("Kept SDE zip at {:?}", archive));
+        } else {
+            std::fs::remove_file(archive).ok();
+        }
+    }
 
-    // Clean up old builds
-    cache.cleanup_old_builds(info.build_number).ok();
+    prune_cache(cache, fetched.info.build_number, retention, ui);
+}
 
-    Ok((build_dir, info.build_number))
+/// Remove old builds according to `retention`, keeping `current_build`
+fn prune_cache(
+    cache: &CacheManager,
+    current_build: u64,
+    retention: &RetentionPolicy,
+    ui: &mut impl Ui,
+) {
+    match cache.apply_retention(retention, current_build) {
+        Ok(removed) if !removed.is_empty() => {
+            ui.log(format!("Removed {} old cached build(s)", removed.len()))
+        }
+        Ok(_) => {}
+        Err(e) => ui.warn(format!("Cache cleanup failed: {}", e)),
+    }
+}
+
+/// Download the latest SDE zip into `output_dir` (the cache by default) without
+/// extracting it, returning the zip's path. A complete zip of the build already there
+/// is reused unless `force`. Old builds are only pruned from the default cache.
+#[tracing::instrument(skip(retention, ui))]
+pub fn download_only(
+    output_dir: Option<PathBuf>,
+    force: bool,
+    retention: &RetentionPolicy,
+    ui: &mut impl Ui,
+) -> Result<(PathBuf, SdeInfo)> {
+    let custom_output = output_dir.is_some();
+    let cache = CacheManager::new(output_dir)?;
+    let info = check_latest_build(ui)?;
+    let zip_path = cache.zip_path(info.build_number);
+
+    if !force && zip_path.is_file() {
+        ui.log(format!("Using existing SDE zip {:?}", zip_path));
+    } else {
+        ui.set_phase(Phase::Downloading);
+        if force {
+            std::fs::remove_file(partial_path(&zip_path)).ok();
+        }
+        ui.log(format!("Downloading SDE build {}...", info.build_number));
+        SdeClient::new()?.download_zip(&zip_path, ui)?;
+    }
+
+    prune_download_dir(&cache, custom_output, info.build_number, retention, ui);
+    Ok((zip_path, info))
+}
+
+/// Prune old builds after `download_only`, unless the zip went to a custom `--output`
+/// directory: older archives there were put there by the user, not by the cache
+fn prune_download_dir(
+    cache: &CacheManager,
+    custom_output: bool,
+    current_build: u64,
+    retention: &RetentionPolicy,
+    ui: &mut impl Ui,
+) {
+    if !custom_output {
+        prune_cache(cache, current_build, retention, ui);
+    }
+}
+
+/// Download the SDE if not cached, return path to extracted directory
+#[tracing::instrument(skip(retention, ui))]
+pub fn ensure_sde_downloaded(
+    cache_dir: Option<PathBuf>,
+    force: bool,
+    offline: bool,
+    retention: &RetentionPolicy,
+    ui: &mut impl Ui,
+) -> Result<(PathBuf, SdeInfo)> {
+    let cache = CacheManager::new(cache_dir)?;
+    let fetched = fetch_sde(&cache, force, offline, ui)?;
+    tracing::debug!(
+        build = fetched.info.build_number,
+        build_dir = ?fetched.build_dir,
+        archive = ?fetched.archive,
+        "fetched SDE"
+    );
+
+    if let Some(archive) = &fetched.archive {
+        // Extract zip
+        ui.set_phase(Phase::Extracting);
+        ui.log(format!("Extracting to {:?}...", fetched.build_dir));
+        extract_archive(archive, &fetched.build_dir, ui)?;
+
+        finish_download(&cache, &fetched, retention, ui);
+    }
+
+    Ok((fetched.build_dir, fetched.info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::SilentUi;
+
+    #[test]
+    fn test_download_only_keeps_archives_in_custom_output() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = CacheManager::new(Some(tmp.path().to_path_buf())).unwrap();
+        std::fs::write(cache.zip_path(100), b"older").unwrap();
+        std::fs::write(cache.zip_path(200), b"latest").unwrap();
+        let retention = RetentionPolicy::default();
+
+        prune_download_dir(&cache, true, 200, &retention, &mut SilentUi::new());
+        assert!(cache.zip_path(100).exists());
+
+        // The default cache is pruned as usual
+        prune_download_dir(&cache, false, 200, &retention, &mut SilentUi::new());
+        assert!(!cache.zip_path(100).exists());
+        assert!(cache.zip_path(200).exists());
+    }
 }
@@ -1,42 +1,296 @@
-use crate::schema::{DependencyResolver, TableSchema};
+use crate::cli::TableArgs;
+use crate::schema::{get_table, table_names, DependencyResolver, TableSchema};
+use crate::ui::Ui;
 use anyhow::{anyhow, bail, Result};
 
+/// Curated tables selected by `--only-core` (dependencies are added during resolution)
+pub const CORE_TABLES: &[&str] = &[
+    "types",
+    "groups",
+    "categories",
+    "dogma_attributes",
+    "dogma_effects",
+    "type_dogma_attributes",
+    "type_dogma_effects",
+    "map_regions",
+    "map_constellations",
+    "map_solar_systems",
+    "market_groups",
+    "blueprints",
+    "blueprint_materials",
+    "blueprint_products",
+    "blueprint_skills",
+];
+
 /// Resolves which tables to process based on include/exclude filters
-pub fn resolve_tables(
-    include: Option<Vec<String>>,
-    exclude: Option<Vec<String>>,
-) -> Result<Vec<&'static TableSchema>> {
+pub fn resolve_tables(args: &TableArgs) -> Result<Vec<&'static TableSchema>> {
     let resolver = DependencyResolver::new();
 
-    match (include, exclude) {
+    let include = if args.only_core {
+        if args.include.is_some() || args.exclude.is_some() {
+            bail!("Cannot combine --only-core with --include or --exclude");
+        }
+        eprintln!("Using core table preset");
+        Some(CORE_TABLES.iter().map(|s| s.to_string()).collect())
+    } else {
+        args.include.clone()
+    };
+
+    match (include, args.exclude.clone()) {
         (Some(_), Some(_)) => {
             bail!("Cannot use both --include and --exclude at the same time");
         }
         (Some(include_list), None) => {
+            let include_list = expand_patterns(&include_list)?;
             let refs: Vec<&str> = include_list.iter().map(|s| s.as_str()).collect();
-            println!("Resolving dependencies for: {:?}", refs);
+            eprintln!("Resolving dependencies for: {:?}", refs);
             let tables = resolver.resolve_includes(&refs).map_err(|e| anyhow!(e))?;
 
-            println!("Including {} tables:", tables.len());
+            eprintln!("Including {} tables:", tables.len());
             for t in &tables {
-                println!("  - {}", t.name);
+                eprintln!("  - {}", t.name);
             }
 
             Ok(tables)
         }
         (None, Some(exclude_list)) => {
+            let exclude_list = expand_patterns(&exclude_list)?;
             let refs: Vec<&str> = exclude_list.iter().map(|s| s.as_str()).collect();
-            println!("Excluding tables: {:?}", refs);
+            eprintln!("Excluding tables: {:?}", refs);
             let tables = resolver.resolve_excludes(&refs).map_err(|e| anyhow!(e))?;
 
-            println!("Including {} tables (after exclusions):", tables.len());
+            eprintln!("Including {} tables (after exclusions):", tables.len());
 
             Ok(tables)
         }
         (None, None) => {
             let tables = resolver.all_tables_ordered();
-            println!("Including all {} tables", tables.len());
+            eprintln!("Including all {} tables", tables.len());
             Ok(tables)
         }
     }
 }
+
+/// Expand glob entries (`*` matches any run of characters, `?` one character) of an
+/// include/exclude list into the table names they match. Other entries are kept as
+/// given, so unknown names are still reported during resolution.
+pub fn expand_patterns(entries: &[String]) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+
+    for entry in entries {
+        if !is_pattern(entry) {
+            names.push(entry.clone());
+            continue;
+        }
+
+        let matched = matching_tables(entry);
+        if matched.is_empty() {
+            bail!("pattern '{}' matched no tables", entry);
+        }
+        names.extend(matched.into_iter().map(String::from));
+    }
+
+    Ok(names)
+}
+
+fn is_pattern(entry: &str) -> bool {
+    entry.contains(['*', '?'])
+}
+
+/// Table names matching a glob pattern, in dependency order
+fn matching_tables(pattern: &str) -> Vec<&'static str> {
+    table_names()
+        .into_iter()
+        .filter(|name| glob_matches(pattern.as_bytes(), name.as_bytes()))
+        .collect()
+}
+
+fn glob_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| glob_matches(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && glob_matches(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && glob_matches(rest, &name[1..]),
+    }
+}
+
+/// Guard against accidentally large imports: if the resolved table set exceeds
+/// `--limit-tables`, ask for confirmation unless `--yes` was given
+pub fn check_table_limit(
+    tables: &[&TableSchema],
+    args: &TableArgs,
+    ui: &mut impl Ui,
+) -> Result<()> {
+    let limit = match args.limit_tables {
+        Some(limit) if tables.len() > limit && !args.yes => limit,
+        _ => return Ok(()),
+    };
+
+    let prompt = format!(
+        "About to import {} tables (limit {}), continue?",
+        tables.len(),
+        limit
+    );
+    if ui.confirm(prompt) {
+        return Ok(());
+    }
+
+    bail!(
+        "Selected {} tables, more than --limit-tables {}; narrow the selection or pass --yes",
+        tables.len(),
+        limit
+    )
+}
+
+/// Explicitly included tables whose source file must exist (`--require-requested`).
+/// Dependencies pulled in automatically are not required.
+pub fn requested_sources(args: &TableArgs) -> Vec<&'static TableSchema> {
+    match (&args.include, args.require_requested) {
+        (Some(include), true) => include
+            .iter()
+            .flat_map(|entry| {
+                if is_pattern(entry) {
+                    matching_tables(entry)
+                } else {
+                    vec![entry.as_str()]
+                }
+            })
+            .filter_map(get_table)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Fail if any of the requested tables has no source file
+pub fn check_sources_present(
+    requested: &[&TableSchema],
+    has_source: impl Fn(&str) -> bool,
+) -> Result<()> {
+    let missing: Vec<String> = requested
+        .iter()
+        .filter(|t| !has_source(t.source_file))
+        .map(|t| format!("{} ({})", t.name, t.source_file))
+        .collect();
+
+    if !missing.is_empty() {
+        bail!(
+            "Requested tables have no source file: {}",
+            missing.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_limit() {
+        let tables: Vec<_> = crate::schema::ALL_TABLES.to_vec();
+        let mut ui = crate::ui::SilentUi::new();
+
+        let unlimited = TableArgs::default();
+        assert!(check_table_limit(&tables, &unlimited, &mut ui).is_ok());
+
+        let limited = TableArgs {
+            limit_tables: Some(10),
+            ..Default::default()
+        };
+        assert!(check_table_limit(&tables, &limited, &mut ui).is_err());
+        assert!(check_table_limit(&tables[..10], &limited, &mut ui).is_ok());
+
+        let confirmed = TableArgs {
+            yes: true,
+            ..limited
+        };
+        assert!(check_table_limit(&tables, &confirmed, &mut ui).is_ok());
+    }
+
+    #[test]
+    fn test_core_tables_exist() {
+        for name in CORE_TABLES {
+            assert!(get_table(name).is_some(), "Unknown core table: {}", name);
+        }
+    }
+
+    #[test]
+    fn test_only_core_resolves_dependencies() {
+        let args = TableArgs {
+            only_core: true,
+            ..Default::default()
+        };
+        let tables = resolve_tables(&args).unwrap();
+        let names: Vec<&str> = tables.iter().map(|t| t.name).collect();
+
+        for name in CORE_TABLES {
+            assert!(names.contains(name));
+        }
+        assert!(names.len() < crate::schema::ALL_TABLES.len());
+
+        let args = TableArgs {
+            only_core: true,
+            include: Some(vec!["types".to_string()]),
+            ..Default::default()
+        };
+        assert!(resolve_tables(&args).is_err());
+    }
+
+    #[test]
+    fn test_glob_patterns_expand_to_tables() {
+        let expanded = expand_patterns(&["map_s*".to_string(), "types".to_string()]).unwrap();
+        assert_eq!(
+            expanded,
+            vec![
+                "map_solar_systems",
+                "map_stars",
+                "map_stargates",
+                "map_secondary_suns",
+                "types"
+            ]
+        );
+        assert_eq!(matching_tables("?cons"), vec!["icons"]);
+        assert!(glob_matches(b"*_modifiers", b"dbuff_item_modifiers"));
+        assert!(!glob_matches(b"map_?", b"map_moons"));
+
+        let err = expand_patterns(&["foo_*".to_string()]).unwrap_err();
+        assert_eq!(err.to_string(), "pattern 'foo_*' matched no tables");
+
+        // Literal names pass through; resolution reports unknown ones
+        let args = TableArgs {
+            exclude: Some(vec!["map_*".to_string()]),
+            ..Default::default()
+        };
+        let tables = resolve_tables(&args).unwrap();
+        assert!(tables.iter().all(|t| !t.name.starts_with("map_")));
+
+        let args = TableArgs {
+            include: Some(vec!["no_such_table".to_string()]),
+            ..Default::default()
+        };
+        assert!(resolve_tables(&args).is_err());
+    }
+
+    #[test]
+    fn test_requested_sources_must_exist() {
+        let args = TableArgs {
+            include: Some(vec!["types".to_string()]),
+            ..Default::default()
+        };
+        assert!(requested_sources(&args).is_empty());
+
+        let args = TableArgs {
+            require_requested: true,
+            ..args
+        };
+        let requested = requested_sources(&args);
+        let names: Vec<&str> = requested.iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["types"]);
+
+        assert!(check_sources_present(&requested, |f| f == "types.jsonl").is_ok());
+        // Dependencies such as groups.jsonl are not required
+        let err = check_sources_present(&requested, |f| f == "groups.jsonl").unwrap_err();
+        assert!(err.to_string().contains("types (types.jsonl)"));
+    }
+}
@@ -0,0 +1,83 @@
+//! Ctrl+C handling: remove the partial output of an interrupted run, then exit
+//!
+//! Files and directories that only exist while a run is in progress (the `<db>.tmp`
+//! database being built, a `<build>.partial` extraction directory) are registered as
+//! [`Artifact`]s for as long as they are incomplete.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Exit code of an interrupted run (128 + SIGINT, as shells report it)
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Paths of the artifacts currently alive
+static ARTIFACTS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// A file or directory removed if the run is interrupted while this guard is alive
+pub struct Artifact {
+    path: PathBuf,
+}
+
+impl Artifact {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        lock_artifacts().push(path.clone());
+        Self { path }
+    }
+}
+
+impl Drop for Artifact {
+    fn drop(&mut self) {
+        let mut artifacts = lock_artifacts();
+        if let Some(i) = artifacts.iter().position(|p| *p == self.path) {
+            artifacts.swap_remove(i);
+        }
+    }
+}
+
+fn lock_artifacts() -> std::sync::MutexGuard<'static, Vec<PathBuf>> {
+    // A panic while holding the lock leaves the list itself intact
+    ARTIFACTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Install a Ctrl+C handler that calls `exit_interrupted`.
+///
+/// The TUI puts the terminal in raw mode, where Ctrl+C arrives as a key press instead
+/// of a signal; `UiApp` checks for it on every redraw.
+pub fn install_handler() -> Result<()> {
+    ctrlc::set_handler(|| exit_interrupted()).context("Failed to install Ctrl+C handler")
+}
+
+/// Remove every live artifact and exit with `EXIT_INTERRUPTED`
+pub fn exit_interrupted() -> ! {
+    for path in std::mem::take(&mut *lock_artifacts()) {
+        remove_artifact(&path);
+    }
+
+    eprintln!("Interrupted; removed partial output");
+    std::process::exit(EXIT_INTERRUPTED)
+}
+
+/// Best-effort removal: the run is being abandoned either way
+fn remove_artifact(path: &Path) {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path).ok();
+    } else if path.exists() {
+        std::fs::remove_file(path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_is_registered_while_alive() {
+        let path = PathBuf::from("/nonexistent/test_artifact.db.tmp");
+        let artifact = Artifact::new(&path);
+        assert!(lock_artifacts().contains(&path));
+        drop(artifact);
+        assert!(!lock_artifacts().contains(&path));
+    }
+}
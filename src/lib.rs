@@ -1,7 +1,9 @@
 pub mod cli;
 pub mod download;
 pub mod filter;
+pub mod interrupt;
 pub mod parser;
+pub mod pipeline;
 pub mod schema;
 pub mod ui;
 pub mod writer;
@@ -1,159 +1,700 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use eve_sde_to_sqlite::{
-    cli::{Cli, Commands},
-    download::ensure_sde_downloaded,
-    filter::resolve_tables,
-    schema::table_names,
+    cli::{Cli, Commands, MergeArgs, TableArgs},
+    download::{download_only, ensure_sde_downloaded, ArchiveSource, RetentionPolicy, SdeInfo},
+    filter::{check_sources_present, check_table_limit, requested_sources, resolve_tables},
+    interrupt,
+    pipeline::{sync_pipelined, CacheSettings, SyncOutcome},
+    schema::{table_names, validate_schema, TableSchema, ALL_TABLES},
     ui::{Phase, SilentUi, Ui, UiApp},
-    writer::convert_to_sqlite,
+    writer::{
+        check_foreign_keys, check_row_counts, column_definitions, convert_from_source,
+        convert_to_duckdb, convert_to_parquet, diff_databases, export_table, generate_create_table,
+        generate_indexes, merge_into, read_meta, ConvertOptions, OutputFormat, SourceProvider,
+    },
 };
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::ExitCode;
 use std::time::Instant;
 
-fn main() -> Result<()> {
+/// Exit codes are a stable contract for automation:
+/// - 0: success
+/// - 1: fatal error
+/// - 2: completed with warnings (only with `--fail-on-warning`)
+/// - 3: `sync --since-build` found no newer build (only with `--exit-code-on-skip`)
+/// - 130: interrupted with Ctrl+C (`interrupt::EXIT_INTERRUPTED`)
+const EXIT_SUCCESS: u8 = 0;
+const EXIT_FAILURE: u8 = 1;
+const EXIT_WARNINGS: u8 = 2;
+const EXIT_SKIPPED: u8 = 3;
+
+/// What a sync or convert run produced, printed to stdout once the UI has closed
+struct Outcome {
+    /// Human-readable summary sentence
+    message: String,
+    json: JsonSummary,
+}
+
+/// The `--json-summary` line
+#[derive(Serialize)]
+struct JsonSummary {
+    output: PathBuf,
+    records: u64,
+    /// SDE build number (`null` for `convert`)
+    build: Option<u64>,
+    elapsed_secs: f64,
+    tables: usize,
+    /// `false` when `--since-build` found no newer build and nothing was written
+    converted: bool,
+}
+
+impl Outcome {
+    fn print(&self, json: bool) -> Result<()> {
+        if json {
+            println!("{}", serde_json::to_string(&self.json)?);
+        } else {
+            println!("{}", self.message);
+        }
+        Ok(())
+    }
+}
+
+/// How a command that did not fail ended
+struct RunSummary {
+    /// Warnings emitted along the way
+    warnings: u64,
+    /// Nothing was done because no newer build exists and `--exit-code-on-skip` was given
+    skipped: bool,
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse_args();
+    let fail_on_warning = cli.fail_on_warning;
+    init_tracing(cli.verbose);
+
+    if let Err(err) = interrupt::install_handler() {
+        eprintln!("Error: {:?}", err);
+        return ExitCode::from(EXIT_FAILURE);
+    }
+
+    if let Err(errors) = validate_schema() {
+        for error in errors {
+            eprintln!("Schema error: {}", error);
+        }
+        return ExitCode::from(EXIT_FAILURE);
+    }
 
-    match cli.command {
+    match run(cli) {
+        Ok(RunSummary { skipped: true, .. }) => ExitCode::from(EXIT_SKIPPED),
+        Ok(RunSummary { warnings: 0, .. }) => ExitCode::from(EXIT_SUCCESS),
+        Ok(RunSummary { warnings, .. }) => {
+            eprintln!("Completed with {} warning(s)", warnings);
+            if fail_on_warning {
+                ExitCode::from(EXIT_WARNINGS)
+            } else {
+                ExitCode::from(EXIT_SUCCESS)
+            }
+        }
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+/// Send `tracing` diagnostics to stderr at a level set by the number of `-v` flags
+fn init_tracing(verbose: u8) {
+    let level = match verbose {
+        0 => return,
+        1 => tracing::Level::INFO,
+        2 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Run the selected command
+fn run(cli: Cli) -> Result<RunSummary> {
+    let mut skipped = false;
+    let warnings = match cli.command {
         Commands::Sync {
             output_db,
-            include,
-            exclude,
+            tables,
             force,
             cache_dir,
+            offline,
+            retention,
+            keep_zip,
+            since_build,
+            exit_code_on_skip,
+            dry_run,
+            options,
+            merge,
         } => {
-            if cli.quiet {
+            let cache = CacheSettings {
+                cache_dir,
+                force,
+                offline,
+                retention: RetentionPolicy {
+                    keep_zip,
+                    ..retention.to_policy()
+                },
+            };
+            if dry_run {
+                // Resolve tables first so a bad selection fails before any download;
+                // the SDE is still fetched so source files can be checked
+                let plan = resolve_tables(&tables)?;
+                let (sde_dir, warnings) = if cli.quiet {
+                    let mut ui = SilentUi::new();
+                    let dir = fetch_for_plan(&mut ui, &cache)?;
+                    (dir, ui.warning_count())
+                } else {
+                    let mut ui = UiApp::new()?;
+                    let dir = fetch_for_plan(&mut ui, &cache)?;
+                    let warnings = ui.warning_count();
+                    ui.finish("Downloaded")?;
+                    (dir, warnings)
+                };
+                print_plan(&plan, &sde_dir, &sde_dir);
+                return Ok(RunSummary {
+                    warnings,
+                    skipped: false,
+                });
+            }
+            let since_build = resolve_since_build(since_build, &output_db)?;
+            let json_summary = options.json_summary;
+            let options = options.to_options();
+            let (outcome, warnings) = if cli.quiet {
                 let mut ui = SilentUi::new();
-                run_sync(&mut ui, output_db, include, exclude, force, cache_dir)?;
+                let outcome = run_sync(
+                    &mut ui,
+                    output_db,
+                    &tables,
+                    &cache,
+                    since_build,
+                    options,
+                    &merge,
+                )?;
+                (outcome, ui.warning_count())
             } else {
                 let mut ui = UiApp::new()?;
-                run_sync(
+                let outcome = run_sync(
                     &mut ui,
                     output_db.clone(),
-                    include,
-                    exclude,
-                    force,
-                    cache_dir,
+                    &tables,
+                    &cache,
+                    since_build,
+                    options,
+                    &merge,
                 )?;
-                ui.finish("Complete")?;
-            }
+                let warnings = ui.warning_count();
+                let converted = outcome.json.converted;
+                ui.finish(if converted { "Complete" } else { "Up to date" })?;
+                (outcome, warnings)
+            };
+            outcome.print(json_summary)?;
+            skipped = !outcome.json.converted && exit_code_on_skip;
+            warnings
         }
 
-        Commands::Download { output, force } => {
+        Commands::Download {
+            output,
+            force,
+            retention,
+        } => {
+            let cache = CacheSettings {
+                cache_dir: output,
+                force,
+                offline: false,
+                retention: retention.to_policy(),
+            };
             if cli.quiet {
                 let mut ui = SilentUi::new();
-                run_download(&mut ui, output, force)?;
+                run_download(&mut ui, &cache)?;
+                ui.warning_count()
             } else {
                 let mut ui = UiApp::new()?;
-                run_download(&mut ui, output, force)?;
+                run_download(&mut ui, &cache)?;
+                let warnings = ui.warning_count();
                 ui.finish("Complete")?;
+                warnings
             }
         }
 
         Commands::Convert {
             input_dir,
             output_db,
-            include,
-            exclude,
+            tables,
+            dry_run,
+            archive,
+            options,
+            merge,
         } => {
-            if cli.quiet {
+            let source: Box<dyn SourceProvider> = if archive {
+                Box::new(ArchiveSource::open(&input_dir)?)
+            } else {
+                Box::new(input_dir.clone())
+            };
+            if dry_run {
+                print_plan(&resolve_tables(&tables)?, &*source, &input_dir);
+                return Ok(RunSummary {
+                    warnings: 0,
+                    skipped: false,
+                });
+            }
+            let json_summary = options.json_summary;
+            let options = options.to_options();
+            let (outcome, warnings) = if cli.quiet {
                 let mut ui = SilentUi::new();
-                run_convert(&mut ui, input_dir, output_db, include, exclude)?;
+                let outcome = run_convert(&mut ui, &*source, output_db, &tables, options, &merge)?;
+                (outcome, ui.warning_count())
             } else {
                 let mut ui = UiApp::new()?;
-                run_convert(
+                let outcome = run_convert(
                     &mut ui,
-                    input_dir.clone(),
+                    &*source,
                     output_db.clone(),
-                    include,
-                    exclude,
+                    &tables,
+                    options,
+                    &merge,
                 )?;
+                let warnings = ui.warning_count();
                 ui.finish("Complete")?;
-            }
+                (outcome, warnings)
+            };
+            outcome.print(json_summary)?;
+            warnings
         }
 
-        Commands::ListTables => {
+        Commands::ListTables { long: false } => {
             println!("Available tables:\n");
             for name in table_names() {
                 println!("  {}", name);
             }
+            0
         }
-    }
 
-    Ok(())
+        Commands::ListTables { long: true } => {
+            print_table_details(ALL_TABLES);
+            0
+        }
+
+        Commands::PrintSchema { tables } => {
+            print_schema(&resolve_tables(&tables)?);
+            0
+        }
+
+        Commands::Export {
+            db,
+            table,
+            format,
+            output,
+        } => {
+            let count = export_table(&db, &table, format, &output)?;
+            println!("Exported {} rows from {} to {:?}", count, table, output);
+            0
+        }
+
+        Commands::Verify { db } => {
+            run_verify(&db)?;
+            0
+        }
+
+        Commands::Diff { old, new, table } => {
+            run_diff(&old, &new, table.as_deref())?;
+            0
+        }
+    };
+
+    Ok(RunSummary { warnings, skipped })
 }
 
+/// Sync the output database; the outcome is not `converted` if `since_build` found no
+/// newer build
 fn run_sync(
     ui: &mut impl Ui,
     output_db: std::path::PathBuf,
-    include: Option<Vec<String>>,
-    exclude: Option<Vec<String>>,
-    force: bool,
-    cache_dir: Option<std::path::PathBuf>,
-) -> Result<()> {
+    table_args: &TableArgs,
+    cache: &CacheSettings,
+    since_build: Option<u64>,
+    options: ConvertOptions,
+    merge: &MergeArgs,
+) -> Result<Outcome> {
     let start = Instant::now();
+    options.validate()?;
+    check_merge_target(&options, merge)?;
+    if options.output_format != OutputFormat::Sqlite {
+        bail!("sync only writes SQLite; download, then use `convert --output-format ...`");
+    }
 
-    // Download SDE if needed
-    let (input_dir, build_number) = ensure_sde_downloaded(cache_dir, force, ui)?;
-
-    // Resolve table filters
-    let tables = resolve_tables(include, exclude)?;
+    // Resolve table filters before downloading so mistakes fail fast
+    let tables = resolve_tables(table_args)?;
+    check_table_limit(&tables, table_args, ui)?;
     ui.log(format!("Selected {} tables for import", tables.len()));
 
-    // Convert to SQLite
-    ui.set_phase(Phase::Converting);
-    ui.log("Converting to SQLite...");
-    let record_count = convert_to_sqlite(&input_dir, &output_db, tables, ui)?;
+    // Download SDE if needed, importing tables as their files are extracted
+    let outcome = sync_pipelined(
+        cache,
+        since_build,
+        &output_db,
+        tables.clone(),
+        &requested_sources(table_args),
+        &options,
+        ui,
+    )?;
+    let (info, record_count) = match outcome {
+        SyncOutcome::Converted {
+            info,
+            total_records,
+        } => (info, total_records),
+        SyncOutcome::UpToDate { info, since_build } => {
+            let message = format!(
+                "No new build: latest SDE is {}, not newer than {}; {:?} left unchanged",
+                describe_build(&info),
+                since_build,
+                output_db
+            );
+            ui.log(&message);
+            return Ok(Outcome {
+                message,
+                json: JsonSummary {
+                    output: output_db,
+                    records: 0,
+                    build: Some(info.build_number),
+                    elapsed_secs: start.elapsed().as_secs_f64(),
+                    tables: tables.len(),
+                    converted: false,
+                },
+            });
+        }
+    };
+
+    merge_built_tables(&output_db, &tables, merge, ui)?;
+    check_expected_rows(&output_db, &options.row_expectations)?;
 
     let elapsed = start.elapsed();
-    let summary = format!(
-        "Created {:?} ({} records) from SDE build {} in {:.1}s",
+    let message = format!(
+        "Created {:?} ({} records) from SDE {} in {:.1}s",
         output_db,
         record_count,
-        build_number,
+        describe_build(&info),
         elapsed.as_secs_f64()
     );
-    ui.log(&summary);
-    println!("{}", summary);
+    ui.log(&message);
+
+    Ok(Outcome {
+        message,
+        json: JsonSummary {
+            output: output_db,
+            records: record_count,
+            build: Some(info.build_number),
+            elapsed_secs: elapsed.as_secs_f64(),
+            tables: tables.len(),
+            converted: true,
+        },
+    })
+}
+
+/// Print the DDL for `tables` in dependency order, as a script SQLite can run
+fn print_schema(tables: &[&TableSchema]) {
+    for schema in tables {
+        println!("{};", generate_create_table(schema));
+        for index_sql in generate_indexes(schema) {
+            println!("{};", index_sql);
+        }
+        println!();
+    }
+}
+
+/// Resolve `--since-build`: an explicit build number, or (without a value) the build
+/// recorded in the existing output database. `None` means always sync.
+fn resolve_since_build(
+    since_build: Option<Option<u64>>,
+    output_db: &std::path::Path,
+) -> Result<Option<u64>> {
+    match since_build {
+        None => Ok(None),
+        Some(Some(build)) => Ok(Some(build)),
+        Some(None) => match read_meta(output_db, "build_number")? {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .with_context(|| format!("Invalid build_number in {:?}: {}", output_db, value)),
+            None => Ok(None),
+        },
+    }
+}
+
+/// Fail early when `--merge-into` has no single built database to copy from
+fn check_merge_target(options: &ConvertOptions, merge: &MergeArgs) -> Result<()> {
+    if options.split_by_table && merge.merge_into.is_some() {
+        bail!("--merge-into cannot be combined with --split-by-table");
+    }
+    Ok(())
+}
 
+/// Copy the built tables into the `--merge-into` target, if one was given
+fn merge_built_tables(
+    output_db: &std::path::Path,
+    tables: &[&TableSchema],
+    merge: &MergeArgs,
+    ui: &mut impl Ui,
+) -> Result<()> {
+    if let Some(target) = &merge.merge_into {
+        ui.set_phase(Phase::Merging);
+        merge_into(output_db, target, tables, merge.on_conflict, ui)?;
+    }
     Ok(())
 }
 
-fn run_download(ui: &mut impl Ui, output: Option<std::path::PathBuf>, force: bool) -> Result<()> {
-    let (path, build_number) = ensure_sde_downloaded(output, force, ui)?;
-    let summary = format!("SDE build {} downloaded to {:?}", build_number, path);
+/// Build number, release date and any extra release info for summaries
+fn describe_build(info: &SdeInfo) -> String {
+    let mut details = info.release_date.clone();
+    if let Some(extra) = info.describe_extra() {
+        details = format!("{}, {}", details, extra);
+    }
+    format!("build {} ({})", info.build_number, details)
+}
+
+fn run_download(ui: &mut impl Ui, cache: &CacheSettings) -> Result<()> {
+    let (zip_path, info) =
+        download_only(cache.cache_dir.clone(), cache.force, &cache.retention, ui)?;
+    let summary = format!("SDE {} downloaded to {:?}", describe_build(&info), zip_path);
     ui.log(&summary);
     println!("{}", summary);
 
     Ok(())
 }
 
+/// Download and extract the SDE if needed, returning the directory of JSONL files
+fn fetch_for_plan(ui: &mut impl Ui, cache: &CacheSettings) -> Result<std::path::PathBuf> {
+    let (path, _) = ensure_sde_downloaded(
+        cache.cache_dir.clone(),
+        cache.force,
+        cache.offline,
+        &cache.retention,
+        ui,
+    )?;
+    Ok(path)
+}
+
+/// Print one line per table with its source file and the shape of its schema
+fn print_table_details(tables: &[&TableSchema]) {
+    let languages = ConvertOptions::default().languages();
+    let name_width = tables.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    let file_width = tables
+        .iter()
+        .map(|t| t.source_file.len())
+        .max()
+        .unwrap_or(0);
+
+    println!(
+        "{:<name_width$}  {:<file_width$}  COLUMNS  FKS  INDEXES  JUNCTION",
+        "TABLE", "SOURCE"
+    );
+    for schema in tables {
+        println!(
+            "{:<name_width$}  {:<file_width$}  {:>7}  {:>3}  {:>7}  {}",
+            schema.name,
+            schema.source_file,
+            column_definitions(schema, &languages).len(),
+            schema.foreign_keys.len(),
+            schema.indexes.len(),
+            if schema.array_source.is_some() {
+                "yes"
+            } else {
+                "no"
+            },
+        );
+    }
+}
+
+/// Print the tables that would be imported, in order, marking missing source files
+fn print_plan(tables: &[&TableSchema], source: &dyn SourceProvider, input_dir: &std::path::Path) {
+    let name_width = tables.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    let file_width = tables
+        .iter()
+        .map(|t| t.source_file.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut missing = 0;
+    for (i, schema) in tables.iter().enumerate() {
+        let marker = if source.has_source(schema.source_file) {
+            ""
+        } else {
+            missing += 1;
+            "MISSING"
+        };
+        let line = format!(
+            "{:>4}. {:<name_width$}  {:<file_width$}  {}",
+            i + 1,
+            schema.name,
+            schema.source_file,
+            marker,
+            name_width = name_width,
+            file_width = file_width
+        );
+        println!("{}", line.trim_end());
+    }
+
+    println!(
+        "\nWould import {} tables from {:?} ({} source file(s) missing)",
+        tables.len(),
+        input_dir,
+        missing
+    );
+}
+
 fn run_convert(
     ui: &mut impl Ui,
-    input_dir: std::path::PathBuf,
+    source: &dyn SourceProvider,
     output_db: std::path::PathBuf,
-    include: Option<Vec<String>>,
-    exclude: Option<Vec<String>>,
-) -> Result<()> {
+    table_args: &TableArgs,
+    options: ConvertOptions,
+    merge: &MergeArgs,
+) -> Result<Outcome> {
     let start = Instant::now();
+    check_merge_target(&options, merge)?;
 
     // Resolve table filters
-    let tables = resolve_tables(include, exclude)?;
+    let tables = resolve_tables(table_args)?;
+    check_table_limit(&tables, table_args, ui)?;
     ui.log(format!("Selected {} tables for import", tables.len()));
 
-    // Convert to SQLite
+    check_sources_present(&requested_sources(table_args), |f| source.has_source(f))?;
+
     ui.set_phase(Phase::Converting);
     ui.set_info(format!("Output: {:?}", output_db));
-    ui.log("Converting to SQLite...");
-    let record_count = convert_to_sqlite(&input_dir, &output_db, tables, ui)?;
+    let record_count = match options.output_format {
+        OutputFormat::Sqlite => {
+            ui.log("Converting to SQLite...");
+            let count = convert_from_source(source, &output_db, tables.clone(), &options, ui)?;
+            merge_built_tables(&output_db, &tables, merge, ui)?;
+            check_expected_rows(&output_db, &options.row_expectations)?;
+            count
+        }
+        OutputFormat::Parquet => {
+            if merge.merge_into.is_some() {
+                bail!("--merge-into needs SQLite output");
+            }
+            // The output path names a directory of .parquet files
+            ui.log("Converting to Parquet...");
+            convert_to_parquet(source, &output_db, tables.clone(), &options, ui)?
+        }
+        OutputFormat::Duckdb => {
+            if merge.merge_into.is_some() {
+                bail!("--merge-into needs SQLite output");
+            }
+            ui.log("Converting to DuckDB...");
+            convert_to_duckdb(source, &output_db, tables.clone(), &options, ui)?
+        }
+    };
 
     let elapsed = start.elapsed();
-    let summary = format!(
+    let message = format!(
         "Created {:?} ({} records) in {:.1}s",
         output_db,
         record_count,
         elapsed.as_secs_f64()
     );
-    ui.log(&summary);
-    println!("{}", summary);
+    ui.log(&message);
+
+    Ok(Outcome {
+        message,
+        json: JsonSummary {
+            output: output_db,
+            records: record_count,
+            build: None,
+            elapsed_secs: elapsed.as_secs_f64(),
+            tables: tables.len(),
+            converted: true,
+        },
+    })
+}
+
+/// Fail if any `--expect` table of the finished database has fewer rows than required
+fn check_expected_rows(db: &std::path::Path, expectations: &[(String, u64)]) -> Result<()> {
+    if expectations.is_empty() {
+        return Ok(());
+    }
+
+    let reports = check_row_counts(db, expectations)?;
+    let failed: Vec<String> = reports
+        .iter()
+        .filter(|r| !r.is_met())
+        .map(|r| {
+            format!(
+                "{}: {} rows, expected at least {}",
+                r.table, r.actual, r.expected
+            )
+        })
+        .collect();
+    if !failed.is_empty() {
+        bail!("Row count check failed:\n  {}", failed.join("\n  "));
+    }
+
+    Ok(())
+}
+
+fn run_verify(db: &std::path::Path) -> Result<()> {
+    if !db.is_file() {
+        bail!("Database not found: {:?}", db);
+    }
+
+    let reports = check_foreign_keys(db)?;
+    let labels: Vec<String> = reports
+        .iter()
+        .map(|r| format!("{}.{} -> {}", r.table, r.column, r.references_table))
+        .collect();
+    let width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+
+    for (label, report) in labels.iter().zip(&reports) {
+        println!("  {:<width$}  {}", label, report.orphans, width = width);
+    }
+
+    let orphans: u64 = reports.iter().map(|r| r.orphans).sum();
+    let broken = reports.iter().filter(|r| r.orphans > 0).count();
+    if broken > 0 {
+        bail!(
+            "{} orphaned row(s) across {} foreign key(s)",
+            orphans,
+            broken
+        );
+    }
+
+    println!("\nChecked {} foreign keys: no orphaned rows", reports.len());
+    Ok(())
+}
+
+/// Print a `table: +added -removed ~changed` line per changed table, and every changed
+/// column value when a single table is compared
+fn run_diff(old: &std::path::Path, new: &std::path::Path, table: Option<&str>) -> Result<()> {
+    for db in [old, new] {
+        if !db.is_file() {
+            bail!("Database not found: {:?}", db);
+        }
+    }
 
+    let diffs = diff_databases(old, new, table)?;
+    let unchanged = diffs.iter().filter(|d| d.is_empty()).count();
+    for diff in diffs.iter().filter(|d| !d.is_empty()) {
+        println!("{}", diff);
+        for change in &diff.changes {
+            println!("  {}", change);
+        }
+    }
+
+    println!(
+        "\nCompared {} tables: {} changed, {} unchanged",
+        diffs.len(),
+        diffs.len() - unchanged,
+        unchanged
+    );
     Ok(())
 }
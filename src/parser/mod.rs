@@ -1,3 +1,4 @@
 pub mod record;
+pub mod transform;
 
 pub use record::*;
@@ -1,15 +1,83 @@
 use anyhow::{Context, Result};
-use serde_json::Value;
-use std::collections::HashMap;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
 
-use crate::schema::{ArraySource, ColumnType, TableSchema, LANGUAGES};
+use crate::schema::{
+    get_table, ArraySource, Column, ColumnDefault, ColumnType, TableSchema, ALL_TABLES, LANGUAGES,
+};
+
+/// Column holding the original JSON line for tables imported with `--raw-json`
+pub const RAW_JSON_COLUMN: &str = "_raw";
+
+/// Column holding all localized text of a record, for single-column search
+pub const SEARCH_TEXT_COLUMN: &str = "search_text";
+
+/// Per-table parsing options
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Store the original JSON line in the `_raw` column
+    pub raw_json: bool,
+    /// Concatenate the localized text into the `search_text` column
+    pub search_text: bool,
+    /// Include localized `description` columns in `search_text`
+    pub search_text_descriptions: bool,
+    /// Languages localized columns expand to (empty means all of `LANGUAGES`)
+    pub languages: Vec<&'static str>,
+    /// Top-level JSON fields read by some table; others are recorded in
+    /// `ParsedRow::unknown_fields` (`None` skips the check)
+    pub known_fields: Option<HashSet<String>>,
+}
+
+impl ParseOptions {
+    /// Languages localized columns expand to
+    pub fn languages(&self) -> &[&'static str] {
+        if self.languages.is_empty() {
+            LANGUAGES
+        } else {
+            &self.languages
+        }
+    }
+}
+
+/// A JSON field had a different shape than the schema expects,
+/// e.g. an object where a junction table expects an array
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnexpectedShape {
+    pub field: String,
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+impl std::fmt::Display for UnexpectedShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "field `{}`: expected {}, found {}",
+            self.field, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for UnexpectedShape {}
 
 /// A parsed row ready for insertion
 pub struct ParsedRow {
     pub values: HashMap<String, SqlValue>,
+    /// Top-level JSON fields no table reads (only checked with `ParseOptions::known_fields`)
+    pub unknown_fields: Vec<String>,
+}
+
+impl ParsedRow {
+    pub fn new(values: HashMap<String, SqlValue>) -> Self {
+        Self {
+            values,
+            unknown_fields: Vec::new(),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SqlValue {
     Null,
     Integer(i64),
@@ -27,47 +95,141 @@ impl SqlValue {
         }
         Ok(())
     }
+
+    /// The value as JSON, e.g. to re-serialize a parsed row (non-finite reals become null)
+    pub fn as_json(&self) -> Value {
+        match self {
+            SqlValue::Null => Value::Null,
+            SqlValue::Integer(i) => Value::from(*i),
+            SqlValue::Real(f) => {
+                serde_json::Number::from_f64(*f).map_or(Value::Null, Value::Number)
+            }
+            SqlValue::Text(s) => Value::String(s.clone()),
+        }
+    }
+}
+
+impl From<SqlValue> for rusqlite::types::Value {
+    fn from(value: SqlValue) -> Self {
+        match value {
+            SqlValue::Null => rusqlite::types::Value::Null,
+            SqlValue::Integer(i) => rusqlite::types::Value::Integer(i),
+            SqlValue::Real(f) => rusqlite::types::Value::Real(f),
+            SqlValue::Text(s) => rusqlite::types::Value::Text(s),
+        }
+    }
+}
+
+/// Callback receiving each row produced from a junction table line
+pub type RowSink<'a> = dyn FnMut(ParsedRow) -> Result<()> + 'a;
+
+/// Parse a JSON line of the named table into its rows: one row for a regular table,
+/// any number for a junction table (one with an `array_source`)
+///
+/// ```
+/// use eve_sde_to_sqlite::parser::{parse_line, SqlValue};
+///
+/// let rows = parse_line("categories", r#"{"_key": 6, "name": {"en": "Ship"}}"#).unwrap();
+/// assert_eq!(rows.len(), 1);
+/// assert_eq!(rows[0].values["name_en"], SqlValue::Text("Ship".to_string()));
+///
+/// let line = r#"{"_key": 587, "materials": [{"materialTypeID": 34, "quantity": 100}]}"#;
+/// let rows = parse_line("type_materials", line).unwrap();
+/// assert_eq!(rows[0].values["material_type_id"], SqlValue::Integer(34));
+///
+/// assert!(parse_line("no_such_table", "{}").is_err());
+/// ```
+pub fn parse_line(table_name: &str, line: &str) -> Result<Vec<ParsedRow>> {
+    let schema =
+        get_table(table_name).ok_or_else(|| anyhow::anyhow!("Unknown table: {}", table_name))?;
+
+    if schema.array_source.is_some() {
+        parse_junction_records(line, schema)
+    } else {
+        Ok(vec![parse_record(line, schema)?])
+    }
 }
 
 /// Parse a JSON line into rows for a junction table (tables with array_source)
 /// Returns multiple rows extracted from nested arrays
 pub fn parse_junction_records(line: &str, schema: &TableSchema) -> Result<Vec<ParsedRow>> {
+    let mut rows = Vec::new();
+    for_each_junction_record(line, schema, &mut |row| {
+        rows.push(row);
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+/// Parse a JSON line for a junction table, passing each row to `emit` as it is built
+/// so a line that expands to many rows never has to be held in memory at once.
+/// Shapes are checked before the first row is emitted, so an `UnexpectedShape`
+/// error means no rows of the line were emitted.
+#[tracing::instrument(level = "trace", skip_all, fields(table = schema.name))]
+pub fn for_each_junction_record(
+    line: &str,
+    schema: &TableSchema,
+    emit: &mut RowSink,
+) -> Result<()> {
     let json: Value = serde_json::from_str(line).context("Failed to parse JSON")?;
+    for_each_junction_record_json(&json, schema, emit)
+}
 
+/// Like `for_each_junction_record`, for a line already parsed into JSON
+pub fn for_each_junction_record_json(
+    json: &Value,
+    schema: &TableSchema,
+    emit: &mut RowSink,
+) -> Result<()> {
     let array_source = schema
         .array_source
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("Table {} has no array_source", schema.name))?;
 
+    let emit = &mut |row: ParsedRow| -> Result<()> {
+        check_required(schema, json, &row.values)?;
+        emit(row)
+    };
+
     match array_source {
         ArraySource::Simple {
             array_field,
             parent_id_column,
-        } => parse_simple_array(&json, schema, array_field, parent_id_column),
+        } => parse_simple_array(json, schema, array_field, parent_id_column, emit),
         ArraySource::SimpleIntArray {
             array_field,
             parent_id_column,
             value_column,
-        } => parse_simple_int_array(&json, array_field, parent_id_column, value_column),
+        } => parse_simple_int_array(json, array_field, parent_id_column, value_column, emit),
         ArraySource::BlueprintActivity {
             activity_column,
             array_field,
-        } => parse_blueprint_activity(&json, schema, activity_column, array_field),
+        } => parse_blueprint_activity(json, schema, activity_column, array_field, emit),
         ArraySource::NestedKeyValue {
             array_field,
             parent_id_column,
             nested_key_column,
         } => parse_nested_key_value(
-            &json,
+            json,
             schema,
             array_field,
             parent_id_column,
             nested_key_column,
+            emit,
         ),
         ArraySource::DoubleNested {
             parent_id_column,
             level_key_column,
-        } => parse_double_nested(&json, schema, parent_id_column, level_key_column),
+        } => parse_double_nested(json, schema, parent_id_column, level_key_column, emit),
+        ArraySource::NestedMap {
+            field,
+            parent_id_column,
+            key_column,
+        } => parse_nested_map(json, schema, field, parent_id_column, key_column, emit),
+        ArraySource::SingleObject {
+            field,
+            parent_id_column,
+        } => parse_single_object(json, schema, field, parent_id_column, emit),
     }
 }
 
@@ -77,19 +239,18 @@ fn parse_simple_array(
     schema: &TableSchema,
     array_field: &str,
     parent_id_column: &str,
-) -> Result<Vec<ParsedRow>> {
+    emit: &mut RowSink,
+) -> Result<()> {
     let parent_id = json
         .get("_key")
         .and_then(|v| v.as_i64())
         .ok_or_else(|| anyhow::anyhow!("Missing _key in JSON"))?;
 
-    let array = match json.get(array_field) {
-        Some(Value::Array(arr)) => arr,
-        _ => return Ok(vec![]), // No array or empty
+    let array = match array_field_of(json, array_field)? {
+        Some(arr) => arr,
+        None => return Ok(()), // No array
     };
 
-    let mut rows = Vec::with_capacity(array.len());
-
     for item in array {
         let mut values = HashMap::new();
         values.insert(parent_id_column.to_string(), SqlValue::Integer(parent_id));
@@ -100,19 +261,14 @@ fn parse_simple_array(
                 continue; // Already added
             }
 
-            // Use explicit json_field if set, otherwise derive from column name
-            let json_key = col
-                .json_field
-                .map(String::from)
-                .unwrap_or_else(|| to_camel_case(col.name));
-            let value = extract_value(item, &json_key, &col.col_type);
+            let value = extract_column(item, col);
             values.insert(col.name.to_string(), value);
         }
 
-        rows.push(ParsedRow { values });
+        emit(ParsedRow::new(values))?;
     }
 
-    Ok(rows)
+    Ok(())
 }
 
 /// Parse blueprint-style nested structure: activities.{activity}.{field}[]
@@ -121,24 +277,26 @@ fn parse_blueprint_activity(
     schema: &TableSchema,
     activity_column: &str,
     array_field: &str,
-) -> Result<Vec<ParsedRow>> {
-    let blueprint_id = json
-        .get("blueprintTypeID")
-        .or_else(|| json.get("_key"))
-        .and_then(|v| v.as_i64())
-        .ok_or_else(|| anyhow::anyhow!("Missing blueprintTypeID or _key in JSON"))?;
+    emit: &mut RowSink,
+) -> Result<()> {
+    let blueprint_id = record_key(json)
+        .ok_or_else(|| anyhow::anyhow!("Missing _key or blueprintTypeID in JSON"))?;
 
     let activities = match json.get("activities") {
+        None | Some(Value::Null) => return Ok(()),
         Some(Value::Object(obj)) => obj,
-        _ => return Ok(vec![]),
+        Some(other) => return Err(unexpected_shape("activities", "object", other)),
     };
 
-    let mut rows = Vec::new();
+    // Check every activity before emitting so a malformed line yields no rows
+    for activity_data in activities.values() {
+        array_field_of(activity_data, array_field)?;
+    }
 
     for (activity_name, activity_data) in activities {
-        let array = match activity_data.get(array_field) {
-            Some(Value::Array(arr)) => arr,
-            _ => continue,
+        let array = match array_field_of(activity_data, array_field)? {
+            Some(arr) => arr,
+            None => continue,
         };
 
         for item in array {
@@ -155,16 +313,15 @@ fn parse_blueprint_activity(
                     continue; // Already added
                 }
 
-                let json_key = to_camel_case(col.name);
-                let value = extract_value(item, &json_key, &col.col_type);
+                let value = extract_column(item, col);
                 values.insert(col.name.to_string(), value);
             }
 
-            rows.push(ParsedRow { values });
+            emit(ParsedRow::new(values))?;
         }
     }
 
-    Ok(rows)
+    Ok(())
 }
 
 /// Parse simple integer array: {"_key": X, "fieldName": [123, 456, ...]}
@@ -173,29 +330,28 @@ fn parse_simple_int_array(
     array_field: &str,
     parent_id_column: &str,
     value_column: &str,
-) -> Result<Vec<ParsedRow>> {
+    emit: &mut RowSink,
+) -> Result<()> {
     let parent_id = json
         .get("_key")
         .and_then(|v| v.as_i64())
         .ok_or_else(|| anyhow::anyhow!("Missing _key in JSON"))?;
 
-    let array = match json.get(array_field) {
-        Some(Value::Array(arr)) => arr,
-        _ => return Ok(vec![]), // No array or empty
+    let array = match array_field_of(json, array_field)? {
+        Some(arr) => arr,
+        None => return Ok(()), // No array
     };
 
-    let mut rows = Vec::with_capacity(array.len());
-
     for item in array {
         if let Some(value) = item.as_i64() {
             let mut values = HashMap::new();
             values.insert(parent_id_column.to_string(), SqlValue::Integer(parent_id));
             values.insert(value_column.to_string(), SqlValue::Integer(value));
-            rows.push(ParsedRow { values });
+            emit(ParsedRow::new(values))?;
         }
     }
 
-    Ok(rows)
+    Ok(())
 }
 
 /// Parse nested key-value array: {"_key": X, "types": [{"_key": Y, "_value": [{...}]}]}
@@ -205,18 +361,22 @@ fn parse_nested_key_value(
     array_field: &str,
     parent_id_column: &str,
     nested_key_column: &str,
-) -> Result<Vec<ParsedRow>> {
+    emit: &mut RowSink,
+) -> Result<()> {
     let parent_id = json
         .get("_key")
         .and_then(|v| v.as_i64())
         .ok_or_else(|| anyhow::anyhow!("Missing _key in JSON"))?;
 
-    let outer_array = match json.get(array_field) {
-        Some(Value::Array(arr)) => arr,
-        _ => return Ok(vec![]),
+    let outer_array = match array_field_of(json, array_field)? {
+        Some(arr) => arr,
+        None => return Ok(()),
     };
 
-    let mut rows = Vec::new();
+    // Check every inner array before emitting so a malformed line yields no rows
+    for outer_item in outer_array {
+        array_field_of(outer_item, "_value")?;
+    }
 
     for outer_item in outer_array {
         let nested_key = match outer_item.get("_key").and_then(|v| v.as_i64()) {
@@ -224,9 +384,9 @@ fn parse_nested_key_value(
             None => continue,
         };
 
-        let inner_array = match outer_item.get("_value") {
-            Some(Value::Array(arr)) => arr,
-            _ => continue,
+        let inner_array = match array_field_of(outer_item, "_value")? {
+            Some(arr) => arr,
+            None => continue,
         };
 
         for item in inner_array {
@@ -240,16 +400,15 @@ fn parse_nested_key_value(
                     continue;
                 }
 
-                let json_key = to_camel_case(col.name);
-                let value = extract_value(item, &json_key, &col.col_type);
+                let value = extract_column(item, col);
                 values.insert(col.name.to_string(), value);
             }
 
-            rows.push(ParsedRow { values });
+            emit(ParsedRow::new(values))?;
         }
     }
 
-    Ok(rows)
+    Ok(())
 }
 
 /// Parse double-nested arrays: {"_key": X, "_value": [{"_key": Y, "_value": [Z, ...]}]}
@@ -258,18 +417,22 @@ fn parse_double_nested(
     schema: &TableSchema,
     parent_id_column: &str,
     level_key_column: &str,
-) -> Result<Vec<ParsedRow>> {
+    emit: &mut RowSink,
+) -> Result<()> {
     let parent_id = json
         .get("_key")
         .and_then(|v| v.as_i64())
         .ok_or_else(|| anyhow::anyhow!("Missing _key in JSON"))?;
 
-    let outer_array = match json.get("_value") {
-        Some(Value::Array(arr)) => arr,
-        _ => return Ok(vec![]),
+    let outer_array = match array_field_of(json, "_value")? {
+        Some(arr) => arr,
+        None => return Ok(()),
     };
 
-    let mut rows = Vec::new();
+    // Check every inner array before emitting so a malformed line yields no rows
+    for outer_item in outer_array {
+        array_field_of(outer_item, "_value")?;
+    }
 
     for outer_item in outer_array {
         let level_key = match outer_item.get("_key").and_then(|v| v.as_i64()) {
@@ -277,9 +440,9 @@ fn parse_double_nested(
             None => continue,
         };
 
-        let inner_array = match outer_item.get("_value") {
-            Some(Value::Array(arr)) => arr,
-            _ => continue,
+        let inner_array = match array_field_of(outer_item, "_value")? {
+            Some(arr) => arr,
+            None => continue,
         };
 
         for item in inner_array {
@@ -297,64 +460,358 @@ fn parse_double_nested(
                 // The inner value is typically a plain integer
                 let value = match item.as_i64() {
                     Some(i) => SqlValue::Integer(i),
-                    None => extract_value(item, &to_camel_case(col.name), &col.col_type),
+                    None => extract_column(item, col),
                 };
                 values.insert(col.name.to_string(), value);
             }
 
-            rows.push(ParsedRow { values });
+            emit(ParsedRow::new(values))?;
         }
     }
 
-    Ok(rows)
+    Ok(())
+}
+
+/// Parse an object keyed by id: {"_key": X, "fieldName": {"123": {...}, "456": {...}}}
+fn parse_nested_map(
+    json: &Value,
+    schema: &TableSchema,
+    field: &str,
+    parent_id_column: &str,
+    key_column: &str,
+    emit: &mut RowSink,
+) -> Result<()> {
+    let parent_id = json
+        .get("_key")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(|| anyhow::anyhow!("Missing _key in JSON"))?;
+
+    let map = match json.get(field) {
+        None | Some(Value::Null) => return Ok(()),
+        Some(Value::Object(obj)) => obj,
+        Some(other) => return Err(unexpected_shape(field, "object", other)),
+    };
+
+    // Check every entry before emitting so a malformed line yields no rows
+    let mut entries = Vec::with_capacity(map.len());
+    for (key, item) in map {
+        let id = key
+            .parse::<i64>()
+            .map_err(|_| unexpected_shape(field, "object keyed by integer ids", &json!(key)))?;
+        if !item.is_object() {
+            return Err(unexpected_shape(
+                &format!("{}.{}", field, key),
+                "object",
+                item,
+            ));
+        }
+        entries.push((id, item));
+    }
+
+    for (id, item) in entries {
+        let mut values = HashMap::new();
+        values.insert(parent_id_column.to_string(), SqlValue::Integer(parent_id));
+        values.insert(key_column.to_string(), SqlValue::Integer(id));
+
+        // Extract other columns from the entry's object
+        for col in schema.columns {
+            if col.name == parent_id_column || col.name == key_column {
+                continue;
+            }
+
+            let value = extract_column(item, col);
+            values.insert(col.name.to_string(), value);
+        }
+
+        emit(ParsedRow::new(values))?;
+    }
+
+    Ok(())
+}
+
+/// Parse a single nested object: {"_key": X, "fieldName": {...}}
+fn parse_single_object(
+    json: &Value,
+    schema: &TableSchema,
+    field: &str,
+    parent_id_column: &str,
+    emit: &mut RowSink,
+) -> Result<()> {
+    let parent_id = json
+        .get("_key")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(|| anyhow::anyhow!("Missing _key in JSON"))?;
+
+    let item = match json.get(field) {
+        None | Some(Value::Null) => return Ok(()),
+        Some(item @ Value::Object(_)) => item,
+        Some(other) => return Err(unexpected_shape(field, "object", other)),
+    };
+
+    let mut values = HashMap::new();
+    values.insert(parent_id_column.to_string(), SqlValue::Integer(parent_id));
+
+    for col in schema.columns {
+        if col.name == parent_id_column {
+            continue;
+        }
+
+        let value = extract_column(item, col);
+        values.insert(col.name.to_string(), value);
+    }
+
+    emit(ParsedRow::new(values))
+}
+
+/// Get an array field. Absent or null fields yield `None` (zero rows);
+/// any other non-array value is an `UnexpectedShape` error.
+fn array_field_of<'a>(json: &'a Value, field: &str) -> Result<Option<&'a Vec<Value>>> {
+    match json.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Array(arr)) => Ok(Some(arr)),
+        Some(other) => Err(unexpected_shape(field, "array", other)),
+    }
+}
+
+fn unexpected_shape(field: &str, expected: &'static str, found: &Value) -> anyhow::Error {
+    let found = match found {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+
+    UnexpectedShape {
+        field: field.to_string(),
+        expected,
+        found,
+    }
+    .into()
 }
 
 /// Parse a JSON line into a row for the given table schema
 pub fn parse_record(line: &str, schema: &TableSchema) -> Result<ParsedRow> {
+    parse_record_with(line, schema, &ParseOptions::default())
+}
+
+/// Parse a JSON line into a row, applying per-table parsing options
+pub fn parse_record_with(
+    line: &str,
+    schema: &TableSchema,
+    options: &ParseOptions,
+) -> Result<ParsedRow> {
     let json: Value = serde_json::from_str(line).context("Failed to parse JSON")?;
+    parse_record_json(&json, line, schema, options)
+}
 
+/// Like `parse_record_with`, for a line already parsed into JSON (`line` is only
+/// stored, as the raw JSON column)
+pub fn parse_record_json(
+    json: &Value,
+    line: &str,
+    schema: &TableSchema,
+    options: &ParseOptions,
+) -> Result<ParsedRow> {
     let mut values = HashMap::new();
 
     for col in schema.columns {
         match col.col_type {
             ColumnType::Localized => {
-                // Handle localized fields
-                let json_key = to_camel_case(col.name);
-                if let Some(obj) = json.get(&json_key).and_then(|v| v.as_object()) {
-                    for lang in LANGUAGES {
-                        let col_name = format!("{}_{}", col.name, lang);
-                        let value = obj
-                            .get(*lang)
-                            .and_then(|v| v.as_str())
-                            .map(|s| SqlValue::Text(s.to_string()))
-                            .unwrap_or(SqlValue::Null);
-                        values.insert(col_name, value);
-                    }
-                } else {
-                    for lang in LANGUAGES {
-                        let col_name = format!("{}_{}", col.name, lang);
-                        values.insert(col_name, SqlValue::Null);
-                    }
+                // Handle localized fields: a `{"en": ..., "de": ...}` object, or a
+                // bare string (as in some older exports) taken as the English text
+                let json_key = json_key(col);
+                let field = lookup(json, &json_key);
+                let text = |lang: &str| match field {
+                    Some(Value::Object(obj)) => obj.get(lang).and_then(|v| v.as_str()),
+                    Some(Value::String(s)) if lang == "en" => Some(s.as_str()),
+                    _ => None,
+                };
+                if col.require_en && text("en").is_none() {
+                    anyhow::bail!("Missing required English text for {}", json_key);
+                }
+
+                for lang in options.languages() {
+                    let col_name = format!("{}_{}", col.name, lang);
+                    let value = text(lang)
+                        .map(|s| SqlValue::Text(s.to_string()))
+                        .unwrap_or(SqlValue::Null);
+                    values.insert(col_name, value);
                 }
             }
+            ColumnType::Text if col.name == "id" && col.json_field.is_none() => {
+                // Some files (e.g. translationLanguages.jsonl) key records by string
+                let value = json
+                    .get("_key")
+                    .and_then(|v| v.as_str())
+                    .map(|s| SqlValue::Text(s.to_string()))
+                    .unwrap_or(SqlValue::Null);
+                values.insert(col.name.to_string(), value);
+            }
+            _ if col.name == "id" && col.json_field.is_none() => {
+                let value = record_key(json)
+                    .map(SqlValue::Integer)
+                    .unwrap_or(SqlValue::Null);
+                values.insert(col.name.to_string(), value);
+            }
             _ => {
-                let json_key = if col.name == "id" {
-                    "_key".to_string()
-                } else {
-                    to_camel_case(col.name)
-                };
-
-                let value = extract_value(&json, &json_key, &col.col_type);
+                let value = extract_column(json, col);
                 values.insert(col.name.to_string(), value);
             }
         }
     }
 
-    Ok(ParsedRow { values })
+    check_required(schema, json, &values)?;
+
+    if options.search_text {
+        values.insert(
+            SEARCH_TEXT_COLUMN.to_string(),
+            search_text(
+                schema,
+                options.languages(),
+                options.search_text_descriptions,
+                &values,
+            ),
+        );
+    }
+
+    if options.raw_json {
+        values.insert(
+            RAW_JSON_COLUMN.to_string(),
+            SqlValue::Text(line.to_string()),
+        );
+    }
+
+    let unknown_fields = match (&options.known_fields, json.as_object()) {
+        (Some(known), Some(object)) => object
+            .keys()
+            .filter(|field| !known.contains(field.as_str()))
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(ParsedRow {
+        values,
+        unknown_fields,
+    })
+}
+
+/// Top-level JSON fields of a source file that some table reads: `_key`, the fields
+/// of base table columns and the arrays junction tables are built from
+pub fn known_fields(source_file: &str) -> HashSet<String> {
+    let mut fields = HashSet::from(["_key".to_string()]);
+
+    for schema in ALL_TABLES.iter().filter(|t| t.source_file == source_file) {
+        match &schema.array_source {
+            Some(source) => {
+                fields.insert(source.top_level_field().to_string());
+            }
+            None => fields.extend(schema.columns.iter().map(|col| {
+                let key = json_key(col);
+                match key.split_once('.') {
+                    Some((top, _)) => top.to_string(),
+                    None => key,
+                }
+            })),
+        }
+    }
+
+    fields
+}
+
+/// Fail with a descriptive error if a required (NOT NULL) column of a row is NULL,
+/// e.g. `types._key=12345 missing required field groupID`. The key is only looked up
+/// in `json` when a field is missing.
+fn check_required(
+    schema: &TableSchema,
+    json: &Value,
+    values: &HashMap<String, SqlValue>,
+) -> Result<()> {
+    let missing = schema.columns.iter().find(|col| {
+        !col.nullable
+            && col.col_type != ColumnType::Localized
+            && matches!(values.get(col.name), None | Some(SqlValue::Null))
+    });
+
+    match missing {
+        Some(col) => {
+            let field = if col.name == "id" && col.json_field.is_none() {
+                "_key".to_string()
+            } else {
+                json_key(col)
+            };
+            anyhow::bail!(
+                "{}._key={} missing required field {}",
+                schema.name,
+                record_key_text(json),
+                field
+            )
+        }
+        None => Ok(()),
+    }
+}
+
+/// Record key for error messages (`?` when the record has none)
+fn record_key_text(json: &Value) -> String {
+    match json.get("_key") {
+        Some(Value::String(s)) => s.clone(),
+        Some(key) => key.to_string(),
+        None => record_key(json).map_or("?".to_string(), |k| k.to_string()),
+    }
+}
+
+/// Parse just the key of a JSON line (see `record_key`)
+pub fn parse_record_key(line: &str) -> Result<Option<i64>> {
+    let json: Value = serde_json::from_str(line).context("Failed to parse JSON")?;
+    Ok(record_key(&json))
+}
+
+/// Key identifying a record: `_key`, falling back to `blueprintTypeID`.
+/// Header rows and junction rows must both use this so foreign keys line up.
+pub fn record_key(json: &Value) -> Option<i64> {
+    json.get("_key")
+        .or_else(|| json.get("blueprintTypeID"))
+        .and_then(|v| v.as_i64())
+}
+
+/// JSON key for a column: explicit `json_field` if set, otherwise camelCase of the name
+fn json_key(col: &Column) -> String {
+    col.json_field
+        .map(String::from)
+        .unwrap_or_else(|| to_camel_case(col.name))
+}
+
+/// Look up a key, following dotted paths through nested objects and arrays
+fn lookup<'a>(json: &'a Value, key: &str) -> Option<&'a Value> {
+    if !key.contains('.') {
+        return json.get(key);
+    }
+
+    key.split('.')
+        .try_fold(json, |current, segment| match current {
+            Value::Array(arr) => segment.parse::<usize>().ok().and_then(|i| arr.get(i)),
+            _ => current.get(segment),
+        })
+}
+
+/// Extract a column's value, applying its transform if it has one (or its default if
+/// the field is absent)
+fn extract_column(json: &Value, col: &Column) -> SqlValue {
+    let key = json_key(col);
+    match (col.transform, lookup(json, &key)) {
+        (_, None) => col
+            .default
+            .map_or(SqlValue::Null, ColumnDefault::to_sql_value),
+        (Some(_), Some(Value::Null)) => SqlValue::Null,
+        (Some(transform), Some(v)) => transform.apply(v),
+        (None, Some(_)) => extract_value(json, &key, &col.col_type),
+    }
 }
 
 fn extract_value(json: &Value, key: &str, col_type: &ColumnType) -> SqlValue {
-    let val = json.get(key);
+    let val = lookup(json, key);
 
     match val {
         None | Some(Value::Null) => SqlValue::Null,
@@ -370,14 +827,69 @@ fn extract_value(json: &Value, key: &str, col_type: &ColumnType) -> SqlValue {
                 .map(|b| SqlValue::Integer(if b { 1 } else { 0 }))
                 .unwrap_or(SqlValue::Null),
             ColumnType::Json => SqlValue::Text(v.to_string()),
+            ColumnType::Timestamp => v
+                .as_str()
+                .map(|s| SqlValue::Text(normalize_timestamp(s).unwrap_or_else(|| s.to_string())))
+                .unwrap_or(SqlValue::Null),
             ColumnType::Localized => SqlValue::Null, // Handled separately
         },
     }
 }
 
+/// Join the expanded localized values of a row (column order, then language order),
+/// leaving out `description` unless `descriptions` is set
+fn search_text(
+    schema: &TableSchema,
+    languages: &[&str],
+    descriptions: bool,
+    values: &HashMap<String, SqlValue>,
+) -> SqlValue {
+    let parts: Vec<&str> = schema
+        .columns
+        .iter()
+        .filter(|col| col.col_type == ColumnType::Localized)
+        .filter(|col| descriptions || col.name != "description")
+        .flat_map(|col| {
+            languages
+                .iter()
+                .map(move |lang| format!("{}_{}", col.name, lang))
+        })
+        .filter_map(|key| match values.get(&key) {
+            Some(SqlValue::Text(text)) if !text.is_empty() => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    if parts.is_empty() {
+        SqlValue::Null
+    } else {
+        SqlValue::Text(parts.join(" "))
+    }
+}
+
+/// Normalize a date/time string to UTC RFC 3339 with a `Z` suffix.
+/// Values without an offset are taken to be UTC; date-only values become midnight UTC.
+/// Returns `None` if the value isn't a recognizable date.
+pub fn normalize_timestamp(value: &str) -> Option<String> {
+    let utc = if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        dt.with_timezone(&Utc)
+    } else if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f") {
+        naive.and_utc()
+    } else if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f") {
+        naive.and_utc()
+    } else {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
+            .and_utc()
+    };
+
+    Some(utc.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
 /// Convert snake_case to camelCase
 /// Handles special case: `_id` suffix becomes `ID` (e.g., category_id -> categoryID)
-fn to_camel_case(s: &str) -> String {
+pub fn to_camel_case(s: &str) -> String {
     // Handle _id suffix specially (EVE uses categoryID, groupID, etc.)
     if let Some(prefix) = s.strip_suffix("_id") {
         let prefix_camel = to_camel_case_inner(prefix);
@@ -416,5 +928,475 @@ mod tests {
         assert_eq!(to_camel_case("solar_system_id"), "solarSystemID");
         assert_eq!(to_camel_case("name"), "name");
         assert_eq!(to_camel_case("sof_faction_name"), "sofFactionName");
+        assert_eq!(to_camel_case("name_id"), "nameID");
+        assert_eq!(to_camel_case("description_id"), "descriptionID");
+        assert_eq!(to_camel_case("orbit_id"), "orbitID");
+        assert_eq!(to_camel_case("ceo_id"), "ceoID");
+        assert_eq!(to_camel_case("guid"), "guid");
+    }
+
+    #[test]
+    fn test_json_key_overrides() {
+        // Columns whose SDE key is not the camelCase of their name
+        let key = |table: &str, column: &str| {
+            let schema = crate::schema::get_table(table).unwrap();
+            json_key(schema.columns.iter().find(|c| c.name == column).unwrap())
+        };
+        assert_eq!(key("skins", "allow_ccp_devs"), "allowCCPDevs");
+        assert_eq!(key("blueprints", "copying_time"), "activities.copying.time");
+        assert_eq!(
+            key("blueprints", "research_time_time"),
+            "activities.research_time.time"
+        );
+        assert_eq!(
+            key("map_stars", "spectral_class"),
+            "statistics.spectralClass"
+        );
+        assert_eq!(key("map_regions", "name_id"), "nameID");
+        assert_eq!(key("graphics", "icon_folder"), "iconInfo.folder");
+    }
+
+    #[test]
+    fn test_extract_value_dotted_path() {
+        let json: Value = serde_json::from_str(
+            r#"{"position": {"x": 1.5, "y": -2.0, "z": 3}, "bounds": [[0, 1], [2, 3]]}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            extract_value(&json, "position.x", &ColumnType::Real),
+            SqlValue::Real(x) if x == 1.5
+        ));
+        assert!(matches!(
+            extract_value(&json, "position.z", &ColumnType::Real),
+            SqlValue::Real(z) if z == 3.0
+        ));
+        assert!(matches!(
+            extract_value(&json, "bounds.1.0", &ColumnType::Integer),
+            SqlValue::Integer(2)
+        ));
+        assert!(matches!(
+            extract_value(&json, "position.w", &ColumnType::Real),
+            SqlValue::Null
+        ));
+    }
+
+    fn shape_error(line: &str, schema: &TableSchema) -> Option<UnexpectedShape> {
+        parse_junction_records(line, schema)
+            .err()
+            .and_then(|e| e.downcast_ref::<UnexpectedShape>().cloned())
+    }
+
+    #[test]
+    fn test_junction_absent_or_empty_array_yields_no_rows() {
+        let schema = &crate::schema::TYPE_DOGMA_ATTRIBUTES;
+
+        for line in [
+            r#"{"_key": 1}"#,
+            r#"{"_key": 1, "dogmaAttributes": null}"#,
+            r#"{"_key": 1, "dogmaAttributes": []}"#,
+        ] {
+            assert!(parse_junction_records(line, schema).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_junction_wrong_shape_is_an_error() {
+        let line = r#"{"_key": 1, "dogmaAttributes": {"attributeID": 10, "value": 1.0}}"#;
+        assert_eq!(
+            shape_error(line, &crate::schema::TYPE_DOGMA_ATTRIBUTES),
+            Some(UnexpectedShape {
+                field: "dogmaAttributes".to_string(),
+                expected: "array",
+                found: "object",
+            })
+        );
+
+        let line = r#"{"_key": 681, "activities": []}"#;
+        assert_eq!(
+            shape_error(line, &crate::schema::BLUEPRINT_MATERIALS).map(|e| e.expected),
+            Some("object")
+        );
+
+        let line = r#"{"_key": 681, "activities": {"manufacturing": {"materials": 5}}}"#;
+        assert_eq!(
+            shape_error(line, &crate::schema::BLUEPRINT_MATERIALS).map(|e| e.found),
+            Some("number")
+        );
+    }
+
+    #[test]
+    fn test_nested_map() {
+        static RESOURCES: TableSchema = TableSchema {
+            name: "resources",
+            source_file: "resources.jsonl",
+            columns: &[
+                Column::required("planet_id", ColumnType::Integer),
+                Column::required("type_id", ColumnType::Integer),
+                Column::new("quantity", ColumnType::Integer),
+            ],
+            foreign_keys: &[],
+            indexes: &[],
+            child_tables: &[],
+            array_source: Some(ArraySource::NestedMap {
+                field: "resources",
+                parent_id_column: "planet_id",
+                key_column: "type_id",
+            }),
+            strict: false,
+            without_rowid: false,
+            primary_key: &[],
+        };
+
+        let line = r#"{"_key": 40000002, "resources": {"2268": {"quantity": 5}, "2305": {"quantity": 9}}}"#;
+        let mut rows: Vec<(i64, i64, i64)> = parse_junction_records(line, &RESOURCES)
+            .unwrap()
+            .iter()
+            .map(|row| {
+                let int = |col: &str| match row.values.get(col) {
+                    Some(SqlValue::Integer(i)) => *i,
+                    other => panic!("expected integer {}, got {:?}", col, other),
+                };
+                (int("planet_id"), int("type_id"), int("quantity"))
+            })
+            .collect();
+        rows.sort();
+        assert_eq!(rows, [(40000002, 2268, 5), (40000002, 2305, 9)]);
+
+        assert!(parse_junction_records(r#"{"_key": 1}"#, &RESOURCES)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            shape_error(r#"{"_key": 1, "resources": {"abc": {}}}"#, &RESOURCES).map(|e| e.expected),
+            Some("object keyed by integer ids")
+        );
+        assert_eq!(
+            shape_error(r#"{"_key": 1, "resources": {"2268": 5}}"#, &RESOURCES).map(|e| e.found),
+            Some("number")
+        );
+    }
+
+    #[test]
+    fn test_single_object() {
+        let schema = &crate::schema::PLANET_RESOURCE_TYPES;
+        let line = r#"{"_key": 40000002, "power": 300, "reagent": {"amountPerCycle": 4, "cyclePeriod": 1800, "securedCapacity": 1000, "typeID": 81143, "unsecuredCapacity": 500}}"#;
+        let rows = parse_junction_records(line, schema).unwrap();
+        assert_eq!(rows.len(), 1);
+        let int = |col: &str| match rows[0].values.get(col) {
+            Some(SqlValue::Integer(i)) => *i,
+            other => panic!("expected integer {}, got {:?}", col, other),
+        };
+        assert_eq!(int("planet_id"), 40000002);
+        assert_eq!(int("type_id"), 81143);
+        assert_eq!(int("amount_per_cycle"), 4);
+        assert_eq!(int("unsecured_capacity"), 500);
+
+        assert!(
+            parse_junction_records(r#"{"_key": 1, "power": 300}"#, schema)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            shape_error(r#"{"_key": 1, "reagent": [81143]}"#, schema).map(|e| e.found),
+            Some("array")
+        );
+    }
+
+    #[test]
+    fn test_junction_rows_are_streamed() {
+        let line = r#"{"_key": 681, "activities": {"manufacturing": {"materials": [{"typeID": 34, "quantity": 10}, {"typeID": 35, "quantity": 5}]}, "copying": {"materials": [{"typeID": 36, "quantity": 1}]}}}"#;
+        let schema = &crate::schema::BLUEPRINT_MATERIALS;
+
+        let mut emitted = 0;
+        for_each_junction_record(line, schema, &mut |_| {
+            emitted += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(emitted, 3);
+
+        // The sink can stop the expansion early
+        let mut emitted = 0;
+        let result = for_each_junction_record(line, schema, &mut |_| {
+            emitted += 1;
+            anyhow::bail!("batch full")
+        });
+        assert!(result.is_err());
+        assert_eq!(emitted, 1);
+
+        // A malformed activity anywhere in the line emits nothing
+        let line = r#"{"_key": 681, "activities": {"copying": {"materials": [{"typeID": 36}]}, "manufacturing": {"materials": 5}}}"#;
+        let mut emitted = 0;
+        let result = for_each_junction_record(line, schema, &mut |_| {
+            emitted += 1;
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(emitted, 0);
+    }
+
+    #[test]
+    fn test_blueprint_key_matches_junction() {
+        let line = r#"{"_key": 681, "blueprintTypeID": 999, "activities": {"manufacturing": {"materials": [{"typeID": 34, "quantity": 10}]}}}"#;
+
+        let header = parse_record(line, &crate::schema::BLUEPRINTS).unwrap();
+        let materials = parse_junction_records(line, &crate::schema::BLUEPRINT_MATERIALS).unwrap();
+
+        assert!(matches!(
+            header.values.get("id"),
+            Some(SqlValue::Integer(681))
+        ));
+        assert_eq!(materials.len(), 1);
+        assert!(matches!(
+            materials[0].values.get("blueprint_id"),
+            Some(SqlValue::Integer(681))
+        ));
+    }
+
+    #[test]
+    fn test_absent_real_is_null_not_zero() {
+        let line = r#"{"_key": 3673, "factions": [{"_key": 500001, "attackMinSec": 0.0, "fineByValue": 2.5}]}"#;
+        let rows = parse_junction_records(line, &crate::schema::CONTRABAND_TYPE_FACTIONS).unwrap();
+        let values = &rows[0].values;
+
+        assert!(matches!(
+            values.get("attack_min_sec"),
+            Some(SqlValue::Real(v)) if *v == 0.0
+        ));
+        assert!(matches!(
+            values.get("fine_by_value"),
+            Some(SqlValue::Real(v)) if *v == 2.5
+        ));
+        assert!(matches!(
+            values.get("confiscate_min_sec"),
+            Some(SqlValue::Null)
+        ));
+        assert!(matches!(values.get("standing_loss"), Some(SqlValue::Null)));
+    }
+
+    #[test]
+    fn test_absent_field_uses_column_default() {
+        let row = parse_record(
+            r#"{"_key": 6, "name": {"en": "Ship"}}"#,
+            &crate::schema::CATEGORIES,
+        )
+        .unwrap();
+        assert!(matches!(
+            row.values.get("published"),
+            Some(SqlValue::Integer(0))
+        ));
+
+        let line = r#"{"_key": 6, "name": {"en": "Ship"}, "published": true}"#;
+        let row = parse_record(line, &crate::schema::CATEGORIES).unwrap();
+        assert!(matches!(
+            row.values.get("published"),
+            Some(SqlValue::Integer(1))
+        ));
+
+        // Columns without a default stay NULL
+        let row = parse_record(r#"{"_key": 1}"#, &crate::schema::ICONS).unwrap();
+        assert!(matches!(row.values.get("icon_file"), Some(SqlValue::Null)));
+    }
+
+    #[test]
+    fn test_sql_value_conversions() {
+        use rusqlite::types::Value as SqliteValue;
+
+        let values = [
+            SqlValue::Null,
+            SqlValue::Integer(34),
+            SqlValue::Real(2.5),
+            SqlValue::Text("Tritanium".to_string()),
+        ];
+        let json: Vec<Value> = values.iter().map(SqlValue::as_json).collect();
+        assert_eq!(
+            json,
+            vec![json!(null), json!(34), json!(2.5), json!("Tritanium")]
+        );
+        assert_eq!(SqlValue::Real(f64::NAN).as_json(), Value::Null);
+
+        let sqlite: Vec<SqliteValue> = values.into_iter().map(SqliteValue::from).collect();
+        assert_eq!(
+            sqlite,
+            vec![
+                SqliteValue::Null,
+                SqliteValue::Integer(34),
+                SqliteValue::Real(2.5),
+                SqliteValue::Text("Tritanium".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_timestamp() {
+        assert_eq!(
+            normalize_timestamp("2025-06-01T12:30:00+02:00").as_deref(),
+            Some("2025-06-01T10:30:00Z")
+        );
+        assert_eq!(
+            normalize_timestamp("2003-05-20T00:00:00").as_deref(),
+            Some("2003-05-20T00:00:00Z")
+        );
+        assert_eq!(
+            normalize_timestamp("2003-05-20").as_deref(),
+            Some("2003-05-20T00:00:00Z")
+        );
+        assert_eq!(
+            normalize_timestamp("2025-06-01T10:30:00.5Z").as_deref(),
+            Some("2025-06-01T10:30:00.500Z")
+        );
+        assert_eq!(normalize_timestamp("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_record_raw_json() {
+        let line = r#"{"_key": 7, "name": {"en": "Ship"}, "published": true, "extra": [1, 2]}"#;
+
+        let row = parse_record(line, &crate::schema::CATEGORIES).unwrap();
+        assert!(!row.values.contains_key(RAW_JSON_COLUMN));
+
+        let options = ParseOptions {
+            raw_json: true,
+            ..Default::default()
+        };
+        let row = parse_record_with(line, &crate::schema::CATEGORIES, &options).unwrap();
+        match row.values.get(RAW_JSON_COLUMN) {
+            Some(SqlValue::Text(raw)) => assert_eq!(raw, line),
+            other => panic!("expected raw JSON text, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_required_english_is_an_error() {
+        let schema = &crate::schema::TYPES;
+        assert!(parse_record(r#"{"_key": 34, "name": {"en": "Tritanium"}}"#, schema).is_ok());
+
+        for line in [
+            r#"{"_key": 34}"#,
+            r#"{"_key": 34, "name": {"de": "Tritanium"}}"#,
+            r#"{"_key": 34, "name": {"en": null}}"#,
+        ] {
+            match parse_record(line, schema) {
+                Err(err) => assert!(err.to_string().contains("name"), "{}", err),
+                Ok(_) => panic!("expected an error for {}", line),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_record_string_key() {
+        let line = r#"{"_key": "de", "name": "German"}"#;
+        let row = parse_record(line, &crate::schema::TRANSLATION_LANGUAGES).unwrap();
+        assert!(matches!(row.values.get("id"), Some(SqlValue::Text(id)) if id == "de"));
+        assert!(matches!(row.values.get("name"), Some(SqlValue::Text(name)) if name == "German"));
+    }
+
+    #[test]
+    fn test_missing_required_field_names_table_and_key() {
+        let err = parse_record(r#"{"name": {"en": "Rifter"}}"#, &crate::schema::TYPES)
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(err, "types._key=? missing required field _key");
+
+        let line = r#"{"_key": 587, "dogmaAttributes": [{"attributeID": 10}]}"#;
+        let err =
+            for_each_junction_record(line, &crate::schema::TYPE_DOGMA_ATTRIBUTES, &mut |_| Ok(()))
+                .unwrap_err()
+                .to_string();
+        assert_eq!(
+            err,
+            "type_dogma_attributes._key=587 missing required field value"
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields_are_reported_when_checked() {
+        let line = r#"{"_key": 6, "name": {"en": "Ship"}, "published": true, "newField": 1}"#;
+
+        let row = parse_record(line, &crate::schema::CATEGORIES).unwrap();
+        assert!(row.unknown_fields.is_empty());
+
+        let options = ParseOptions {
+            known_fields: Some(known_fields("categories.jsonl")),
+            ..Default::default()
+        };
+        let row = parse_record_with(line, &crate::schema::CATEGORIES, &options).unwrap();
+        assert_eq!(row.unknown_fields, ["newField"]);
+
+        // Arrays read by junction tables of the same file are known
+        let known = known_fields("typeDogma.jsonl");
+        assert!(known.contains("dogmaAttributes"));
+        assert!(known.contains("dogmaEffects"));
+    }
+
+    #[test]
+    fn test_localized_object_or_bare_string() {
+        let text = |row: &ParsedRow, col: &str| match row.values.get(col) {
+            Some(SqlValue::Text(t)) => Some(t.clone()),
+            Some(SqlValue::Null) => None,
+            other => panic!("expected text or NULL for {}, got {:?}", col, other),
+        };
+
+        let line = r#"{"_key": 6, "name": {"en": "Ship", "de": "Schiff"}}"#;
+        let row = parse_record(line, &crate::schema::CATEGORIES).unwrap();
+        assert_eq!(text(&row, "name_en").as_deref(), Some("Ship"));
+        assert_eq!(text(&row, "name_de").as_deref(), Some("Schiff"));
+        assert_eq!(text(&row, "name_fr"), None);
+
+        let line = r#"{"_key": 6, "name": "Ship"}"#;
+        let row = parse_record(line, &crate::schema::CATEGORIES).unwrap();
+        assert_eq!(text(&row, "name_en").as_deref(), Some("Ship"));
+        assert_eq!(text(&row, "name_de"), None);
+    }
+
+    #[test]
+    fn test_parse_record_selected_languages() {
+        let line = r#"{"_key": 587, "name": {"en": "Rifter", "ru": "Рифтер"}}"#;
+        let options = ParseOptions {
+            languages: vec!["en", "de"],
+            ..Default::default()
+        };
+
+        let row = parse_record_with(line, &crate::schema::TYPES, &options).unwrap();
+        assert!(matches!(row.values.get("name_en"), Some(SqlValue::Text(t)) if t == "Rifter"));
+        assert!(matches!(row.values.get("name_de"), Some(SqlValue::Null)));
+        assert!(matches!(
+            row.values.get("description_de"),
+            Some(SqlValue::Null)
+        ));
+        assert!(!row.values.contains_key("name_ru"));
+    }
+
+    #[test]
+    fn test_parse_record_search_text() {
+        let line = r#"{"_key": 587, "name": {"en": "Rifter", "de": "Rifter", "ru": "Рифтер"}, "description": {"en": "A frigate"}}"#;
+        let options = ParseOptions {
+            search_text: true,
+            ..Default::default()
+        };
+
+        let row = parse_record_with(line, &crate::schema::TYPES, &options).unwrap();
+        match row.values.get(SEARCH_TEXT_COLUMN) {
+            Some(SqlValue::Text(text)) => assert_eq!(text, "Rifter Rifter Рифтер"),
+            other => panic!("expected search text, got {:?}", other),
+        }
+
+        let with_descriptions = ParseOptions {
+            search_text_descriptions: true,
+            ..options.clone()
+        };
+        let row = parse_record_with(line, &crate::schema::TYPES, &with_descriptions).unwrap();
+        match row.values.get(SEARCH_TEXT_COLUMN) {
+            Some(SqlValue::Text(text)) => assert_eq!(text, "Rifter Rifter Рифтер A frigate"),
+            other => panic!("expected search text, got {:?}", other),
+        }
+
+        let row =
+            parse_record_with(r#"{"_key": 1}"#, &crate::schema::META_GROUPS, &options).unwrap();
+        assert!(matches!(
+            row.values.get(SEARCH_TEXT_COLUMN),
+            Some(SqlValue::Null)
+        ));
     }
 }
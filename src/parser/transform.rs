@@ -0,0 +1,107 @@
+use serde_json::Value;
+
+use super::record::SqlValue;
+use crate::schema::Transform;
+
+impl Transform {
+    /// Convert a (non-null) JSON value
+    pub fn apply(&self, value: &Value) -> SqlValue {
+        match self {
+            Transform::RoundSecurity => value
+                .as_f64()
+                .map(|s| SqlValue::Real(round_security(s)))
+                .unwrap_or(SqlValue::Null),
+            Transform::DecodeEffectCategory => value
+                .as_i64()
+                .and_then(effect_category_name)
+                .map(|name| SqlValue::Text(name.to_string()))
+                .unwrap_or(SqlValue::Null),
+            Transform::Custom(f) => f(value),
+        }
+    }
+}
+
+/// Round a security status to one decimal place as shown in game.
+/// Systems just above 0.0 display as 0.1 rather than 0.0.
+pub fn round_security(security: f64) -> f64 {
+    if security > 0.0 && security < 0.05 {
+        0.1
+    } else {
+        (security * 10.0).round() / 10.0
+    }
+}
+
+/// Name of a dogma effect category
+pub fn effect_category_name(category: i64) -> Option<&'static str> {
+    match category {
+        0 => Some("passive"),
+        1 => Some("active"),
+        2 => Some("target"),
+        3 => Some("area"),
+        4 => Some("online"),
+        5 => Some("overload"),
+        6 => Some("dungeon"),
+        7 => Some("system"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_record;
+    use crate::schema::{Column, ColumnType, TableSchema};
+
+    #[test]
+    fn test_round_security() {
+        assert_eq!(round_security(0.9459), 0.9);
+        assert_eq!(round_security(0.45), 0.5);
+        assert_eq!(round_security(0.04), 0.1);
+        assert_eq!(round_security(0.0), 0.0);
+        assert_eq!(round_security(-0.26), -0.3);
+    }
+
+    fn shout(value: &Value) -> SqlValue {
+        value
+            .as_str()
+            .map(|s| SqlValue::Text(s.to_uppercase()))
+            .unwrap_or(SqlValue::Null)
+    }
+
+    static TRANSFORMED: TableSchema = TableSchema {
+        name: "transformed",
+        source_file: "transformed.jsonl",
+        columns: &[
+            Column::required("id", ColumnType::Integer),
+            Column::new("security_status", ColumnType::Real),
+            Column::new("security_rounded", ColumnType::Real)
+                .json("securityStatus")
+                .transform(Transform::RoundSecurity),
+            Column::new("category", ColumnType::Text)
+                .json("effectCategory")
+                .transform(Transform::DecodeEffectCategory),
+            Column::new("label", ColumnType::Text).transform(Transform::Custom(shout)),
+        ],
+        foreign_keys: &[],
+        indexes: &[],
+        child_tables: &[],
+        array_source: None,
+        strict: false,
+        without_rowid: false,
+        primary_key: &[],
+    };
+
+    #[test]
+    fn test_transforms_applied_in_parse_record() {
+        let line = r#"{"_key": 1, "securityStatus": 0.46, "effectCategory": 4, "label": "hi"}"#;
+        let row = parse_record(line, &TRANSFORMED).unwrap();
+
+        assert!(matches!(row.values["security_status"], SqlValue::Real(s) if s == 0.46));
+        assert!(matches!(row.values["security_rounded"], SqlValue::Real(s) if s == 0.5));
+        assert!(matches!(&row.values["category"], SqlValue::Text(c) if c == "online"));
+        assert!(matches!(&row.values["label"], SqlValue::Text(l) if l == "HI"));
+
+        let row = parse_record(r#"{"_key": 2}"#, &TRANSFORMED).unwrap();
+        assert!(matches!(row.values["security_rounded"], SqlValue::Null));
+    }
+}
@@ -0,0 +1,156 @@
+//! Pipelined sync: import tables while the rest of the SDE archive is still being extracted
+//!
+//! A producer thread extracts `.jsonl` files in the order the selected tables need them
+//! and announces each finished file over a channel. The importer (which owns the UI)
+//! waits for a table's source file only when it reaches that table.
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::download::{
+    extract_jsonl_prioritized, fetch_build, finish_download, latest_or_cached_build, list_jsonl,
+    CacheManager, RetentionPolicy, SdeInfo,
+};
+use crate::filter::check_sources_present;
+use crate::interrupt::Artifact;
+use crate::schema::TableSchema;
+use crate::ui::{Phase, Ui};
+use crate::writer::{convert_when_ready, convert_with_options, ConvertOptions};
+
+/// Where the SDE is cached and how the cache is managed
+pub struct CacheSettings {
+    pub cache_dir: Option<PathBuf>,
+    pub force: bool,
+    /// Use the newest cached build without checking for a newer one
+    pub offline: bool,
+    pub retention: RetentionPolicy,
+}
+
+/// Result of a pipelined sync
+pub enum SyncOutcome {
+    /// The latest build was converted
+    Converted { info: SdeInfo, total_records: u64 },
+    /// The latest build is not newer than `since_build`, so nothing was done
+    UpToDate { info: SdeInfo, since_build: u64 },
+}
+
+/// Download the latest SDE (unless cached) and convert it, overlapping extraction and import.
+/// Returns early without downloading if the latest build is not newer than `since_build`.
+/// Fails before importing if any `requested` table has no source file.
+pub fn sync_pipelined(
+    settings: &CacheSettings,
+    since_build: Option<u64>,
+    output_db: &Path,
+    tables: Vec<&TableSchema>,
+    requested: &[&TableSchema],
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+) -> Result<SyncOutcome> {
+    let cache = CacheManager::new(settings.cache_dir.clone())?;
+    let info = latest_or_cached_build(&cache, settings.force, settings.offline, ui)?;
+    if let Some(since_build) = since_build.filter(|n| info.build_number <= *n) {
+        ui.log(format!(
+            "No new build: latest is {}, have {}",
+            info.build_number, since_build
+        ));
+        return Ok(SyncOutcome::UpToDate { info, since_build });
+    }
+
+    let fetched = fetch_build(&cache, info, settings.force, ui)?;
+
+    let mut options = options.clone();
+    options.metadata.extend(fetched.info.metadata());
+    options
+        .metadata
+        .push(("source".to_string(), "download".to_string()));
+
+    let archive = match &fetched.archive {
+        Some(archive) => archive.clone(),
+        None => {
+            // Already extracted: plain conversion
+            check_sources_present(requested, |f| fetched.build_dir.join(f).is_file())?;
+            ui.set_phase(Phase::Converting);
+            ui.log("Converting to SQLite...");
+            let total_records =
+                convert_with_options(&fetched.build_dir, output_db, tables, &options, ui)?;
+            return Ok(SyncOutcome::Converted {
+                info: fetched.info,
+                total_records,
+            });
+        }
+    };
+
+    if !requested.is_empty() {
+        let available = list_jsonl(&archive)?;
+        check_sources_present(requested, |f| available.contains(f))?;
+    }
+
+    // Extract into a staging directory that only becomes the cached build once complete
+    let staging_dir = fetched.build_dir.with_extension("partial");
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir).context("Failed to clear staging directory")?;
+    }
+    let _staging = Artifact::new(&staging_dir);
+
+    // Source files in import order, so the first tables can start right away
+    let mut priority: Vec<String> = Vec::new();
+    for schema in &tables {
+        if !priority.iter().any(|f| f == schema.source_file) {
+            priority.push(schema.source_file.to_string());
+        }
+    }
+
+    let (tx, rx) = mpsc::channel::<String>();
+    let producer = {
+        let staging_dir = staging_dir.clone();
+        thread::spawn(move || {
+            extract_jsonl_prioritized(&archive, &staging_dir, &priority, |name| {
+                tx.send(name.to_string()).ok();
+            })
+        })
+    };
+
+    ui.set_phase(Phase::Converting);
+    ui.log(format!(
+        "Extracting to {:?} and converting to SQLite...",
+        fetched.build_dir
+    ));
+
+    let mut extracted: HashSet<String> = HashSet::new();
+    let converted = convert_when_ready(&staging_dir, output_db, tables, &options, ui, |source| {
+        while !extracted.contains(source) {
+            match rx.recv() {
+                Ok(name) => {
+                    extracted.insert(name);
+                }
+                // Extraction finished (or failed) without producing this file
+                Err(_) => break,
+            }
+        }
+    });
+
+    // An extraction failure explains any conversion failure, so report it first
+    producer
+        .join()
+        .map_err(|_| anyhow!("Extraction thread panicked"))?
+        .context("Failed to extract SDE archive")?;
+    let total_records = converted?;
+
+    // Publish the fully extracted build to the cache
+    if fetched.build_dir.exists() {
+        fs::remove_dir_all(&fetched.build_dir).context("Failed to replace cached build")?;
+    }
+    fs::rename(&staging_dir, &fetched.build_dir).context("Failed to move extracted SDE")?;
+    ui.log("Extraction complete");
+
+    finish_download(&cache, &fetched, &settings.retention, ui);
+
+    Ok(SyncOutcome::Converted {
+        info: fetched.info,
+        total_records,
+    })
+}
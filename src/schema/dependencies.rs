@@ -108,6 +108,9 @@ impl DependencyResolver {
     }
 
     /// Topological sort of tables by dependencies
+    ///
+    /// Tables are visited in `ALL_TABLES` order so the result is stable across runs
+    /// (iterating the `HashSet` directly would order independent tables randomly).
     fn topological_sort(
         &self,
         included: &HashSet<&str>,
@@ -116,7 +119,7 @@ impl DependencyResolver {
         let mut visited: HashSet<&str> = HashSet::new();
         let mut temp_visited: HashSet<&str> = HashSet::new();
 
-        for table_name in included {
+        for table_name in in_registry_order(included.iter().copied()) {
             if !visited.contains(table_name) {
                 self.visit(
                     table_name,
@@ -149,9 +152,9 @@ impl DependencyResolver {
         temp_visited.insert(name);
 
         if let Some(deps) = self.deps.get(name) {
-            for dep in deps {
+            for dep in in_registry_order(deps.iter().copied()) {
                 // Skip self-references (e.g., market_groups.parent_group_id -> market_groups)
-                if *dep != name && included.contains(dep) {
+                if dep != name && included.contains(dep) {
                     self.visit(dep, included, visited, temp_visited, result)?;
                 }
             }
@@ -168,6 +171,16 @@ impl DependencyResolver {
     }
 }
 
+/// Sort table names by their position in `ALL_TABLES` (unknown names last, alphabetically)
+fn in_registry_order<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut names: Vec<&str> = names.collect();
+    names.sort_by_key(|name| {
+        let position = ALL_TABLES.iter().position(|t| t.name == *name);
+        (position.unwrap_or(usize::MAX), *name)
+    });
+    names
+}
+
 impl Default for DependencyResolver {
     fn default() -> Self {
         Self::new()
@@ -197,6 +210,56 @@ mod tests {
         assert!(groups_pos < types_pos);
     }
 
+    #[test]
+    fn test_resolve_order_is_deterministic() {
+        let requested = ["types", "map_stargates", "blueprint_materials", "skins"];
+        let first: Vec<_> = DependencyResolver::new()
+            .resolve_includes(&requested)
+            .unwrap()
+            .iter()
+            .map(|t| t.name)
+            .collect();
+
+        for _ in 0..10 {
+            // Fresh resolvers get fresh HashMap/HashSet seeds
+            let again: Vec<_> = DependencyResolver::new()
+                .resolve_includes(&requested)
+                .unwrap()
+                .iter()
+                .map(|t| t.name)
+                .collect();
+            assert_eq!(first, again);
+        }
+    }
+
+    #[test]
+    fn test_foreign_keys_follow_registry_order() {
+        // ALL_TABLES is imported as-is, so every parent must come before its children
+        let position = |name: &str| ALL_TABLES.iter().position(|t| t.name == name);
+        for (i, table) in ALL_TABLES.iter().enumerate() {
+            for fk in table.foreign_keys {
+                let parent = position(fk.references_table).unwrap_or_else(|| {
+                    panic!(
+                        "{}.{}: unknown table {}",
+                        table.name, fk.column, fk.references_table
+                    )
+                });
+                assert!(
+                    parent < i || fk.references_table == table.name,
+                    "{}.{} references {}, which is registered after it",
+                    table.name,
+                    fk.column,
+                    fk.references_table
+                );
+            }
+        }
+
+        // No cycles: every table resolves
+        let all: Vec<&str> = ALL_TABLES.iter().map(|t| t.name).collect();
+        let resolved = DependencyResolver::new().resolve_includes(&all).unwrap();
+        assert_eq!(resolved.len(), ALL_TABLES.len());
+    }
+
     #[test]
     fn test_unknown_table_error() {
         let resolver = DependencyResolver::new();
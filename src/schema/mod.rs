@@ -1,7 +1,11 @@
 pub mod dependencies;
 pub mod tables;
 pub mod types;
+pub mod validate;
+pub mod views;
 
 pub use dependencies::*;
 pub use tables::*;
 pub use types::*;
+pub use validate::*;
+pub use views::*;
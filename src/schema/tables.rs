@@ -11,13 +11,16 @@ pub static CATEGORIES: TableSchema = TableSchema {
     source_file: "categories.jsonl",
     columns: &[
         Column::required("id", ColumnType::Integer),
-        Column::new("name", ColumnType::Localized),
-        Column::new("published", ColumnType::Boolean),
+        Column::new("name", ColumnType::Localized).localized_require_en(),
+        Column::new("published", ColumnType::Boolean).default(ColumnDefault::Integer(0)),
     ],
     foreign_keys: &[],
     indexes: &[Index::on(&["name_en"]), Index::on(&["published"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static DOGMA_ATTRIBUTE_CATEGORIES: TableSchema = TableSchema {
@@ -32,6 +35,9 @@ pub static DOGMA_ATTRIBUTE_CATEGORIES: TableSchema = TableSchema {
     indexes: &[Index::on(&["name"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static DOGMA_UNITS: TableSchema = TableSchema {
@@ -47,6 +53,9 @@ pub static DOGMA_UNITS: TableSchema = TableSchema {
     indexes: &[Index::on(&["name"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static ICONS: TableSchema = TableSchema {
@@ -61,6 +70,9 @@ pub static ICONS: TableSchema = TableSchema {
     indexes: &[],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static GRAPHICS: TableSchema = TableSchema {
@@ -73,11 +85,16 @@ pub static GRAPHICS: TableSchema = TableSchema {
         Column::new("sof_faction_name", ColumnType::Text),
         Column::new("sof_hull_name", ColumnType::Text),
         Column::new("sof_race_name", ColumnType::Text),
+        Column::new("sof_layout", ColumnType::Json),
+        Column::new("icon_folder", ColumnType::Text).json("iconInfo.folder"),
     ],
     foreign_keys: &[],
     indexes: &[],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static AGENT_TYPES: TableSchema = TableSchema {
@@ -91,6 +108,9 @@ pub static AGENT_TYPES: TableSchema = TableSchema {
     indexes: &[],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static STATION_SERVICES: TableSchema = TableSchema {
@@ -104,6 +124,9 @@ pub static STATION_SERVICES: TableSchema = TableSchema {
     indexes: &[Index::on(&["service_name"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static CORPORATION_ACTIVITIES: TableSchema = TableSchema {
@@ -117,6 +140,9 @@ pub static CORPORATION_ACTIVITIES: TableSchema = TableSchema {
     indexes: &[Index::on(&["name_en"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static META_GROUPS: TableSchema = TableSchema {
@@ -129,10 +155,13 @@ pub static META_GROUPS: TableSchema = TableSchema {
         Column::new("icon_id", ColumnType::Integer),
         Column::new("icon_suffix", ColumnType::Text),
     ],
-    foreign_keys: &[],
+    foreign_keys: &[ForeignKey::new("icon_id", "icons")],
     indexes: &[Index::on(&["name_en"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static CHARACTER_ATTRIBUTES: TableSchema = TableSchema {
@@ -146,23 +175,30 @@ pub static CHARACTER_ATTRIBUTES: TableSchema = TableSchema {
         Column::new("notes", ColumnType::Text),
         Column::new("icon_id", ColumnType::Integer),
     ],
-    foreign_keys: &[],
+    foreign_keys: &[ForeignKey::new("icon_id", "icons")],
     indexes: &[Index::on(&["name_en"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static TRANSLATION_LANGUAGES: TableSchema = TableSchema {
     name: "translation_languages",
     source_file: "translationLanguages.jsonl",
     columns: &[
-        Column::required("id", ColumnType::Integer),
+        // Keyed by language code (e.g. "de"), not a numeric ID
+        Column::required("id", ColumnType::Text),
         Column::new("name", ColumnType::Text),
     ],
     foreign_keys: &[],
     indexes: &[],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static SKIN_MATERIALS: TableSchema = TableSchema {
@@ -180,6 +216,9 @@ pub static SKIN_MATERIALS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static LANDMARKS: TableSchema = TableSchema {
@@ -191,14 +230,17 @@ pub static LANDMARKS: TableSchema = TableSchema {
         Column::new("description", ColumnType::Localized),
         Column::new("importance", ColumnType::Integer),
         Column::new("location_id", ColumnType::Integer),
-        Column::new("position_x", ColumnType::Real),
-        Column::new("position_y", ColumnType::Real),
-        Column::new("position_z", ColumnType::Real),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
     ],
     foreign_keys: &[],
     indexes: &[Index::on(&["name_en"]), Index::on(&["location_id"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static NPC_CORPORATION_DIVISIONS: TableSchema = TableSchema {
@@ -215,19 +257,31 @@ pub static NPC_CORPORATION_DIVISIONS: TableSchema = TableSchema {
     indexes: &[Index::on(&["name_en"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
+/// Power, workforce and reagent output of planets and stars (Skyhook/Sovereignty Hub data).
+/// Format: {"_key": 40000002, "power": 300, "workforce": 1300,
+///          "reagent": {"amountPerCycle": 4, "cyclePeriod": 1800, "securedCapacity": 1000,
+///                      "typeID": 81143, "unsecuredCapacity": 500}}
+/// The reagent goes into `planet_resource_types`.
 pub static PLANET_RESOURCES: TableSchema = TableSchema {
     name: "planet_resources",
     source_file: "planetResources.jsonl",
     columns: &[
         Column::required("id", ColumnType::Integer),
         Column::new("power", ColumnType::Integer),
+        Column::new("workforce", ColumnType::Integer),
     ],
     foreign_keys: &[],
     indexes: &[],
-    child_tables: &[],
+    child_tables: &["planet_resource_types"],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static CLONE_GRADES: TableSchema = TableSchema {
@@ -241,6 +295,9 @@ pub static CLONE_GRADES: TableSchema = TableSchema {
     indexes: &[],
     child_tables: &["clone_grade_skills"],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static PLANET_SCHEMATICS: TableSchema = TableSchema {
@@ -255,6 +312,9 @@ pub static PLANET_SCHEMATICS: TableSchema = TableSchema {
     indexes: &[Index::on(&["name_en"])],
     child_tables: &["planet_schematic_pins", "planet_schematic_types"],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 // =============================================================================
@@ -275,6 +335,9 @@ pub static RACES: TableSchema = TableSchema {
     indexes: &[Index::on(&["name_en"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static GROUPS: TableSchema = TableSchema {
@@ -282,9 +345,9 @@ pub static GROUPS: TableSchema = TableSchema {
     source_file: "groups.jsonl",
     columns: &[
         Column::required("id", ColumnType::Integer),
-        Column::new("name", ColumnType::Localized),
+        Column::new("name", ColumnType::Localized).localized_require_en(),
         Column::new("category_id", ColumnType::Integer),
-        Column::new("published", ColumnType::Boolean),
+        Column::new("published", ColumnType::Boolean).default(ColumnDefault::Integer(0)),
         Column::new("anchorable", ColumnType::Boolean),
         Column::new("anchored", ColumnType::Boolean),
         Column::new("fittable_non_singleton", ColumnType::Boolean),
@@ -302,6 +365,9 @@ pub static GROUPS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static DOGMA_ATTRIBUTES: TableSchema = TableSchema {
@@ -320,7 +386,7 @@ pub static DOGMA_ATTRIBUTES: TableSchema = TableSchema {
         Column::new("display_when_zero", ColumnType::Boolean),
         Column::new("high_is_good", ColumnType::Boolean),
         Column::new("icon_id", ColumnType::Integer),
-        Column::new("published", ColumnType::Boolean),
+        Column::new("published", ColumnType::Boolean).default(ColumnDefault::Integer(0)),
         Column::new("stackable", ColumnType::Boolean),
         Column::new("unit_id", ColumnType::Integer),
     ],
@@ -336,6 +402,9 @@ pub static DOGMA_ATTRIBUTES: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static DOGMA_EFFECTS: TableSchema = TableSchema {
@@ -347,13 +416,16 @@ pub static DOGMA_EFFECTS: TableSchema = TableSchema {
         Column::new("description", ColumnType::Text),
         Column::new("display_name", ColumnType::Localized),
         Column::new("effect_category", ColumnType::Integer),
+        Column::new("effect_category_name", ColumnType::Text)
+            .json("effectCategory")
+            .transform(Transform::DecodeEffectCategory),
         Column::new("effect_name", ColumnType::Text),
         Column::new("guid", ColumnType::Text),
         Column::new("icon_id", ColumnType::Integer),
         Column::new("is_assistance", ColumnType::Boolean),
         Column::new("is_offensive", ColumnType::Boolean),
         Column::new("is_warp_safe", ColumnType::Boolean),
-        Column::new("published", ColumnType::Boolean),
+        Column::new("published", ColumnType::Boolean).default(ColumnDefault::Integer(0)),
         Column::new("range_chance", ColumnType::Boolean),
         Column::new("electronic_chance", ColumnType::Boolean),
         Column::new("propulsion_chance", ColumnType::Boolean),
@@ -366,8 +438,19 @@ pub static DOGMA_EFFECTS: TableSchema = TableSchema {
         Column::new("tracking_speed_attribute_id", ColumnType::Integer),
         Column::new("fitting_usage_chance_attribute_id", ColumnType::Integer),
         Column::new("resistance_attribute_id", ColumnType::Integer),
+        // Kept as a JSON array; query with json_each()
+        Column::new("modifier_info", ColumnType::Json),
+    ],
+    foreign_keys: &[
+        ForeignKey::new("icon_id", "icons"),
+        ForeignKey::new("duration_attribute_id", "dogma_attributes"),
+        ForeignKey::new("discharge_attribute_id", "dogma_attributes"),
+        ForeignKey::new("falloff_attribute_id", "dogma_attributes"),
+        ForeignKey::new("range_attribute_id", "dogma_attributes"),
+        ForeignKey::new("tracking_speed_attribute_id", "dogma_attributes"),
+        ForeignKey::new("fitting_usage_chance_attribute_id", "dogma_attributes"),
+        ForeignKey::new("resistance_attribute_id", "dogma_attributes"),
     ],
-    foreign_keys: &[ForeignKey::new("icon_id", "icons")],
     indexes: &[
         Index::on(&["icon_id"]),
         Index::on(&["name"]),
@@ -375,6 +458,9 @@ pub static DOGMA_EFFECTS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static MAP_REGIONS: TableSchema = TableSchema {
@@ -399,10 +485,13 @@ pub static MAP_REGIONS: TableSchema = TableSchema {
         Column::new("nebula", ColumnType::Integer),
         Column::new("wormhole_class_id", ColumnType::Integer),
     ],
-    foreign_keys: &[],
+    foreign_keys: &[ForeignKey::new("faction_id", "factions")],
     indexes: &[Index::on(&["faction_id"]), Index::on(&["name_en"])],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static MARKET_GROUPS: TableSchema = TableSchema {
@@ -427,6 +516,9 @@ pub static MARKET_GROUPS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static STATION_OPERATIONS: TableSchema = TableSchema {
@@ -445,13 +537,16 @@ pub static STATION_OPERATIONS: TableSchema = TableSchema {
         Column::new("manufacturing_factor", ColumnType::Real),
         Column::new("research_factor", ColumnType::Real),
     ],
-    foreign_keys: &[],
+    foreign_keys: &[ForeignKey::new("activity_id", "corporation_activities")],
     indexes: &[
         Index::on(&["activity_id"]),
         Index::on(&["operation_name_en"]),
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static SKINS: TableSchema = TableSchema {
@@ -461,7 +556,7 @@ pub static SKINS: TableSchema = TableSchema {
         Column::required("id", ColumnType::Integer),
         Column::new("internal_name", ColumnType::Text),
         Column::new("skin_material_id", ColumnType::Integer),
-        Column::new("allow_ccp_devs", ColumnType::Boolean),
+        Column::new("allow_ccp_devs", ColumnType::Boolean).json("allowCCPDevs"),
         Column::new("visible_serenity", ColumnType::Boolean),
         Column::new("visible_tranquility", ColumnType::Boolean),
     ],
@@ -469,6 +564,9 @@ pub static SKINS: TableSchema = TableSchema {
     indexes: &[Index::on(&["skin_material_id"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 // =============================================================================
@@ -493,6 +591,7 @@ pub static BLOODLINES: TableSchema = TableSchema {
     ],
     foreign_keys: &[
         ForeignKey::new("race_id", "races"),
+        ForeignKey::new("corporation_id", "npc_corporations"),
         ForeignKey::new("icon_id", "icons"),
     ],
     indexes: &[
@@ -502,6 +601,9 @@ pub static BLOODLINES: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static FACTIONS: TableSchema = TableSchema {
@@ -519,7 +621,13 @@ pub static FACTIONS: TableSchema = TableSchema {
         Column::new("size_factor", ColumnType::Real),
         Column::new("unique_name", ColumnType::Boolean),
     ],
-    foreign_keys: &[ForeignKey::new("icon_id", "icons")],
+    // solar_system_id has no foreign key: map_regions.faction_id already makes
+    // the map depend on factions
+    foreign_keys: &[
+        ForeignKey::new("corporation_id", "npc_corporations"),
+        ForeignKey::new("militia_corporation_id", "npc_corporations"),
+        ForeignKey::new("icon_id", "icons"),
+    ],
     indexes: &[
         Index::on(&["icon_id"]),
         Index::on(&["solar_system_id"]),
@@ -529,6 +637,9 @@ pub static FACTIONS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static NPC_CORPORATIONS: TableSchema = TableSchema {
@@ -558,6 +669,9 @@ pub static NPC_CORPORATIONS: TableSchema = TableSchema {
     indexes: &[Index::on(&["name_en"]), Index::on(&["ticker_name"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static MAP_CONSTELLATIONS: TableSchema = TableSchema {
@@ -579,7 +693,10 @@ pub static MAP_CONSTELLATIONS: TableSchema = TableSchema {
         Column::new("min_z", ColumnType::Real),
         Column::new("radius", ColumnType::Real),
     ],
-    foreign_keys: &[ForeignKey::new("region_id", "map_regions")],
+    foreign_keys: &[
+        ForeignKey::new("region_id", "map_regions"),
+        ForeignKey::new("faction_id", "factions"),
+    ],
     indexes: &[
         Index::on(&["region_id"]),
         Index::on(&["faction_id"]),
@@ -587,6 +704,9 @@ pub static MAP_CONSTELLATIONS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static TYPES: TableSchema = TableSchema {
@@ -594,7 +714,7 @@ pub static TYPES: TableSchema = TableSchema {
     source_file: "types.jsonl",
     columns: &[
         Column::required("id", ColumnType::Integer),
-        Column::new("name", ColumnType::Localized),
+        Column::new("name", ColumnType::Localized).localized_require_en(),
         Column::new("description", ColumnType::Localized),
         Column::new("group_id", ColumnType::Integer),
         Column::new("graphic_id", ColumnType::Integer),
@@ -608,7 +728,7 @@ pub static TYPES: TableSchema = TableSchema {
         Column::new("portion_size", ColumnType::Integer),
         Column::new("capacity", ColumnType::Real),
         Column::new("base_price", ColumnType::Real),
-        Column::new("published", ColumnType::Boolean),
+        Column::new("published", ColumnType::Boolean).default(ColumnDefault::Integer(0)),
         Column::new("race_id", ColumnType::Integer),
         Column::new("faction_id", ColumnType::Integer),
         Column::new("sof_faction_name", ColumnType::Text),
@@ -635,6 +755,9 @@ pub static TYPES: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static COMPRESSIBLE_TYPES: TableSchema = TableSchema {
@@ -651,6 +774,9 @@ pub static COMPRESSIBLE_TYPES: TableSchema = TableSchema {
     indexes: &[Index::on(&["compressed_type_id"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static NPC_CHARACTERS: TableSchema = TableSchema {
@@ -665,7 +791,7 @@ pub static NPC_CHARACTERS: TableSchema = TableSchema {
         Column::new("location_id", ColumnType::Integer),
         Column::new("ceo", ColumnType::Boolean),
         Column::new("gender", ColumnType::Boolean),
-        Column::new("start_date", ColumnType::Text),
+        Column::new("start_date", ColumnType::Timestamp),
         Column::new("unique_name", ColumnType::Boolean),
     ],
     foreign_keys: &[
@@ -681,6 +807,9 @@ pub static NPC_CHARACTERS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 /// Sovereignty upgrades - simple table with flattened nested "fuel" object
@@ -703,6 +832,9 @@ pub static SOVEREIGNTY_UPGRADES: TableSchema = TableSchema {
     indexes: &[Index::on(&["fuel_type_id"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 // =============================================================================
@@ -736,6 +868,9 @@ pub static ANCESTRIES: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static MAP_SOLAR_SYSTEMS: TableSchema = TableSchema {
@@ -748,6 +883,9 @@ pub static MAP_SOLAR_SYSTEMS: TableSchema = TableSchema {
         Column::new("region_id", ColumnType::Integer),
         Column::new("star_id", ColumnType::Integer),
         Column::new("security_status", ColumnType::Real),
+        Column::new("security_rounded", ColumnType::Real)
+            .json("securityStatus")
+            .transform(Transform::RoundSecurity),
         Column::new("security_class", ColumnType::Text),
         Column::new("luminosity", ColumnType::Real),
         Column::new("radius", ColumnType::Real),
@@ -757,9 +895,9 @@ pub static MAP_SOLAR_SYSTEMS: TableSchema = TableSchema {
         Column::new("hub", ColumnType::Boolean),
         Column::new("international", ColumnType::Boolean),
         Column::new("regional", ColumnType::Boolean),
-        Column::new("position_x", ColumnType::Real),
-        Column::new("position_y", ColumnType::Real),
-        Column::new("position_z", ColumnType::Real),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
     ],
     foreign_keys: &[
         ForeignKey::new("constellation_id", "map_constellations"),
@@ -773,6 +911,9 @@ pub static MAP_SOLAR_SYSTEMS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static BLUEPRINTS: TableSchema = TableSchema {
@@ -782,12 +923,15 @@ pub static BLUEPRINTS: TableSchema = TableSchema {
         Column::required("id", ColumnType::Integer),
         Column::new("blueprint_type_id", ColumnType::Integer),
         Column::new("max_production_limit", ColumnType::Integer),
-        Column::new("copying_time", ColumnType::Integer),
-        Column::new("manufacturing_time", ColumnType::Integer),
-        Column::new("research_material_time", ColumnType::Integer),
-        Column::new("research_time_time", ColumnType::Integer),
-        Column::new("invention_time", ColumnType::Integer),
-        Column::new("reaction_time", ColumnType::Integer),
+        Column::new("copying_time", ColumnType::Integer).json("activities.copying.time"),
+        Column::new("manufacturing_time", ColumnType::Integer)
+            .json("activities.manufacturing.time"),
+        Column::new("research_material_time", ColumnType::Integer)
+            .json("activities.research_material.time"),
+        Column::new("research_time_time", ColumnType::Integer)
+            .json("activities.research_time.time"),
+        Column::new("invention_time", ColumnType::Integer).json("activities.invention.time"),
+        Column::new("reaction_time", ColumnType::Integer).json("activities.reaction.time"),
     ],
     foreign_keys: &[ForeignKey::new("blueprint_type_id", "types")],
     indexes: &[Index::on(&["blueprint_type_id"])],
@@ -797,6 +941,9 @@ pub static BLUEPRINTS: TableSchema = TableSchema {
         "blueprint_skills",
     ],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static SKIN_LICENSES: TableSchema = TableSchema {
@@ -815,6 +962,9 @@ pub static SKIN_LICENSES: TableSchema = TableSchema {
     indexes: &[Index::on(&["skin_id"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static CERTIFICATES: TableSchema = TableSchema {
@@ -830,6 +980,9 @@ pub static CERTIFICATES: TableSchema = TableSchema {
     indexes: &[Index::on(&["name_en"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 // =============================================================================
@@ -843,13 +996,13 @@ pub static MAP_STARS: TableSchema = TableSchema {
         Column::required("id", ColumnType::Integer),
         Column::new("solar_system_id", ColumnType::Integer),
         Column::new("type_id", ColumnType::Integer),
-        Column::new("age", ColumnType::Real),
-        Column::new("life", ColumnType::Real),
-        Column::new("locked", ColumnType::Boolean),
-        Column::new("luminosity", ColumnType::Real),
+        Column::new("age", ColumnType::Real).json("statistics.age"),
+        Column::new("life", ColumnType::Real).json("statistics.life"),
+        Column::new("locked", ColumnType::Boolean).json("statistics.locked"),
+        Column::new("luminosity", ColumnType::Real).json("statistics.luminosity"),
         Column::new("radius", ColumnType::Real),
-        Column::new("spectral_class", ColumnType::Text),
-        Column::new("temperature", ColumnType::Real),
+        Column::new("spectral_class", ColumnType::Text).json("statistics.spectralClass"),
+        Column::new("temperature", ColumnType::Real).json("statistics.temperature"),
     ],
     foreign_keys: &[
         ForeignKey::new("solar_system_id", "map_solar_systems"),
@@ -858,6 +1011,9 @@ pub static MAP_STARS: TableSchema = TableSchema {
     indexes: &[Index::on(&["solar_system_id"])],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static MAP_PLANETS: TableSchema = TableSchema {
@@ -872,9 +1028,9 @@ pub static MAP_PLANETS: TableSchema = TableSchema {
         Column::new("orbit_id", ColumnType::Integer),
         Column::new("orbit_index", ColumnType::Integer),
         Column::new("radius", ColumnType::Real),
-        Column::new("position_x", ColumnType::Real),
-        Column::new("position_y", ColumnType::Real),
-        Column::new("position_z", ColumnType::Real),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
     ],
     foreign_keys: &[
         ForeignKey::new("solar_system_id", "map_solar_systems"),
@@ -883,6 +1039,9 @@ pub static MAP_PLANETS: TableSchema = TableSchema {
     indexes: &[Index::on(&["solar_system_id"]), Index::on(&["type_id"])],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static MAP_MOONS: TableSchema = TableSchema {
@@ -897,9 +1056,9 @@ pub static MAP_MOONS: TableSchema = TableSchema {
         Column::new("orbit_id", ColumnType::Integer),
         Column::new("orbit_index", ColumnType::Integer),
         Column::new("radius", ColumnType::Real),
-        Column::new("position_x", ColumnType::Real),
-        Column::new("position_y", ColumnType::Real),
-        Column::new("position_z", ColumnType::Real),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
     ],
     foreign_keys: &[
         ForeignKey::new("solar_system_id", "map_solar_systems"),
@@ -909,6 +1068,9 @@ pub static MAP_MOONS: TableSchema = TableSchema {
     indexes: &[Index::on(&["planet_id"]), Index::on(&["type_id"])],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static MAP_ASTEROID_BELTS: TableSchema = TableSchema {
@@ -922,9 +1084,9 @@ pub static MAP_ASTEROID_BELTS: TableSchema = TableSchema {
         Column::new("celestial_index", ColumnType::Integer),
         Column::new("orbit_id", ColumnType::Integer),
         Column::new("orbit_index", ColumnType::Integer),
-        Column::new("position_x", ColumnType::Real),
-        Column::new("position_y", ColumnType::Real),
-        Column::new("position_z", ColumnType::Real),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
     ],
     foreign_keys: &[
         ForeignKey::new("solar_system_id", "map_solar_systems"),
@@ -934,6 +1096,9 @@ pub static MAP_ASTEROID_BELTS: TableSchema = TableSchema {
     indexes: &[Index::on(&["planet_id"])],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static MAP_STARGATES: TableSchema = TableSchema {
@@ -945,9 +1110,9 @@ pub static MAP_STARGATES: TableSchema = TableSchema {
         Column::new("type_id", ColumnType::Integer),
         Column::new("destination_stargate_id", ColumnType::Integer),
         Column::new("destination_solar_system_id", ColumnType::Integer),
-        Column::new("position_x", ColumnType::Real),
-        Column::new("position_y", ColumnType::Real),
-        Column::new("position_z", ColumnType::Real),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
     ],
     foreign_keys: &[
         ForeignKey::new("solar_system_id", "map_solar_systems"),
@@ -961,6 +1126,39 @@ pub static MAP_STARGATES: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
+};
+
+/// Wormhole system effects (from mapSecondarySuns.jsonl)
+/// Format: {"_key": 40000001, "effectBeaconTypeID": 30574, "solarSystemID": 31000005, "typeID": 45041, "position": {...}}
+pub static MAP_SECONDARY_SUNS: TableSchema = TableSchema {
+    name: "map_secondary_suns",
+    source_file: "mapSecondarySuns.jsonl",
+    columns: &[
+        Column::required("id", ColumnType::Integer),
+        Column::new("solar_system_id", ColumnType::Integer),
+        Column::new("type_id", ColumnType::Integer),
+        Column::new("effect_beacon_type_id", ColumnType::Integer),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
+    ],
+    foreign_keys: &[
+        ForeignKey::new("solar_system_id", "map_solar_systems"),
+        ForeignKey::new("type_id", "types"),
+        ForeignKey::new("effect_beacon_type_id", "types"),
+    ],
+    indexes: &[
+        Index::on(&["solar_system_id"]),
+        Index::on(&["effect_beacon_type_id"]),
+    ],
+    child_tables: &[],
+    array_source: None,
+    strict: true,
+    without_rowid: true,
+    primary_key: &[],
 };
 
 pub static NPC_STATIONS: TableSchema = TableSchema {
@@ -979,9 +1177,9 @@ pub static NPC_STATIONS: TableSchema = TableSchema {
         Column::new("reprocessing_hangar_flag", ColumnType::Integer),
         Column::new("reprocessing_stations_take", ColumnType::Real),
         Column::new("use_operation_name", ColumnType::Boolean),
-        Column::new("position_x", ColumnType::Real),
-        Column::new("position_y", ColumnType::Real),
-        Column::new("position_z", ColumnType::Real),
+        Column::new("position_x", ColumnType::Real).json("position.x"),
+        Column::new("position_y", ColumnType::Real).json("position.y"),
+        Column::new("position_z", ColumnType::Real).json("position.z"),
     ],
     foreign_keys: &[
         ForeignKey::new("solar_system_id", "map_solar_systems"),
@@ -997,6 +1195,9 @@ pub static NPC_STATIONS: TableSchema = TableSchema {
     ],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 pub static AGENTS_IN_SPACE: TableSchema = TableSchema {
@@ -1016,6 +1217,9 @@ pub static AGENTS_IN_SPACE: TableSchema = TableSchema {
     indexes: &[Index::on(&["solar_system_id"]), Index::on(&["type_id"])],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 // =============================================================================
@@ -1040,6 +1244,9 @@ pub static TYPE_DOGMA_ATTRIBUTES: TableSchema = TableSchema {
         array_field: "dogmaAttributes",
         parent_id_column: "type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["type_id", "attribute_id"],
 };
 
 pub static TYPE_DOGMA_EFFECTS: TableSchema = TableSchema {
@@ -1060,6 +1267,9 @@ pub static TYPE_DOGMA_EFFECTS: TableSchema = TableSchema {
         array_field: "dogmaEffects",
         parent_id_column: "type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["type_id", "effect_id"],
 };
 
 pub static TYPE_MATERIALS: TableSchema = TableSchema {
@@ -1080,6 +1290,9 @@ pub static TYPE_MATERIALS: TableSchema = TableSchema {
         array_field: "materials",
         parent_id_column: "type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["type_id", "material_type_id"],
 };
 
 pub static BLUEPRINT_MATERIALS: TableSchema = TableSchema {
@@ -1105,6 +1318,9 @@ pub static BLUEPRINT_MATERIALS: TableSchema = TableSchema {
         activity_column: "activity",
         array_field: "materials",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["blueprint_id", "activity", "type_id"],
 };
 
 pub static BLUEPRINT_PRODUCTS: TableSchema = TableSchema {
@@ -1125,12 +1341,16 @@ pub static BLUEPRINT_PRODUCTS: TableSchema = TableSchema {
         Index::on(&["blueprint_id"]),
         Index::on(&["type_id"]),
         Index::on(&["activity"]),
+        Index::on(&["type_id", "activity"]),
     ],
     child_tables: &[],
     array_source: Some(ArraySource::BlueprintActivity {
         activity_column: "activity",
         array_field: "products",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["blueprint_id", "activity", "type_id"],
 };
 
 pub static BLUEPRINT_SKILLS: TableSchema = TableSchema {
@@ -1156,6 +1376,9 @@ pub static BLUEPRINT_SKILLS: TableSchema = TableSchema {
         activity_column: "activity",
         array_field: "skills",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["blueprint_id", "activity", "type_id"],
 };
 
 pub static CLONE_GRADE_SKILLS: TableSchema = TableSchema {
@@ -1176,6 +1399,36 @@ pub static CLONE_GRADE_SKILLS: TableSchema = TableSchema {
         array_field: "skills",
         parent_id_column: "clone_grade_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["clone_grade_id", "type_id"],
+};
+
+/// Reagent a planet harvests, from the single `reagent` object of planetResources.jsonl
+pub static PLANET_RESOURCE_TYPES: TableSchema = TableSchema {
+    name: "planet_resource_types",
+    source_file: "planetResources.jsonl",
+    columns: &[
+        Column::required("planet_id", ColumnType::Integer),
+        Column::required("type_id", ColumnType::Integer),
+        Column::new("amount_per_cycle", ColumnType::Integer),
+        Column::new("cycle_period", ColumnType::Integer),
+        Column::new("secured_capacity", ColumnType::Integer),
+        Column::new("unsecured_capacity", ColumnType::Integer),
+    ],
+    foreign_keys: &[
+        ForeignKey::new("planet_id", "planet_resources"),
+        ForeignKey::new("type_id", "types"),
+    ],
+    indexes: &[Index::on(&["planet_id"]), Index::on(&["type_id"])],
+    child_tables: &[],
+    array_source: Some(ArraySource::SingleObject {
+        field: "reagent",
+        parent_id_column: "planet_id",
+    }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["planet_id", "type_id"],
 };
 
 pub static CONTRABAND_TYPE_FACTIONS: TableSchema = TableSchema {
@@ -1199,6 +1452,9 @@ pub static CONTRABAND_TYPE_FACTIONS: TableSchema = TableSchema {
         array_field: "factions",
         parent_id_column: "type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["type_id", "faction_id"],
 };
 
 pub static CONTROL_TOWER_RESOURCES: TableSchema = TableSchema {
@@ -1220,6 +1476,9 @@ pub static CONTROL_TOWER_RESOURCES: TableSchema = TableSchema {
         array_field: "resources",
         parent_id_column: "type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["type_id", "resource_type_id"],
 };
 
 pub static DYNAMIC_ITEM_ATTRIBUTES: TableSchema = TableSchema {
@@ -1241,6 +1500,9 @@ pub static DYNAMIC_ITEM_ATTRIBUTES: TableSchema = TableSchema {
         array_field: "attributeIDs",
         parent_id_column: "type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["type_id", "attribute_id"],
 };
 
 pub static PLANET_SCHEMATIC_PINS: TableSchema = TableSchema {
@@ -1261,6 +1523,9 @@ pub static PLANET_SCHEMATIC_PINS: TableSchema = TableSchema {
         parent_id_column: "schematic_id",
         value_column: "pin_type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["schematic_id", "pin_type_id"],
 };
 
 pub static PLANET_SCHEMATIC_TYPES: TableSchema = TableSchema {
@@ -1282,6 +1547,9 @@ pub static PLANET_SCHEMATIC_TYPES: TableSchema = TableSchema {
         array_field: "types",
         parent_id_column: "schematic_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["schematic_id", "type_id"],
 };
 
 // =============================================================================
@@ -1310,6 +1578,9 @@ pub static TYPE_ROLE_BONUSES: TableSchema = TableSchema {
         array_field: "roleBonuses",
         parent_id_column: "type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 /// Trait bonuses for types based on skills (from typeBonus.jsonl types array)
@@ -1341,6 +1612,9 @@ pub static TYPE_TRAIT_BONUSES: TableSchema = TableSchema {
         parent_id_column: "type_id",
         nested_key_column: "skill_type_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 /// Mastery requirements for types (from masteries.jsonl)
@@ -1367,6 +1641,9 @@ pub static TYPE_MASTERIES: TableSchema = TableSchema {
         parent_id_column: "type_id",
         level_key_column: "mastery_level",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &["type_id", "mastery_level", "certificate_id"],
 };
 
 // =============================================================================
@@ -1391,6 +1668,9 @@ pub static DBUFF_COLLECTIONS: TableSchema = TableSchema {
         "dbuff_location_group_modifiers",
     ],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 /// Dbuff item modifiers junction table
@@ -1415,6 +1695,9 @@ pub static DBUFF_ITEM_MODIFIERS: TableSchema = TableSchema {
         array_field: "itemModifiers",
         parent_id_column: "collection_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 /// Dbuff location modifiers junction table
@@ -1439,6 +1722,9 @@ pub static DBUFF_LOCATION_MODIFIERS: TableSchema = TableSchema {
         array_field: "locationModifiers",
         parent_id_column: "collection_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 /// Dbuff location group modifiers junction table
@@ -1466,26 +1752,29 @@ pub static DBUFF_LOCATION_GROUP_MODIFIERS: TableSchema = TableSchema {
         array_field: "locationGroupModifiers",
         parent_id_column: "collection_id",
     }),
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 // =============================================================================
-// Freelance Job Schemas (complex nested structure - simplified for now)
+// Freelance Job Schemas
 // =============================================================================
 
-/// Freelance job schemas - simplified table (complex nested localized content)
-/// Full structure has deeply nested localized content that may need custom handling
+/// Freelance job schemas - only the id is imported for now.
+/// The name, description and nested job content have not been checked against a real
+/// freelanceJobSchemas.jsonl yet; import it with `--raw-json freelance_job_schemas` to query them.
 pub static FREELANCE_JOB_SCHEMAS: TableSchema = TableSchema {
     name: "freelance_job_schemas",
     source_file: "freelanceJobSchemas.jsonl",
-    columns: &[
-        Column::required("id", ColumnType::Integer),
-        // TODO: Expand with more columns as needed - structure is complex with
-        // deeply nested localized content
-    ],
+    columns: &[Column::required("id", ColumnType::Integer)],
     foreign_keys: &[],
     indexes: &[],
     child_tables: &[],
     array_source: None,
+    strict: false,
+    without_rowid: false,
+    primary_key: &[],
 };
 
 // =============================================================================
@@ -1519,18 +1808,18 @@ pub static ALL_TABLES: &[&TableSchema] = &[
     &GROUPS,
     &DOGMA_ATTRIBUTES,
     &DOGMA_EFFECTS,
-    &MAP_REGIONS,
     &MARKET_GROUPS,
     &STATION_OPERATIONS,
     &SKINS,
+    &NPC_CORPORATIONS,
     // Wave 3: Level 2 deps
     &BLOODLINES,
     &FACTIONS,
-    &NPC_CORPORATIONS,
-    &MAP_CONSTELLATIONS,
     &TYPES,
     &SOVEREIGNTY_UPGRADES,
     // Wave 4: Level 3 deps
+    &MAP_REGIONS,
+    &MAP_CONSTELLATIONS,
     &ANCESTRIES,
     &MAP_SOLAR_SYSTEMS,
     &BLUEPRINTS,
@@ -1544,6 +1833,7 @@ pub static ALL_TABLES: &[&TableSchema] = &[
     &MAP_MOONS,
     &MAP_ASTEROID_BELTS,
     &MAP_STARGATES,
+    &MAP_SECONDARY_SUNS,
     &NPC_STATIONS,
     &AGENTS_IN_SPACE,
     // Wave 6: Junction tables (from nested arrays)
@@ -1554,6 +1844,7 @@ pub static ALL_TABLES: &[&TableSchema] = &[
     &BLUEPRINT_PRODUCTS,
     &BLUEPRINT_SKILLS,
     &CLONE_GRADE_SKILLS,
+    &PLANET_RESOURCE_TYPES,
     &CONTRABAND_TYPE_FACTIONS,
     &CONTROL_TOWER_RESOURCES,
     &DYNAMIC_ITEM_ATTRIBUTES,
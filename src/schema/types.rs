@@ -14,6 +14,8 @@ pub enum ColumnType {
     Localized,
     /// JSON blob stored as text
     Json,
+    /// Date/time stored as UTC RFC 3339 text (e.g. `2003-05-20T00:00:00Z`)
+    Timestamp,
 }
 
 /// Column definition
@@ -23,8 +25,56 @@ pub struct Column {
     pub col_type: ColumnType,
     pub nullable: bool,
     /// Override JSON field name (default: camelCase of name)
-    /// Use "_key" for array item IDs
+    /// Use "_key" for array item IDs; dotted paths reach into nested
+    /// objects and arrays (e.g. "position.x", "position.0")
     pub json_field: Option<&'static str>,
+    /// Function applied to the JSON value instead of the default conversion
+    pub transform: Option<Transform>,
+    /// For localized columns: the English (`_en`) expansion is NOT NULL
+    pub require_en: bool,
+    /// Value stored when the JSON field is absent (NULL if unset), also declared as
+    /// the column's `DEFAULT`
+    pub default: Option<ColumnDefault>,
+}
+
+/// Default value of a column (see `Column::default`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnDefault {
+    Integer(i64),
+    Real(f64),
+    Text(&'static str),
+}
+
+impl ColumnDefault {
+    /// The value stored for a record without the column's field
+    pub fn to_sql_value(self) -> crate::parser::SqlValue {
+        use crate::parser::SqlValue;
+        match self {
+            ColumnDefault::Integer(n) => SqlValue::Integer(n),
+            ColumnDefault::Real(f) => SqlValue::Real(f),
+            ColumnDefault::Text(s) => SqlValue::Text(s.to_string()),
+        }
+    }
+
+    /// SQL literal for a `DEFAULT` clause
+    pub fn sql_literal(self) -> String {
+        match self {
+            ColumnDefault::Integer(n) => n.to_string(),
+            ColumnDefault::Real(f) => format!("{:?}", f),
+            ColumnDefault::Text(s) => format!("'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
+/// Value transform applied to a column during import
+#[derive(Debug, Clone, Copy)]
+pub enum Transform {
+    /// Round a security status the way the game displays it (one decimal place)
+    RoundSecurity,
+    /// Decode a dogma effect category id to its name (e.g. 1 -> "active")
+    DecodeEffectCategory,
+    /// User-supplied conversion from the raw JSON value
+    Custom(fn(&serde_json::Value) -> crate::parser::SqlValue),
 }
 
 impl Column {
@@ -35,6 +85,9 @@ impl Column {
             col_type,
             nullable: true,
             json_field: None,
+            transform: None,
+            require_en: false,
+            default: None,
         }
     }
 
@@ -45,6 +98,9 @@ impl Column {
             col_type,
             nullable: false,
             json_field: None,
+            transform: None,
+            require_en: false,
+            default: None,
         }
     }
 
@@ -55,6 +111,31 @@ impl Column {
             ..self
         }
     }
+
+    /// Require the English text of a localized column (`name_en TEXT NOT NULL`);
+    /// records without it fail the import
+    pub const fn localized_require_en(self) -> Self {
+        Self {
+            require_en: true,
+            ..self
+        }
+    }
+
+    /// Apply a transform to the JSON value during import
+    pub const fn transform(self, transform: Transform) -> Self {
+        Self {
+            transform: Some(transform),
+            ..self
+        }
+    }
+
+    /// Store `value` instead of NULL when the JSON field is absent
+    pub const fn default(self, value: ColumnDefault) -> Self {
+        Self {
+            default: Some(value),
+            ..self
+        }
+    }
 }
 
 /// Foreign key reference
@@ -142,6 +223,61 @@ pub enum ArraySource {
         /// Column to store the first-level _key (e.g., "mastery_level")
         level_key_column: &'static str,
     },
+    /// Object keyed by id: `{"_key": X, "fieldName": {"123": {...}, "456": {...}}}`
+    NestedMap {
+        /// JSON field containing the object (e.g., "resources")
+        field: &'static str,
+        /// Column to store the parent's _key value (e.g., "planet_id")
+        parent_id_column: &'static str,
+        /// Column to store each entry's id, parsed from its object key (e.g., "type_id")
+        key_column: &'static str,
+    },
+    /// Single nested object: `{"_key": X, "fieldName": {...}}`, yielding at most one row
+    SingleObject {
+        /// JSON field containing the object (e.g., "reagent")
+        field: &'static str,
+        /// Column to store the parent's _key value (e.g., "planet_id")
+        parent_id_column: &'static str,
+    },
+}
+
+impl ArraySource {
+    /// Top-level field of the parent record the rows are read from
+    pub fn top_level_field(&self) -> &'static str {
+        match self {
+            ArraySource::Simple { array_field, .. }
+            | ArraySource::SimpleIntArray { array_field, .. }
+            | ArraySource::NestedKeyValue { array_field, .. } => array_field,
+            ArraySource::NestedMap { field, .. } | ArraySource::SingleObject { field, .. } => field,
+            ArraySource::BlueprintActivity { .. } => "activities",
+            ArraySource::DoubleNested { .. } => "_value",
+        }
+    }
+
+    /// Column holding the parent record's key
+    pub fn parent_id_column(&self) -> &'static str {
+        match self {
+            ArraySource::Simple {
+                parent_id_column, ..
+            }
+            | ArraySource::SimpleIntArray {
+                parent_id_column, ..
+            }
+            | ArraySource::NestedKeyValue {
+                parent_id_column, ..
+            }
+            | ArraySource::DoubleNested {
+                parent_id_column, ..
+            }
+            | ArraySource::NestedMap {
+                parent_id_column, ..
+            }
+            | ArraySource::SingleObject {
+                parent_id_column, ..
+            } => parent_id_column,
+            ArraySource::BlueprintActivity { .. } => "blueprint_id",
+        }
+    }
 }
 
 /// Table schema definition
@@ -157,6 +293,15 @@ pub struct TableSchema {
     pub child_tables: &'static [&'static str],
     /// For junction tables: how to extract rows from nested arrays
     pub array_source: Option<ArraySource>,
+    /// Create as a `STRICT` table, so SQLite enforces the declared column types
+    pub strict: bool,
+    /// Create as a `WITHOUT ROWID` table clustered on its primary key, which must be an
+    /// `id` column or `primary_key`; rows are stored in the primary key b-tree, so keep
+    /// this to tables that are mostly looked up or joined by that key
+    pub without_rowid: bool,
+    /// Composite primary key columns (e.g. for junction tables); when empty, an `id`
+    /// column is the primary key
+    pub primary_key: &'static [&'static str],
 }
 
 impl TableSchema {
@@ -167,4 +312,16 @@ impl TableSchema {
             .map(|fk| fk.references_table)
             .collect()
     }
+
+    /// Primary key columns: `primary_key`, else the `id` column; empty when the
+    /// table has neither
+    pub fn primary_key_columns(&self) -> &'static [&'static str] {
+        if !self.primary_key.is_empty() {
+            self.primary_key
+        } else if self.columns.iter().any(|c| c.name == "id") {
+            &["id"]
+        } else {
+            &[]
+        }
+    }
 }
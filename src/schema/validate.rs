@@ -0,0 +1,104 @@
+//! Consistency checks for the static table registry
+
+use super::tables::{get_table, ALL_TABLES};
+use super::types::{ColumnType, TableSchema, LANGUAGES};
+
+/// Check the table registry for mistakes that would otherwise only surface while
+/// converting: dangling `child_tables` or foreign keys, child tables without an
+/// `array_source`, and indexes, keys or foreign keys on columns that do not exist.
+/// Returns every problem found.
+pub fn validate_schema() -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    for schema in ALL_TABLES {
+        for child in schema.child_tables {
+            match get_table(child) {
+                None => errors.push(format!("{}: unknown child table {}", schema.name, child)),
+                Some(child_schema) if child_schema.array_source.is_none() => errors.push(format!(
+                    "{}: child table {} has no array_source",
+                    schema.name, child
+                )),
+                Some(_) => {}
+            }
+        }
+
+        for fk in schema.foreign_keys {
+            if !has_column(schema, fk.column) {
+                errors.push(format!(
+                    "{}: foreign key on unknown column {}",
+                    schema.name, fk.column
+                ));
+            }
+            match get_table(fk.references_table) {
+                None => errors.push(format!(
+                    "{}.{}: references unknown table {}",
+                    schema.name, fk.column, fk.references_table
+                )),
+                Some(parent) if !has_column(parent, fk.references_column) => errors.push(format!(
+                    "{}.{}: references unknown column {}.{}",
+                    schema.name, fk.column, fk.references_table, fk.references_column
+                )),
+                Some(_) => {}
+            }
+        }
+
+        for index in schema.indexes {
+            for column in index.columns {
+                if !has_column(schema, column) {
+                    errors.push(format!(
+                        "{}: index on unknown column {}",
+                        schema.name, column
+                    ));
+                }
+            }
+        }
+
+        for column in schema.primary_key {
+            if !has_column(schema, column) {
+                errors.push(format!(
+                    "{}: primary key on unknown column {}",
+                    schema.name, column
+                ));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Whether `name` is a column of the table once localized columns are expanded
+/// (`name` -> `name_en`, `name_de`, ...)
+fn has_column(schema: &TableSchema, name: &str) -> bool {
+    schema.columns.iter().any(|col| match col.col_type {
+        ColumnType::Localized => LANGUAGES
+            .iter()
+            .any(|lang| name.strip_prefix(col.name) == Some(&format!("_{}", lang))),
+        _ => col.name == name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_valid() {
+        if let Err(errors) = validate_schema() {
+            panic!("Schema errors:\n{}", errors.join("\n"));
+        }
+    }
+
+    #[test]
+    fn test_has_column_expands_localized() {
+        let types = get_table("types").unwrap();
+        assert!(has_column(types, "group_id"));
+        assert!(has_column(types, "name_en"));
+        assert!(has_column(types, "name_zh"));
+        assert!(!has_column(types, "name"));
+        assert!(!has_column(types, "name_xx"));
+    }
+}
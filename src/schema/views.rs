@@ -0,0 +1,54 @@
+/// A convenience view created on top of the imported tables
+#[derive(Debug, Clone)]
+pub struct ViewSchema {
+    pub name: &'static str,
+    /// Tables the view selects from; the view is only created if all are present
+    pub tables: &'static [&'static str],
+    pub select: &'static str,
+}
+
+// =============================================================================
+// Items
+// =============================================================================
+
+/// Icon file of each type that has an icon, for tools rendering item images
+pub static TYPE_ICONS: ViewSchema = ViewSchema {
+    name: "type_icons",
+    tables: &["types", "icons"],
+    select: "SELECT t.id AS type_id, t.name_en, i.icon_file
+    FROM types t
+    JOIN icons i ON i.id = t.icon_id",
+};
+
+// =============================================================================
+// Industry
+// =============================================================================
+
+/// Which blueprint manufactures a given type
+pub static MANUFACTURING_SOURCES: ViewSchema = ViewSchema {
+    name: "manufacturing_sources",
+    tables: &["blueprint_products"],
+    select: "SELECT type_id AS product_type_id, blueprint_id, quantity
+    FROM blueprint_products
+    WHERE activity = 'manufacturing'",
+};
+
+/// Reprocessing yield of each material per unit of a type (materials are listed per
+/// `portion_size` units)
+pub static TYPE_REPROCESSING: ViewSchema = ViewSchema {
+    name: "type_reprocessing",
+    tables: &["type_materials", "types"],
+    select: "SELECT m.type_id, COALESCE(t.portion_size, 1) AS portion_size,
+        m.material_type_id, m.quantity,
+        CAST(m.quantity AS REAL) / COALESCE(t.portion_size, 1) AS quantity_per_unit
+    FROM type_materials m
+    JOIN types t ON t.id = m.type_id",
+};
+
+/// All views, in creation order
+pub static ALL_VIEWS: &[&ViewSchema] = &[&TYPE_ICONS, &MANUFACTURING_SOURCES, &TYPE_REPROCESSING];
+
+/// Get a view by name
+pub fn get_view(name: &str) -> Option<&'static ViewSchema> {
+    ALL_VIEWS.iter().find(|v| v.name == name).copied()
+}
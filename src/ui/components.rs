@@ -45,6 +45,7 @@ impl StatusPanel {
             Phase::Downloading => "↓",
             Phase::Extracting => "⤷",
             Phase::Converting => "⚙",
+            Phase::Merging => "⇉",
             Phase::Complete => "✓",
         };
 
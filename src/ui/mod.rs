@@ -8,7 +8,9 @@
 mod components;
 
 use anyhow::Result;
-use crossterm::event::{self, Event as CrosstermEvent, KeyCode, KeyEvent};
+use crossterm::event::{
+    self, Event as CrosstermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::ExecutableCommand;
 use ratatui::backend::CrosstermBackend;
@@ -26,6 +28,7 @@ pub enum Phase {
     Downloading,
     Extracting,
     Converting,
+    Merging,
     Complete,
 }
 
@@ -36,6 +39,7 @@ impl std::fmt::Display for Phase {
             Phase::Downloading => write!(f, "Downloading SDE"),
             Phase::Extracting => write!(f, "Extracting files"),
             Phase::Converting => write!(f, "Converting to SQLite"),
+            Phase::Merging => write!(f, "Merging into target database"),
             Phase::Complete => write!(f, "Complete"),
         }
     }
@@ -67,6 +71,35 @@ impl Progress {
     }
 }
 
+/// Format a byte count as a human-readable size
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1_000_000_000 {
+        format!("{:.1} GB", bytes as f64 / 1_000_000_000.0)
+    } else if bytes >= 1_000_000 {
+        format!("{:.1} MB", bytes as f64 / 1_000_000.0)
+    } else if bytes >= 1_000 {
+        format!("{:.1} KB", bytes as f64 / 1_000.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Structured conversion progress, for embedders that render their own
+/// per-table and overall progress display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// A table import began (`index` is zero-based, out of `total_tables`)
+    TableStarted {
+        name: &'static str,
+        index: usize,
+        total_tables: usize,
+    },
+    /// A batch was written; `count` is the table's running row total
+    RowsInserted { name: &'static str, count: u64 },
+    /// A table import completed with `rows` rows
+    TableFinished { name: &'static str, rows: u64 },
+}
+
 /// Trait for UI implementations - allows both real TUI and silent/test modes
 pub trait Ui {
     fn set_phase(&mut self, phase: Phase);
@@ -74,6 +107,14 @@ pub trait Ui {
     fn set_progress(&mut self, current: u64, total: u64, label: impl Into<String>);
     fn clear_progress(&mut self);
     fn log(&mut self, message: impl Into<String>);
+    /// Log a non-fatal problem (skipped file, skipped line, ...)
+    fn warn(&mut self, message: impl Into<String>);
+    /// Number of warnings emitted so far
+    fn warning_count(&self) -> u64;
+    /// Ask the user a yes/no question; non-interactive UIs answer no
+    fn confirm(&mut self, prompt: impl Into<String>) -> bool;
+    /// Receive structured progress alongside the display methods above
+    fn on_event(&mut self, _event: ProgressEvent) {}
 }
 
 /// Main UI application state - full TUI implementation
@@ -82,6 +123,7 @@ pub struct UiApp {
     status: StatusPanel,
     progress: ProgressPanel,
     log: LogPanel,
+    warnings: u64,
     should_quit: bool,
 }
 
@@ -100,6 +142,7 @@ impl UiApp {
             status: StatusPanel::new(),
             progress: ProgressPanel::new(),
             log: LogPanel::new(),
+            warnings: 0,
             should_quit: false,
         })
     }
@@ -107,8 +150,16 @@ impl UiApp {
     /// Check for quit signal (Ctrl+C or 'q')
     pub fn check_quit(&mut self) -> bool {
         if event::poll(Duration::from_millis(0)).unwrap_or(false) {
-            if let Ok(CrosstermEvent::Key(KeyEvent { code, .. })) = event::read() {
-                if code == KeyCode::Char('q') || code == KeyCode::Char('c') {
+            if let Ok(CrosstermEvent::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            })) = event::read()
+            {
+                let ctrl_c =
+                    code == KeyCode::Char('c') && modifiers.contains(KeyModifiers::CONTROL);
+                if ctrl_c || code == KeyCode::Char('q') {
                     self.should_quit = true;
                 }
             }
@@ -116,8 +167,14 @@ impl UiApp {
         self.should_quit
     }
 
-    /// Draw the UI
+    /// Draw the UI, first handling a pending quit: raw mode delivers Ctrl+C as a key
+    /// press rather than a signal, so the partial output is cleaned up from here
     fn draw(&mut self) -> Result<()> {
+        if self.check_quit() {
+            self.reset_terminal();
+            crate::interrupt::exit_interrupted();
+        }
+
         let status = &self.status;
         let progress = &self.progress;
         let log = &self.log;
@@ -163,6 +220,16 @@ impl UiApp {
         self.restore()
     }
 
+    /// Best effort terminal cleanup, for paths that cannot report errors
+    fn reset_terminal(&mut self) {
+        terminal::disable_raw_mode().ok();
+        self.terminal
+            .backend_mut()
+            .execute(LeaveAlternateScreen)
+            .ok();
+        self.terminal.show_cursor().ok();
+    }
+
     /// Restore terminal without waiting
     pub fn restore(mut self) -> Result<()> {
         terminal::disable_raw_mode()?;
@@ -198,27 +265,51 @@ impl Ui for UiApp {
         self.log.add(message);
         self.draw().ok();
     }
+
+    fn warn(&mut self, message: impl Into<String>) {
+        self.warnings += 1;
+        self.log.add(format!("Warning: {}", message.into()));
+        self.draw().ok();
+    }
+
+    fn warning_count(&self) -> u64 {
+        self.warnings
+    }
+
+    fn confirm(&mut self, prompt: impl Into<String>) -> bool {
+        self.log.add(format!("{} [y/N]", prompt.into()));
+        self.draw().ok();
+
+        loop {
+            match event::read() {
+                Ok(CrosstermEvent::Key(KeyEvent { code, .. })) => {
+                    let yes = matches!(code, KeyCode::Char('y') | KeyCode::Char('Y'));
+                    self.log.add(if yes { "Continuing" } else { "Cancelled" });
+                    self.draw().ok();
+                    return yes;
+                }
+                Ok(_) => continue,
+                Err(_) => return false,
+            }
+        }
+    }
 }
 
 impl Drop for UiApp {
     fn drop(&mut self) {
-        // Best effort cleanup
-        terminal::disable_raw_mode().ok();
-        self.terminal
-            .backend_mut()
-            .execute(LeaveAlternateScreen)
-            .ok();
-        self.terminal.show_cursor().ok();
+        self.reset_terminal();
     }
 }
 
 /// Silent UI implementation for testing and non-interactive use
 #[derive(Default)]
-pub struct SilentUi;
+pub struct SilentUi {
+    warnings: u64,
+}
 
 impl SilentUi {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 }
 
@@ -227,5 +318,21 @@ impl Ui for SilentUi {
     fn set_info(&mut self, _info: impl Into<String>) {}
     fn set_progress(&mut self, _current: u64, _total: u64, _label: impl Into<String>) {}
     fn clear_progress(&mut self) {}
-    fn log(&mut self, _message: impl Into<String>) {}
+    // Without a TUI, messages are only visible as `--verbose` diagnostics
+    fn log(&mut self, message: impl Into<String>) {
+        tracing::info!("{}", message.into());
+    }
+
+    fn warn(&mut self, message: impl Into<String>) {
+        tracing::warn!("{}", message.into());
+        self.warnings += 1;
+    }
+
+    fn warning_count(&self) -> u64 {
+        self.warnings
+    }
+
+    fn confirm(&mut self, _prompt: impl Into<String>) -> bool {
+        false
+    }
 }
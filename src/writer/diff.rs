@@ -0,0 +1,299 @@
+use anyhow::{bail, Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OpenFlags, Rows};
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::Path;
+
+use crate::schema::{get_table, TableSchema, ALL_TABLES};
+
+/// Records added, removed and changed in one table between two databases
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableDiff {
+    pub table: String,
+    pub added: u64,
+    pub removed: u64,
+    pub changed: u64,
+    /// Every changed column value, only collected when diffing a single table
+    pub changes: Vec<ColumnChange>,
+}
+
+impl TableDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.changed == 0
+    }
+}
+
+impl fmt::Display for TableDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: +{} -{} ~{}",
+            self.table, self.added, self.removed, self.changed
+        )
+    }
+}
+
+/// A column whose value differs between the old and new version of a record
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnChange {
+    pub id: i64,
+    pub column: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl fmt::Display for ColumnChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} -> {}",
+            self.id,
+            self.column,
+            display_value(&self.old),
+            display_value(&self.new)
+        )
+    }
+}
+
+/// Compare the tables keyed by `id` of two databases, opened read-only.
+///
+/// With `table`, only that table is compared and every changed column value is
+/// collected; otherwise every known table with an `id` key found in either database
+/// is compared (junction tables have composite keys and are skipped). A table missing
+/// from one database counts all its records as added or removed.
+pub fn diff_databases(old: &Path, new: &Path, table: Option<&str>) -> Result<Vec<TableDiff>> {
+    let tables: Vec<&TableSchema> = match table {
+        Some(name) => match get_table(name) {
+            None => bail!(
+                "Unknown table: {} (run `list-tables` to see available tables)",
+                name
+            ),
+            Some(schema) if !keyed_by_id(schema) => {
+                bail!("{} has a composite primary key and cannot be diffed", name)
+            }
+            Some(schema) => vec![schema],
+        },
+        None => ALL_TABLES
+            .iter()
+            .copied()
+            .filter(|s| keyed_by_id(s))
+            .collect(),
+    };
+
+    let old_conn = open_read_only(old)?;
+    let new_conn = open_read_only(new)?;
+
+    let mut diffs = Vec::new();
+    for schema in tables {
+        let old_columns = table_columns(&old_conn, schema.name)?;
+        let new_columns = table_columns(&new_conn, schema.name)?;
+        if old_columns.is_empty() && new_columns.is_empty() {
+            if table.is_some() {
+                bail!("Table {} exists in neither database", schema.name);
+            }
+            continue;
+        }
+
+        // Only columns both versions have can be compared
+        let columns: Vec<String> = new_columns
+            .into_iter()
+            .filter(|c| c != "id" && old_columns.contains(c))
+            .collect();
+        diffs.push(diff_table(
+            &old_conn,
+            &new_conn,
+            schema.name,
+            &columns,
+            table.is_some(),
+        )?);
+    }
+
+    Ok(diffs)
+}
+
+/// Whether a table's primary key is its `id` column
+fn keyed_by_id(schema: &TableSchema) -> bool {
+    schema.primary_key.is_empty() && schema.columns.iter().any(|c| c.name == "id")
+}
+
+fn open_read_only(db_path: &Path) -> Result<Connection> {
+    Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {:?}", db_path))
+}
+
+/// Column names of a table, empty if the database has no such table
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    Ok(conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get(1))?
+        .collect::<rusqlite::Result<_>>()?)
+}
+
+/// Merge-compare both versions of a table, streamed in `id` order
+fn diff_table(
+    old_conn: &Connection,
+    new_conn: &Connection,
+    table: &str,
+    columns: &[String],
+    collect_changes: bool,
+) -> Result<TableDiff> {
+    let mut diff = TableDiff {
+        table: table.to_string(),
+        ..Default::default()
+    };
+
+    let mut select = String::from("SELECT id");
+    for column in columns {
+        select.push_str(&format!(", \"{}\"", column));
+    }
+    select.push_str(&format!(" FROM {} ORDER BY id", table));
+
+    let mut old_stmt = match table_exists(old_conn, table)? {
+        true => Some(old_conn.prepare(&select)?),
+        false => None,
+    };
+    let mut new_stmt = match table_exists(new_conn, table)? {
+        true => Some(new_conn.prepare(&select)?),
+        false => None,
+    };
+    let mut old_rows = old_stmt.as_mut().map(|s| s.query([])).transpose()?;
+    let mut new_rows = new_stmt.as_mut().map(|s| s.query([])).transpose()?;
+
+    let mut old_row = next_row(&mut old_rows, columns.len())?;
+    let mut new_row = next_row(&mut new_rows, columns.len())?;
+    loop {
+        let order = match (&old_row, &new_row) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((old_id, _)), Some((new_id, _))) => old_id.cmp(new_id),
+        };
+
+        match order {
+            Ordering::Less => {
+                diff.removed += 1;
+                old_row = next_row(&mut old_rows, columns.len())?;
+            }
+            Ordering::Greater => {
+                diff.added += 1;
+                new_row = next_row(&mut new_rows, columns.len())?;
+            }
+            Ordering::Equal => {
+                let ((id, old_values), (_, new_values)) = (
+                    old_row.take().unwrap_or_default(),
+                    new_row.take().unwrap_or_default(),
+                );
+                let mut changed = false;
+                for ((column, old), new) in columns.iter().zip(old_values).zip(new_values) {
+                    if old == new {
+                        continue;
+                    }
+                    changed = true;
+                    if collect_changes {
+                        diff.changes.push(ColumnChange {
+                            id,
+                            column: column.clone(),
+                            old,
+                            new,
+                        });
+                    }
+                }
+                if changed {
+                    diff.changed += 1;
+                }
+                old_row = next_row(&mut old_rows, columns.len())?;
+                new_row = next_row(&mut new_rows, columns.len())?;
+            }
+        }
+    }
+
+    Ok(diff)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [table],
+        |row| row.get(0),
+    )?)
+}
+
+/// Next `(id, values)` row, `None` once done (or for a table missing from the database)
+fn next_row(rows: &mut Option<Rows>, columns: usize) -> Result<Option<(i64, Vec<Value>)>> {
+    let Some(rows) = rows else {
+        return Ok(None);
+    };
+    let Some(row) = rows.next()? else {
+        return Ok(None);
+    };
+
+    let id = row.get(0)?;
+    let values = (1..=columns)
+        .map(|i| row.get(i))
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(Some((id, values)))
+}
+
+/// A column value as shown in a diff
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(n) => n.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(s) => format!("{:?}", s),
+        Value::Blob(bytes) => format!("<{} bytes>", bytes.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_db(path: &Path, sql: &str) {
+        Connection::open(path).unwrap().execute_batch(sql).unwrap();
+    }
+
+    #[test]
+    fn test_diff_databases() {
+        let dir = tempfile::tempdir().unwrap();
+        let (old, new) = (dir.path().join("old.db"), dir.path().join("new.db"));
+        create_db(
+            &old,
+            "CREATE TABLE categories (id INTEGER PRIMARY KEY, name_en TEXT, published INTEGER);
+             INSERT INTO categories VALUES (1, 'Ship', 1), (2, 'Module', 1), (3, 'Old', 0);
+             CREATE TABLE races (id INTEGER PRIMARY KEY, name_en TEXT);
+             INSERT INTO races VALUES (1, 'Caldari');",
+        );
+        create_db(
+            &new,
+            "CREATE TABLE categories (id INTEGER PRIMARY KEY, name_en TEXT, published INTEGER);
+             INSERT INTO categories VALUES (1, 'Ship', 1), (2, 'Modules', 0), (4, 'New', 1);
+             CREATE TABLE races (id INTEGER PRIMARY KEY, name_en TEXT);
+             INSERT INTO races VALUES (1, 'Caldari');
+             CREATE TABLE groups (id INTEGER PRIMARY KEY, category_id INTEGER);
+             INSERT INTO groups VALUES (10, 1), (11, 2);",
+        );
+
+        let diffs = diff_databases(&old, &new, None).unwrap();
+        let summary: Vec<String> = diffs.iter().map(|d| d.to_string()).collect();
+        assert!(summary.contains(&"categories: +1 -1 ~1".to_string()));
+        assert!(summary.contains(&"groups: +2 -0 ~0".to_string()));
+        assert!(diffs.iter().any(|d| d.table == "races" && d.is_empty()));
+        // Column detail is only collected for a single table
+        assert!(diffs.iter().all(|d| d.changes.is_empty()));
+
+        let diffs = diff_databases(&old, &new, Some("categories")).unwrap();
+        let changes: Vec<String> = diffs[0].changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "2 name_en: \"Module\" -> \"Modules\"",
+                "2 published: 1 -> 0"
+            ]
+        );
+
+        assert!(diff_databases(&old, &new, Some("nope")).is_err());
+        assert!(diff_databases(&old, &new, Some("type_dogma_attributes")).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! DuckDB output (`--output-format duckdb`, behind the `duckdb` feature)
+
+use anyhow::{bail, Context, Result};
+use duckdb::types::Value;
+use duckdb::{appender_params_from_iter, Connection};
+use std::path::{Path, PathBuf};
+
+use super::options::ConvertOptions;
+use super::profile::Profiler;
+use super::schema_gen::{
+    column_definitions_for, generate_create_table_for, generate_indexes_with, Dialect,
+};
+use super::sink::{convert_into, show_import_progress, RecordSink};
+use super::source::{parse_source, SourceItem, SourceProvider, UnpublishedKeys};
+use crate::parser::{ParsedRow, SqlValue, RAW_JSON_COLUMN, SEARCH_TEXT_COLUMN};
+use crate::schema::TableSchema;
+use crate::ui::{ProgressEvent, Ui};
+
+/// Rows appended between progress updates
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+/// Writes all tables into one DuckDB database file. Like the SQLite writer it builds
+/// `<path>.tmp` and only replaces `path` in `finalize`.
+pub struct DuckDbWriter {
+    conn: Connection,
+    output_path: PathBuf,
+    temp_path: PathBuf,
+    options: ConvertOptions,
+    /// CREATE INDEX statements, run once all tables are loaded
+    indexes: Vec<String>,
+    /// Unpublished parent keys, shared by the junction tables of each parent
+    unpublished_keys: UnpublishedKeys,
+}
+
+impl DuckDbWriter {
+    pub fn new(db_path: &Path, options: ConvertOptions) -> Result<Self> {
+        if let Some(flag) = options.sqlite_only_flag() {
+            bail!("{} needs SQLite output", flag);
+        }
+
+        let mut name = db_path.as_os_str().to_os_string();
+        name.push(".tmp");
+        let temp_path = PathBuf::from(name);
+        remove_database_files(&temp_path).context("Failed to remove stale temporary database")?;
+        let conn = Connection::open(&temp_path)
+            .with_context(|| format!("Failed to create {:?}", temp_path))?;
+
+        Ok(Self {
+            conn,
+            output_path: db_path.to_path_buf(),
+            temp_path,
+            options,
+            indexes: Vec::new(),
+            unpublished_keys: UnpublishedKeys::default(),
+        })
+    }
+
+    /// Parsed row keys and DuckDB types for a table, in column order
+    fn columns(&self, schema: &TableSchema) -> Vec<(String, &'static str)> {
+        let mut columns: Vec<(String, &'static str)> =
+            column_definitions_for(schema, &self.options.languages(), Dialect::DuckDb)
+                .into_iter()
+                .map(|(name, sql_type, _)| (name, sql_type))
+                .collect();
+
+        if self.options.stores_raw_json(schema) {
+            columns.push((RAW_JSON_COLUMN.to_string(), "VARCHAR"));
+        }
+        if self.options.stores_search_text(schema) {
+            columns.push((SEARCH_TEXT_COLUMN.to_string(), "VARCHAR"));
+        }
+
+        columns
+    }
+}
+
+impl RecordSink for DuckDbWriter {
+    fn create_tables(&mut self, tables: &[&TableSchema], ui: &mut impl Ui) -> Result<()> {
+        ui.log(format!("Creating {} tables...", tables.len()));
+
+        for schema in tables {
+            let sql = generate_create_table_for(schema, &self.options, Dialect::DuckDb);
+            self.conn
+                .execute_batch(&sql)
+                .with_context(|| format!("Failed to create table: {}", schema.name))?;
+            self.indexes
+                .extend(generate_indexes_with(schema, &self.options));
+        }
+
+        Ok(())
+    }
+
+    fn import_table(
+        &mut self,
+        schema: &TableSchema,
+        source: &dyn SourceProvider,
+        ui: &mut impl Ui,
+    ) -> Result<u64> {
+        if !source.has_source(schema.source_file) {
+            ui.warn(format!("{}: skipped (file not found)", schema.name));
+            return Ok(0);
+        }
+
+        let columns = self.columns(schema);
+        let mut appender = self.conn.appender(schema.name)?;
+        let row_limit = self.options.row_limit.unwrap_or(u64::MAX);
+        let mut count: u64 = 0;
+        let mut limited = false;
+
+        let skip_keys = self
+            .unpublished_keys
+            .for_table(source, schema, &self.options, ui)?;
+        let (reader, progress) = source.open(schema.source_file)?;
+        let profiler = Profiler::new(false);
+        parse_source(
+            reader,
+            schema,
+            &self.options,
+            skip_keys.as_deref(),
+            &profiler,
+            &mut |item| {
+                match item {
+                    SourceItem::Line { .. } | SourceItem::Row(_) if count >= row_limit => {
+                        limited = true;
+                        return Ok(false);
+                    }
+                    SourceItem::Line { .. } => {}
+                    SourceItem::Row(row) => {
+                        appender
+                            .append_row(appender_params_from_iter(row_values(&columns, &row)))
+                            .with_context(|| {
+                                format!("Failed to append a row to {}", schema.name)
+                            })?;
+                        count += 1;
+                        if count.is_multiple_of(PROGRESS_INTERVAL) {
+                            show_import_progress(ui, schema, count, &progress);
+                            ui.on_event(ProgressEvent::RowsInserted {
+                                name: schema.name,
+                                count,
+                            });
+                        }
+                    }
+                    SourceItem::Skipped { line_index, error }
+                    | SourceItem::Failed { line_index, error } => ui.warn(format!(
+                        "{}: skipped line {} of {}: {}",
+                        schema.name,
+                        line_index + 1,
+                        schema.source_file,
+                        error
+                    )),
+                }
+                Ok(true)
+            },
+        )?;
+        appender
+            .flush()
+            .with_context(|| format!("Failed to write {}", schema.name))?;
+
+        ui.on_event(ProgressEvent::TableFinished {
+            name: schema.name,
+            rows: count,
+        });
+        if limited {
+            ui.log(format!("{}: {} records (limited)", schema.name, count));
+        } else {
+            ui.log(format!("{}: {} records", schema.name, count));
+        }
+
+        Ok(count)
+    }
+
+    fn finalize(self, ui: &mut impl Ui) -> Result<()> {
+        ui.log("Creating indexes...");
+        // Indexes are built once the data is loaded, which is much faster than
+        // maintaining them during the bulk append
+        for sql in &self.indexes {
+            self.conn
+                .execute_batch(sql)
+                .with_context(|| format!("Failed to create index: {}", sql))?;
+        }
+
+        self.conn
+            .close()
+            .map_err(|(_, e)| e)
+            .context("Failed to close database")?;
+        remove_database_files(&self.output_path).context("Failed to remove existing database")?;
+        std::fs::rename(&self.temp_path, &self.output_path).with_context(|| {
+            format!(
+                "Failed to move {:?} to {:?}",
+                self.temp_path, self.output_path
+            )
+        })?;
+
+        ui.log(format!("Wrote DuckDB database {:?}", self.output_path));
+        Ok(())
+    }
+}
+
+/// Convert JSONL files into a DuckDB database at `output_db`
+pub fn convert_to_duckdb(
+    source: &dyn SourceProvider,
+    output_db: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+) -> Result<u64> {
+    options.validate()?;
+    let writer = DuckDbWriter::new(output_db, options.clone())?;
+    convert_into(writer, source, &tables, ui)
+}
+
+/// Values of a parsed row in column order, as the DuckDB types of the columns
+fn row_values(columns: &[(String, &'static str)], row: &ParsedRow) -> Vec<Value> {
+    columns
+        .iter()
+        .map(|(key, sql_type)| match (row.values.get(key), *sql_type) {
+            (Some(SqlValue::Integer(i)), "BOOLEAN") => Value::Boolean(*i != 0),
+            (Some(SqlValue::Integer(i)), "DOUBLE") => Value::Double(*i as f64),
+            (Some(SqlValue::Integer(i)), "VARCHAR") => Value::Text(i.to_string()),
+            (Some(SqlValue::Integer(i)), _) => Value::BigInt(*i),
+            (Some(SqlValue::Real(f)), "VARCHAR") => Value::Text(f.to_string()),
+            (Some(SqlValue::Real(f)), _) => Value::Double(*f),
+            (Some(SqlValue::Text(s)), _) => Value::Text(s.clone()),
+            (Some(SqlValue::Null) | None, _) => Value::Null,
+        })
+        .collect()
+}
+
+/// Remove a DuckDB database file and its WAL, if present
+fn remove_database_files(db_path: &Path) -> std::io::Result<()> {
+    for suffix in ["", ".wal"] {
+        let mut name = db_path.as_os_str().to_os_string();
+        name.push(suffix);
+        match std::fs::remove_file(&name) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{CATEGORIES, GROUPS, MAP_STARS, TYPES};
+    use crate::ui::SilentUi;
+    use crate::writer::convert_with_options;
+
+    #[test]
+    fn test_row_counts_match_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}, "published": true}
+{"_key": 7, "name": {"en": "Module"}}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("groups.jsonl"),
+            r#"{"_key": 25, "categoryID": 6, "name": {"en": "Frigate"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("types.jsonl"),
+            r#"{"_key": 587, "groupID": 25, "name": {"en": "Rifter"}, "mass": 1067000}
+{"_key": 588, "groupID": 25, "name": {"en": "Reaper"}, "mass": 1.2e6}
+{"_key": 589, "groupID": 25, "name": {"en": "Probe"}}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("mapStars.jsonl"),
+            r#"{"_key": 40000001, "solarSystemID": 30000001, "statistics": {"locked": false, "spectralClass": "K7 V"}}"#,
+        )
+        .unwrap();
+        let tables = || vec![&CATEGORIES, &GROUPS, &TYPES, &MAP_STARS];
+        let options = ConvertOptions::default();
+
+        let sqlite_path = dir.path().join("out.db");
+        convert_with_options(
+            dir.path(),
+            &sqlite_path,
+            tables(),
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        let duckdb_path = dir.path().join("out.duckdb");
+        convert_to_duckdb(
+            &dir.path(),
+            &duckdb_path,
+            tables(),
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let sqlite = rusqlite::Connection::open(&sqlite_path).unwrap();
+        let duck = Connection::open(&duckdb_path).unwrap();
+        for table in tables() {
+            let sql = format!("SELECT COUNT(*) FROM {}", table.name);
+            let expected: i64 = sqlite.query_row(&sql, [], |row| row.get(0)).unwrap();
+            let actual: i64 = duck.query_row(&sql, [], |row| row.get(0)).unwrap();
+            assert_eq!(actual, expected, "{}", table.name);
+        }
+
+        let locked: bool = duck
+            .query_row("SELECT locked FROM map_stars", [], |row| row.get(0))
+            .unwrap();
+        assert!(!locked);
+    }
+}
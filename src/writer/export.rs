@@ -0,0 +1,143 @@
+use anyhow::{bail, Context, Result};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::schema::table_names;
+
+/// Flat file formats a table can be exported to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row (RFC 4180 quoting)
+    #[default]
+    Csv,
+}
+
+/// Export one table of an existing database to `output`.
+/// Returns the number of rows written.
+pub fn export_table(
+    db_path: &Path,
+    table: &str,
+    format: ExportFormat,
+    output: &Path,
+) -> Result<u64> {
+    if !table_names().contains(&table) {
+        bail!(
+            "Unknown table: {} (run `list-tables` to see available tables)",
+            table
+        );
+    }
+
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {:?}", db_path))?;
+
+    let columns: Vec<String> = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get(1))?
+        .collect::<rusqlite::Result<_>>()?;
+    if columns.is_empty() {
+        bail!("Table {} does not exist in {:?}", table, db_path);
+    }
+
+    let file = File::create(output).with_context(|| format!("Failed to create {:?}", output))?;
+    let mut out = BufWriter::new(file);
+
+    let count = match format {
+        ExportFormat::Csv => write_csv(&conn, table, &columns, &mut out)?,
+    };
+
+    out.flush()
+        .with_context(|| format!("Failed to write {:?}", output))?;
+    Ok(count)
+}
+
+/// Stream a table as CSV, one record per row
+fn write_csv(
+    conn: &Connection,
+    table: &str,
+    columns: &[String],
+    out: &mut impl Write,
+) -> Result<u64> {
+    let header: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+    write!(out, "{}\r\n", header.join(","))?;
+
+    let quoted: Vec<String> = columns.iter().map(|c| format!("\"{}\"", c)).collect();
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", quoted.join(", "), table))?;
+    let mut rows = stmt.query([])?;
+
+    let mut count = 0;
+    let mut fields = Vec::with_capacity(columns.len());
+    while let Some(row) = rows.next()? {
+        fields.clear();
+        for i in 0..columns.len() {
+            fields.push(match row.get_ref(i)? {
+                ValueRef::Null => String::new(),
+                ValueRef::Integer(n) => n.to_string(),
+                ValueRef::Real(f) => f.to_string(),
+                ValueRef::Text(bytes) | ValueRef::Blob(bytes) => {
+                    csv_field(&String::from_utf8_lossy(bytes))
+                }
+            });
+        }
+        write!(out, "{}\r\n", fields.join(","))?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Quote a field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::CATEGORIES;
+    use crate::ui::SilentUi;
+    use crate::writer::{convert_with_options, ConvertOptions};
+
+    #[test]
+    fn test_export_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship, \"large\"", "de": "Schiff"}, "published": true}
+{"_key": 7, "name": {"en": "Module"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES],
+            &ConvertOptions {
+                languages: vec!["en".to_string(), "de".to_string()],
+                ..Default::default()
+            },
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let csv_path = dir.path().join("categories.csv");
+        let count = export_table(&db_path, "categories", ExportFormat::Csv, &csv_path).unwrap();
+        assert_eq!(count, 2);
+
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], "id,name_en,name_de,published");
+        assert_eq!(lines[1], r#"6,"Ship, ""large""",Schiff,1"#);
+        assert_eq!(lines[2], "7,Module,,0");
+
+        assert!(export_table(&db_path, "nope", ExportFormat::Csv, &csv_path).is_err());
+        assert!(export_table(&db_path, "types", ExportFormat::Csv, &csv_path).is_err());
+    }
+}
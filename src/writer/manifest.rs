@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::schema::TableSchema;
+
+/// Machine-readable record of what a database contains, written next to it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Manifest {
+    /// SDE build the database was built from (`null` for `convert`)
+    pub build_number: Option<u64>,
+    /// When the database was created (UTC RFC 3339)
+    pub generated_at: String,
+    /// Selected tables in import order
+    pub tables: Vec<TableRows>,
+}
+
+/// Row count of one table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TableRows {
+    pub name: String,
+    pub rows: u64,
+}
+
+impl Manifest {
+    /// Describe the selected `tables`, taking row counts from `row_counts`
+    /// (tables without an entry, e.g. missing source files, have 0 rows)
+    pub fn new(
+        build_number: Option<u64>,
+        tables: &[&TableSchema],
+        row_counts: &[(&str, u64)],
+    ) -> Self {
+        let tables = tables
+            .iter()
+            .map(|schema| TableRows {
+                name: schema.name.to_string(),
+                rows: row_counts
+                    .iter()
+                    .find(|(name, _)| *name == schema.name)
+                    .map_or(0, |(_, rows)| *rows),
+            })
+            .collect();
+
+        Self {
+            build_number,
+            generated_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            tables,
+        }
+    }
+
+    /// Write the manifest as pretty-printed JSON
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json + "\n").with_context(|| format!("Failed to write {:?}", path))
+    }
+}
+
+/// Manifest path for a database: `eve.db` -> `eve.manifest.json`
+pub fn manifest_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("manifest.json")
+}
@@ -0,0 +1,226 @@
+//! Copy tables from a built SDE database into an existing database (`--merge-into`)
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use std::path::Path;
+
+use crate::schema::TableSchema;
+use crate::ui::Ui;
+
+/// How to handle a table that already exists in the merge target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OnConflict {
+    /// Abort before copying anything
+    #[default]
+    Fail,
+    /// Keep the existing table and skip the SDE one
+    Skip,
+    /// Drop the existing table and copy the SDE one
+    Replace,
+}
+
+/// Copy the given tables (with their indexes) from `source_db` into `target_db`,
+/// creating the target if needed. Returns the number of tables copied.
+pub fn merge_into(
+    source_db: &Path,
+    target_db: &Path,
+    tables: &[&TableSchema],
+    on_conflict: OnConflict,
+    ui: &mut impl Ui,
+) -> Result<u64> {
+    let mut conn = Connection::open(target_db)
+        .with_context(|| format!("Failed to open merge target: {:?}", target_db))?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS sde",
+        [source_db.to_string_lossy().as_ref()],
+    )
+    .context("Failed to attach SDE database")?;
+
+    let existing: Vec<&str> = tables
+        .iter()
+        .map(|t| t.name)
+        .filter(|name| table_sql(&conn, "main", name).ok().flatten().is_some())
+        .collect();
+
+    if on_conflict == OnConflict::Fail && !existing.is_empty() {
+        bail!(
+            "Tables already exist in {:?}: {} (use --on-conflict skip or replace)",
+            target_db,
+            existing.join(", ")
+        );
+    }
+
+    ui.log(format!(
+        "Merging {} tables into {:?}...",
+        tables.len(),
+        target_db
+    ));
+
+    let tx = conn.transaction()?;
+    let mut copied: u64 = 0;
+
+    for (i, schema) in tables.iter().enumerate() {
+        let name = schema.name;
+
+        if existing.contains(&name) {
+            match on_conflict {
+                OnConflict::Skip => {
+                    ui.warn(format!("{}: already exists in merge target, skipped", name));
+                    continue;
+                }
+                OnConflict::Replace => {
+                    tx.execute_batch(&format!("DROP TABLE main.{}", name))?;
+                }
+                OnConflict::Fail => unreachable!("conflicts rejected above"),
+            }
+        }
+
+        // Recreate the table from its original DDL so keys and constraints carry over
+        let Some(create_sql) = table_sql(&tx, "sde", name)? else {
+            continue;
+        };
+        tx.execute_batch(&create_sql)
+            .with_context(|| format!("Failed to create {} in merge target", name))?;
+        tx.execute_batch(&format!("INSERT INTO main.{name} SELECT * FROM sde.{name}"))
+            .with_context(|| format!("Failed to copy {}", name))?;
+
+        for index_sql in index_sql(&tx, name)? {
+            tx.execute_batch(&index_sql)
+                .with_context(|| format!("Failed to create index for {}", name))?;
+        }
+
+        copied += 1;
+        ui.set_progress((i + 1) as u64, tables.len() as u64, "Merging tables");
+    }
+
+    tx.commit()?;
+    conn.execute("DETACH DATABASE sde", [])?;
+
+    ui.log(format!("Merged {} tables into {:?}", copied, target_db));
+    Ok(copied)
+}
+
+/// CREATE TABLE statement of a table in the given schema, if it exists
+fn table_sql(conn: &Connection, schema: &str, table: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT sql FROM {}.sqlite_master WHERE type = 'table' AND name = ?1",
+                schema
+            ),
+            [table],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Explicit CREATE INDEX statements for a table in the attached SDE database
+fn index_sql(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT sql FROM sde.sqlite_master WHERE type = 'index' AND tbl_name = ?1 AND sql IS NOT NULL",
+    )?;
+    let statements = stmt
+        .query_map([table], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(statements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::CATEGORIES;
+    use crate::ui::SilentUi;
+    use crate::writer::{convert_with_options, ConvertOptions};
+
+    /// Build a one-table SDE database, returning the temp dir and its path
+    fn build_sde() -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}, "published": true}
+{"_key": 7, "name": {"en": "Module"}, "published": true}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("sde.db");
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        (dir, db_path)
+    }
+
+    fn category_count(target: &Path) -> i64 {
+        Connection::open(target)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_merge_into_new_database() {
+        let (dir, source) = build_sde();
+        let target = dir.path().join("app.db");
+        Connection::open(&target)
+            .unwrap()
+            .execute_batch("CREATE TABLE settings (key TEXT PRIMARY KEY)")
+            .unwrap();
+
+        let copied = merge_into(
+            &source,
+            &target,
+            &[&CATEGORIES],
+            OnConflict::Fail,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        assert_eq!(copied, 1);
+        assert_eq!(category_count(&target), 2);
+
+        let indexes: i64 = Connection::open(&target)
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND tbl_name = 'categories' AND sql IS NOT NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, CATEGORIES.indexes.len() as i64);
+    }
+
+    #[test]
+    fn test_merge_into_conflicts() {
+        let (dir, source) = build_sde();
+        let target = dir.path().join("app.db");
+        Connection::open(&target)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE categories (id INTEGER); INSERT INTO categories VALUES (1);",
+            )
+            .unwrap();
+        let mut ui = SilentUi::new();
+
+        assert!(merge_into(&source, &target, &[&CATEGORIES], OnConflict::Fail, &mut ui).is_err());
+        assert_eq!(category_count(&target), 1);
+
+        let copied =
+            merge_into(&source, &target, &[&CATEGORIES], OnConflict::Skip, &mut ui).unwrap();
+        assert_eq!(copied, 0);
+        assert_eq!(ui.warning_count(), 1);
+        assert_eq!(category_count(&target), 1);
+
+        merge_into(
+            &source,
+            &target,
+            &[&CATEGORIES],
+            OnConflict::Replace,
+            &mut ui,
+        )
+        .unwrap();
+        assert_eq!(category_count(&target), 2);
+    }
+}
@@ -1,5 +1,31 @@
+pub mod diff;
+#[cfg(feature = "duckdb")]
+pub mod duckdb;
+pub mod export;
+pub mod manifest;
+pub mod merge;
+pub mod options;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod profile;
 pub mod schema_gen;
+pub mod sink;
+mod source;
 pub mod sqlite;
+pub mod verify;
 
+pub use diff::*;
+#[cfg(feature = "duckdb")]
+pub use duckdb::*;
+pub use export::*;
+pub use manifest::*;
+pub use merge::*;
+pub use options::*;
+#[cfg(feature = "parquet")]
+pub use parquet::*;
+pub use profile::*;
 pub use schema_gen::*;
+pub use sink::*;
+pub use source::{open_source, source_path, ReadProgress, SourceProvider};
 pub use sqlite::*;
+pub use verify::*;
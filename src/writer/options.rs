@@ -0,0 +1,323 @@
+use anyhow::{bail, Result};
+use std::collections::BTreeSet;
+
+use super::sink::OutputFormat;
+use crate::parser::{known_fields, to_camel_case, ParseOptions};
+use crate::schema::{get_table, ColumnType, TableSchema, LANGUAGES};
+
+/// Bytes in a MiB, for the `--cache-size-mb`/`--mmap-size-mb` limits
+const MIB: i64 = 1024 * 1024;
+
+/// Rows inserted per batch unless `--batch-size` overrides it
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Naming convention for generated column names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Naming {
+    /// snake_case column names (e.g. `group_id`, `name_en`)
+    #[default]
+    Snake,
+    /// CCP's camelCase field names (e.g. `groupID`, `nameEn`)
+    Camel,
+}
+
+impl Naming {
+    /// SQL column name for a snake_case schema column name
+    pub fn column_name(self, name: &str) -> String {
+        match self {
+            Naming::Snake => name.to_string(),
+            Naming::Camel => to_camel_case(name),
+        }
+    }
+}
+
+/// Options controlling how tables are generated and imported
+#[derive(Debug, Clone, Default)]
+pub struct ConvertOptions {
+    /// Tables that get a `_raw` column holding the original JSON line
+    pub raw_json_tables: Vec<String>,
+    /// Tables that get a `search_text` column concatenating their localized text
+    /// (descriptions only with `search_text_descriptions`)
+    pub search_text_tables: Vec<String>,
+    /// Include localized `description` columns in `search_text`
+    pub search_text_descriptions: bool,
+    /// Languages localized columns expand to (empty means all of `LANGUAGES`)
+    pub languages: Vec<String>,
+    /// Naming convention for generated column names
+    pub naming: Naming,
+    /// Create convenience views (e.g. `manufacturing_sources`) after import
+    pub views: bool,
+    /// Create `<table>_fts` FTS5 name search tables after import
+    pub fts: bool,
+    /// Warn about source lines that repeat a record key, keeping the first row for each
+    /// primary key
+    pub warn_duplicate_keys: bool,
+    /// Keep only the last line for each repeated record key
+    pub dedupe_keys: bool,
+    /// Fail on source data with an unexpected shape instead of skipping it with a warning
+    pub strict: bool,
+    /// Add indexed `has_icon`/`has_graphic`/`has_sound` flags to `types`
+    pub derive_presence_flags: bool,
+    /// Copy region and constellation names onto `map_solar_systems`
+    pub denormalize: bool,
+    /// Stop importing a table once it has this many rows (junction rows count individually)
+    pub row_limit: Option<u64>,
+    /// Rows inserted per batch (`None` uses `DEFAULT_BATCH_SIZE`)
+    pub batch_size: Option<usize>,
+    /// Number of tables parsed concurrently; 0 or 1 imports serially
+    pub jobs: usize,
+    /// Time each table import and its read/parse/insert stages
+    pub profile: bool,
+    /// `PRAGMA name = value` statements run on the output database before tables are created
+    pub pragmas: Vec<(String, String)>,
+    /// Page cache size in MiB (`None` keeps the 64 MB bulk import default, zero or
+    /// negative SQLite's own default)
+    pub cache_size_mb: Option<i64>,
+    /// Memory-mapped I/O size in MiB (`None`, zero or negative leaves mmap off)
+    pub mmap_size_mb: Option<i64>,
+    /// Extra key/value pairs recorded in the `sde_meta` table
+    pub metadata: Vec<(String, String)>,
+    /// Backend the converted tables are written to
+    pub output_format: OutputFormat,
+    /// Import only these record ids of a base table, and only the matching rows of
+    /// junction tables whose parent id references it
+    pub id_filters: Vec<(String, BTreeSet<i64>)>,
+    /// Skip records whose `published` flag is explicitly false (not ones without the
+    /// flag, stored as 0), along with the junction rows of unpublished parents
+    pub published_only: bool,
+    /// Replace the selected tables inside an existing database instead of rebuilding it
+    pub incremental: bool,
+    /// Warn about top-level JSON fields of base table sources that no column reads
+    pub report_unknown_fields: bool,
+    /// Skip source lines that fail to parse instead of aborting, and fail once the
+    /// database is written
+    pub continue_on_error: bool,
+    /// Skip running `ANALYZE` when finalizing (faster, but no query planner statistics)
+    pub skip_analyze: bool,
+    /// Derive the `map_jumps` edge list from `map_stargates` after importing
+    pub build_jumps: bool,
+    /// Insert junction rows with `ON CONFLICT DO NOTHING`, dropping rows that repeat a
+    /// primary key tuple (other constraint failures still abort)
+    pub dedup_junctions: bool,
+    /// Run `VACUUM` when finalizing to shrink the database file
+    pub vacuum: bool,
+    /// Write each table to `<output>/<table>.db` instead of one database, without
+    /// foreign keys
+    pub split_by_table: bool,
+    /// Minimum row counts checked in the finished database (`--expect`)
+    pub row_expectations: Vec<(String, u64)>,
+    /// Log every foreign key violation once all tables are imported
+    pub check_fk: bool,
+    /// Fail once the database is written if `check_fk` found violations
+    pub strict_fk: bool,
+}
+
+impl ConvertOptions {
+    /// The first option given that only the SQLite writer supports, as its CLI flag
+    pub fn sqlite_only_flag(&self) -> Option<&'static str> {
+        [
+            (self.dedupe_keys, "--dedupe-keys"),
+            (self.views, "--views"),
+            (self.fts, "--fts"),
+            (self.derive_presence_flags, "--derive-presence-flags"),
+            (self.denormalize, "--denormalize"),
+            (!self.pragmas.is_empty(), "--pragma"),
+            (self.batch_size.is_some(), "--batch-size"),
+            (self.cache_size_mb.is_some(), "--cache-size-mb"),
+            (self.mmap_size_mb.is_some(), "--mmap-size-mb"),
+            (self.incremental, "--incremental"),
+            (self.report_unknown_fields, "--report-unknown-fields"),
+            (self.continue_on_error, "--continue-on-error"),
+            (!self.row_expectations.is_empty(), "--expect"),
+            (self.check_fk, "--check-fk"),
+            (self.vacuum, "--vacuum"),
+            (self.dedup_junctions, "--dedup-junctions"),
+            (self.build_jumps, "--build-jumps"),
+            (self.split_by_table, "--split-by-table"),
+        ]
+        .into_iter()
+        .find_map(|(set, flag)| set.then_some(flag))
+    }
+
+    /// Check option values that refer to tables
+    pub fn validate(&self) -> Result<()> {
+        if self.views && self.naming != Naming::Snake {
+            bail!("--views requires snake_case column names (--naming snake)");
+        }
+
+        // These add columns or views once per build, which an existing database already has
+        if self.incremental {
+            let rebuild_only = [
+                (self.views, "--views"),
+                (self.derive_presence_flags, "--derive-presence-flags"),
+                (self.denormalize, "--denormalize"),
+                (self.build_jumps, "--build-jumps"),
+            ];
+            if let Some((_, flag)) = rebuild_only.iter().find(|(set, _)| *set) {
+                bail!("{} cannot be combined with --incremental", flag);
+            }
+        }
+
+        // These need the tables side by side in one database
+        if self.split_by_table {
+            let single_file_only = [
+                (self.views, "--views"),
+                (self.derive_presence_flags, "--derive-presence-flags"),
+                (self.denormalize, "--denormalize"),
+                (self.build_jumps, "--build-jumps"),
+                (self.check_fk, "--check-fk"),
+                (!self.row_expectations.is_empty(), "--expect"),
+            ];
+            if let Some((_, flag)) = single_file_only.iter().find(|(set, _)| *set) {
+                bail!("{} cannot be combined with --split-by-table", flag);
+            }
+        }
+
+        // Pragmas are interpolated into the statement, so reject anything that could end it
+        for (name, value) in &self.pragmas {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                bail!("Invalid pragma name in --pragma: {:?}", name);
+            }
+            if value.is_empty() || value.contains(';') {
+                bail!("Invalid value {:?} for --pragma {}", value, name);
+            }
+        }
+
+        // Both sizes are passed to SQLite in smaller units (KiB and bytes)
+        for (size, flag) in [
+            (self.cache_size_mb, "--cache-size-mb"),
+            (self.mmap_size_mb, "--mmap-size-mb"),
+        ] {
+            if size.is_some_and(|mb| mb > i64::MAX / MIB) {
+                bail!("{} is too large: {}", flag, size.unwrap_or_default());
+            }
+        }
+
+        for lang in &self.languages {
+            if !LANGUAGES.contains(&lang.as_str()) {
+                bail!(
+                    "Unknown language in --languages: {} (expected one of {})",
+                    lang,
+                    LANGUAGES.join(", ")
+                );
+            }
+        }
+        if !self.languages.is_empty() && !self.languages.iter().any(|l| l == "en") {
+            bail!("--languages must include en (indexes and required names use English)");
+        }
+
+        for (name, _) in &self.id_filters {
+            match get_table(name) {
+                None => bail!("Unknown table in --id-filter: {}", name),
+                Some(schema) if schema.array_source.is_some() => bail!(
+                    "--id-filter is only supported for base tables, {} is a junction table",
+                    name
+                ),
+                Some(_) => {}
+            }
+        }
+
+        for (name, _) in &self.row_expectations {
+            if get_table(name).is_none() {
+                bail!("Unknown table in --expect: {}", name);
+            }
+        }
+
+        for name in &self.raw_json_tables {
+            match get_table(name) {
+                None => bail!("Unknown table in --raw-json: {}", name),
+                Some(schema) if schema.array_source.is_some() => bail!(
+                    "--raw-json is only supported for base tables, {} is a junction table",
+                    name
+                ),
+                Some(_) => {}
+            }
+        }
+
+        for name in &self.search_text_tables {
+            match get_table(name) {
+                None => bail!("Unknown table in --search-text-tables: {}", name),
+                Some(schema) if schema.array_source.is_some() => bail!(
+                    "--search-text-tables is only supported for base tables, {} is a junction table",
+                    name
+                ),
+                Some(schema)
+                    if !schema
+                        .columns
+                        .iter()
+                        .any(|c| c.col_type == ColumnType::Localized) =>
+                {
+                    bail!("{} has no localized columns to build search_text from", name)
+                }
+                Some(_) => {}
+            }
+        }
+        if self.search_text_descriptions && self.search_text_tables.is_empty() {
+            bail!("--search-text-descriptions requires --search-text-tables");
+        }
+        Ok(())
+    }
+
+    /// Languages localized columns expand to, in `LANGUAGES` order
+    pub fn languages(&self) -> Vec<&'static str> {
+        LANGUAGES
+            .iter()
+            .copied()
+            .filter(|lang| self.languages.is_empty() || self.languages.iter().any(|l| l == lang))
+            .collect()
+    }
+
+    /// Rows inserted per batch; 0 is treated as 1
+    pub fn batch_size(&self) -> usize {
+        self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1)
+    }
+
+    /// SDE build number recorded in `metadata`, if any (set by `sync`)
+    pub fn build_number(&self) -> Option<u64> {
+        self.metadata
+            .iter()
+            .find(|(key, _)| key == "build_number")
+            .and_then(|(_, value)| value.parse().ok())
+    }
+
+    /// Record ids a table is restricted to by `--id-filter`: the filtered table itself, or
+    /// a junction table whose parent id column references it. `None` imports every record.
+    pub fn key_filter(&self, schema: &TableSchema) -> Option<&BTreeSet<i64>> {
+        self.id_filters.iter().find_map(|(table, ids)| {
+            let keyed_by = match &schema.array_source {
+                None => schema.name == table,
+                Some(source) => schema.foreign_keys.iter().any(|fk| {
+                    fk.column == source.parent_id_column() && fk.references_table == table
+                }),
+            };
+            keyed_by.then_some(ids)
+        })
+    }
+
+    /// Whether the table stores the original JSON line in a `_raw` column
+    pub fn stores_raw_json(&self, schema: &TableSchema) -> bool {
+        self.raw_json_tables.iter().any(|t| t == schema.name)
+    }
+
+    /// Whether the table gets a `search_text` column
+    pub fn stores_search_text(&self, schema: &TableSchema) -> bool {
+        self.search_text_tables.iter().any(|t| t == schema.name)
+    }
+
+    /// Whether record keys need to be tracked during import
+    pub fn tracks_duplicate_keys(&self) -> bool {
+        self.warn_duplicate_keys || self.dedupe_keys
+    }
+
+    /// Parser options for a single table
+    pub fn parse_options(&self, schema: &TableSchema) -> ParseOptions {
+        ParseOptions {
+            raw_json: self.stores_raw_json(schema),
+            search_text: self.stores_search_text(schema),
+            search_text_descriptions: self.search_text_descriptions,
+            languages: self.languages(),
+            known_fields: (self.report_unknown_fields && schema.array_source.is_none())
+                .then(|| known_fields(schema.source_file)),
+        }
+    }
+}
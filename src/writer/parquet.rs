@@ -0,0 +1,325 @@
+//! Parquet output (`--output-format parquet`, behind the `parquet` feature)
+
+use anyhow::{bail, Context, Result};
+use arrow::array::{ArrayRef, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::options::ConvertOptions;
+use super::profile::Profiler;
+use super::sink::{convert_into, show_import_progress, RecordSink};
+use super::source::{parse_source, SourceItem, SourceProvider, UnpublishedKeys};
+use crate::parser::{ParsedRow, SqlValue, RAW_JSON_COLUMN, SEARCH_TEXT_COLUMN};
+use crate::schema::{ColumnType, TableSchema};
+use crate::ui::{ProgressEvent, Ui};
+
+/// Rows buffered per Parquet record batch
+const BATCH_SIZE: usize = 10_000;
+
+/// Writes one `<table>.parquet` file per table into a directory
+pub struct ParquetWriter {
+    output_dir: PathBuf,
+    options: ConvertOptions,
+    /// Unpublished parent keys, shared by the junction tables of each parent
+    unpublished_keys: UnpublishedKeys,
+}
+
+impl ParquetWriter {
+    pub fn new(output_dir: &Path, options: ConvertOptions) -> Result<Self> {
+        if let Some(flag) = options.sqlite_only_flag() {
+            bail!("{} needs SQLite output", flag);
+        }
+
+        std::fs::create_dir_all(output_dir)
+            .with_context(|| format!("Failed to create {:?}", output_dir))?;
+
+        Ok(Self {
+            output_dir: output_dir.to_path_buf(),
+            options,
+            unpublished_keys: UnpublishedKeys::default(),
+        })
+    }
+
+    /// Parsed row keys and Arrow fields for a table, in column order
+    fn columns(&self, schema: &TableSchema) -> Vec<(String, Field)> {
+        let naming = self.options.naming;
+        let mut columns = Vec::new();
+        let mut push = |key: String, data_type: DataType, nullable: bool| {
+            let field = Field::new(naming.column_name(&key), data_type, nullable);
+            columns.push((key, field));
+        };
+
+        for col in schema.columns {
+            match col.col_type {
+                ColumnType::Localized => {
+                    for lang in self.options.languages() {
+                        push(format!("{}_{}", col.name, lang), DataType::Utf8, true);
+                    }
+                }
+                _ => push(col.name.to_string(), arrow_type(&col.col_type), true),
+            }
+        }
+
+        if self.options.stores_raw_json(schema) {
+            push(RAW_JSON_COLUMN.to_string(), DataType::Utf8, true);
+        }
+        if self.options.stores_search_text(schema) {
+            push(SEARCH_TEXT_COLUMN.to_string(), DataType::Utf8, true);
+        }
+
+        columns
+    }
+}
+
+impl RecordSink for ParquetWriter {
+    fn create_tables(&mut self, tables: &[&TableSchema], ui: &mut impl Ui) -> Result<()> {
+        ui.log(format!(
+            "Writing {} tables to {:?}...",
+            tables.len(),
+            self.output_dir
+        ));
+        Ok(())
+    }
+
+    fn import_table(
+        &mut self,
+        schema: &TableSchema,
+        source: &dyn SourceProvider,
+        ui: &mut impl Ui,
+    ) -> Result<u64> {
+        if !source.has_source(schema.source_file) {
+            ui.warn(format!("{}: skipped (file not found)", schema.name));
+            return Ok(0);
+        }
+
+        let columns = self.columns(schema);
+        let arrow_schema: SchemaRef = Arc::new(Schema::new(
+            columns.iter().map(|(_, f)| f.clone()).collect::<Vec<_>>(),
+        ));
+        let out_path = self.output_dir.join(format!("{}.parquet", schema.name));
+        let file =
+            File::create(&out_path).with_context(|| format!("Failed to create {:?}", out_path))?;
+        let mut writer = ArrowWriter::try_new(file, arrow_schema.clone(), None)?;
+
+        let row_limit = self.options.row_limit.unwrap_or(u64::MAX);
+        let mut batch: Vec<ParsedRow> = Vec::with_capacity(BATCH_SIZE);
+        let mut count: u64 = 0;
+        let mut limited = false;
+
+        let mut write_batch = |batch: &mut Vec<ParsedRow>| -> Result<()> {
+            let record_batch = to_record_batch(&arrow_schema, &columns, batch)?;
+            writer.write(&record_batch)?;
+            batch.clear();
+            Ok(())
+        };
+
+        let skip_keys = self
+            .unpublished_keys
+            .for_table(source, schema, &self.options, ui)?;
+        let (reader, progress) = source.open(schema.source_file)?;
+        let profiler = Profiler::new(false);
+        parse_source(
+            reader,
+            schema,
+            &self.options,
+            skip_keys.as_deref(),
+            &profiler,
+            &mut |item| {
+                match item {
+                    SourceItem::Line { .. } | SourceItem::Row(_)
+                        if count + batch.len() as u64 >= row_limit =>
+                    {
+                        limited = true;
+                        return Ok(false);
+                    }
+                    SourceItem::Line { .. } => {}
+                    SourceItem::Row(row) => {
+                        batch.push(row);
+                        if batch.len() >= BATCH_SIZE {
+                            count += batch.len() as u64;
+                            write_batch(&mut batch)?;
+                            show_import_progress(ui, schema, count, &progress);
+                            ui.on_event(ProgressEvent::RowsInserted {
+                                name: schema.name,
+                                count,
+                            });
+                        }
+                    }
+                    SourceItem::Skipped { line_index, error }
+                    | SourceItem::Failed { line_index, error } => ui.warn(format!(
+                        "{}: skipped line {} of {}: {}",
+                        schema.name,
+                        line_index + 1,
+                        schema.source_file,
+                        error
+                    )),
+                }
+                Ok(true)
+            },
+        )?;
+
+        if !batch.is_empty() {
+            count += batch.len() as u64;
+            write_batch(&mut batch)?;
+        }
+        writer
+            .close()
+            .with_context(|| format!("Failed to write {:?}", out_path))?;
+
+        ui.on_event(ProgressEvent::TableFinished {
+            name: schema.name,
+            rows: count,
+        });
+        if limited {
+            ui.log(format!("{}: {} records (limited)", schema.name, count));
+        } else {
+            ui.log(format!("{}: {} records", schema.name, count));
+        }
+
+        Ok(count)
+    }
+
+    fn finalize(self, ui: &mut impl Ui) -> Result<()> {
+        ui.log(format!("Wrote Parquet files to {:?}", self.output_dir));
+        Ok(())
+    }
+}
+
+/// Convert JSONL files into one Parquet file per table in `output_dir`
+pub fn convert_to_parquet(
+    source: &dyn SourceProvider,
+    output_dir: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+) -> Result<u64> {
+    options.validate()?;
+    let writer = ParquetWriter::new(output_dir, options.clone())?;
+    convert_into(writer, source, &tables, ui)
+}
+
+/// Arrow type for a (non-localized) column type
+fn arrow_type(col_type: &ColumnType) -> DataType {
+    match col_type {
+        ColumnType::Integer => DataType::Int64,
+        ColumnType::Real => DataType::Float64,
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Text | ColumnType::Json | ColumnType::Timestamp | ColumnType::Localized => {
+            DataType::Utf8
+        }
+    }
+}
+
+/// Build a record batch from parsed rows, keeping NULLs as Arrow nulls
+fn to_record_batch(
+    arrow_schema: &SchemaRef,
+    columns: &[(String, Field)],
+    rows: &[ParsedRow],
+) -> Result<RecordBatch> {
+    let arrays: Vec<ArrayRef> = columns
+        .iter()
+        .map(|(key, field)| {
+            let values = rows.iter().map(|row| row.values.get(key));
+            column_array(field.data_type(), values)
+        })
+        .collect();
+
+    Ok(RecordBatch::try_new(arrow_schema.clone(), arrays)?)
+}
+
+/// Convert one column of values into an Arrow array of `data_type`
+fn column_array<'a>(
+    data_type: &DataType,
+    values: impl Iterator<Item = Option<&'a SqlValue>>,
+) -> ArrayRef {
+    match data_type {
+        DataType::Int64 => {
+            let mut builder = Int64Builder::new();
+            for value in values {
+                match value {
+                    Some(SqlValue::Integer(i)) => builder.append_value(*i),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Float64 => {
+            let mut builder = Float64Builder::new();
+            for value in values {
+                match value {
+                    Some(SqlValue::Real(f)) => builder.append_value(*f),
+                    Some(SqlValue::Integer(i)) => builder.append_value(*i as f64),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Boolean => {
+            let mut builder = BooleanBuilder::new();
+            for value in values {
+                match value {
+                    Some(SqlValue::Integer(i)) => builder.append_value(*i != 0),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        _ => {
+            let mut builder = StringBuilder::new();
+            for value in values {
+                match value {
+                    Some(SqlValue::Text(s)) => builder.append_value(s),
+                    Some(SqlValue::Integer(i)) => builder.append_value(i.to_string()),
+                    Some(SqlValue::Real(f)) => builder.append_value(f.to_string()),
+                    Some(SqlValue::Null) | None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::CATEGORIES;
+    use crate::ui::SilentUi;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_convert_to_parquet() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}, "published": true}
+{"_key": 7, "name": {"en": "Module"}}
+"#,
+        )
+        .unwrap();
+        let out_dir = dir.path().join("parquet");
+
+        let count = convert_to_parquet(
+            &dir.path(),
+            &out_dir,
+            vec![&CATEGORIES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+
+        let file = File::open(out_dir.join("categories.parquet")).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let names: Vec<&str> = schema.columns().iter().map(|c| c.name()).collect();
+        assert_eq!(names[0], "id");
+        assert!(names.contains(&"name_en"));
+        assert!(names.contains(&"published"));
+    }
+}
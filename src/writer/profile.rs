@@ -0,0 +1,209 @@
+//! Lightweight timing of the import hot paths (`--profile`)
+
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Import stage being timed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Reading lines from the JSONL file
+    Read,
+    /// Parsing JSON into rows
+    Parse,
+    /// Binding and inserting rows
+    Insert,
+}
+
+/// Cumulative time spent in each stage while importing one table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableProfile {
+    pub read: Duration,
+    pub parse: Duration,
+    pub insert: Duration,
+    /// Wall-clock time of the whole import (stages may overlap with `--jobs`)
+    pub elapsed: Duration,
+    /// Rows written
+    pub rows: u64,
+}
+
+impl TableProfile {
+    /// Rows written per second of wall-clock time
+    pub fn rows_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.rows as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    fn add(&mut self, stage: Stage, elapsed: Duration) {
+        match stage {
+            Stage::Read => self.read += elapsed,
+            Stage::Parse => self.parse += elapsed,
+            Stage::Insert => self.insert += elapsed,
+        }
+    }
+}
+
+impl std::ops::Add for TableProfile {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            read: self.read + other.read,
+            parse: self.parse + other.parse,
+            insert: self.insert + other.insert,
+            elapsed: self.elapsed + other.elapsed,
+            rows: self.rows + other.rows,
+        }
+    }
+}
+
+impl fmt::Display for TableProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2}s, {} rows, {:.0} rows/s (read {:.2}s, parse {:.2}s, insert {:.2}s)",
+            self.elapsed.as_secs_f64(),
+            self.rows,
+            self.rows_per_sec(),
+            self.read.as_secs_f64(),
+            self.parse.as_secs_f64(),
+            self.insert.as_secs_f64()
+        )
+    }
+}
+
+/// Accumulates stage timings; when disabled it never reads the clock.
+/// Timings may nest (e.g. inserts flushed while a line is still being parsed);
+/// each stage is then charged only its own time, excluding nested stages.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    enabled: bool,
+    current: Cell<TableProfile>,
+    /// Time spent in stages nested inside the innermost running timer
+    nested: Cell<Duration>,
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Run `f`, adding its duration to `stage` if profiling is enabled
+    #[inline]
+    pub fn time<T>(&self, stage: Stage, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+
+        let outer_nested = self.nested.replace(Duration::ZERO);
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        let mut profile = self.current.get();
+        profile.add(stage, elapsed.saturating_sub(self.nested.get()));
+        self.current.set(profile);
+        self.nested.set(outer_nested + elapsed);
+
+        result
+    }
+
+    /// Timings collected so far, or `None` if profiling is disabled
+    pub fn finish(self) -> Option<TableProfile> {
+        self.enabled.then(|| self.current.get())
+    }
+}
+
+/// Table profiles ordered by wall-clock time, slowest first
+pub fn slowest_first(
+    profiles: &[(&'static str, TableProfile)],
+) -> Vec<(&'static str, TableProfile)> {
+    let mut sorted = profiles.to_vec();
+    sorted.sort_by_key(|(_, profile)| Reverse(profile.elapsed));
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiler_accumulates_per_stage() {
+        let profiler = Profiler::new(true);
+        let value = profiler.time(Stage::Parse, || {
+            std::thread::sleep(Duration::from_millis(2));
+            7
+        });
+        profiler.time(Stage::Parse, || ());
+
+        assert_eq!(value, 7);
+        let profile = profiler.finish().unwrap();
+        assert!(profile.parse >= Duration::from_millis(2));
+        assert_eq!(profile.read, Duration::ZERO);
+        assert_eq!(profile.insert, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_disabled_profiler_records_nothing() {
+        let profiler = Profiler::new(false);
+        assert_eq!(profiler.time(Stage::Insert, || 1 + 1), 2);
+        assert!(profiler.finish().is_none());
+    }
+
+    #[test]
+    fn test_nested_stages_are_exclusive() {
+        let profiler = Profiler::new(true);
+        profiler.time(Stage::Parse, || {
+            profiler.time(Stage::Insert, || {
+                std::thread::sleep(Duration::from_millis(5));
+            });
+        });
+
+        let profile = profiler.finish().unwrap();
+        assert!(profile.insert >= Duration::from_millis(5));
+        assert!(profile.parse < profile.insert);
+    }
+
+    #[test]
+    fn test_profile_display() {
+        let profile = TableProfile {
+            read: Duration::from_secs(2),
+            parse: Duration::from_millis(18_500),
+            insert: Duration::from_secs(9),
+            elapsed: Duration::from_secs(30),
+            rows: 60_000,
+        };
+        assert_eq!(
+            profile.to_string(),
+            "30.00s, 60000 rows, 2000 rows/s (read 2.00s, parse 18.50s, insert 9.00s)"
+        );
+    }
+
+    #[test]
+    fn test_slowest_first() {
+        let timed = |secs| TableProfile {
+            elapsed: Duration::from_secs(secs),
+            ..Default::default()
+        };
+        let profiles = [
+            ("icons", timed(1)),
+            ("types", timed(9)),
+            ("groups", timed(3)),
+        ];
+
+        let names: Vec<&str> = slowest_first(&profiles)
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        assert_eq!(names, vec!["types", "groups", "icons"]);
+        assert_eq!(TableProfile::default().rows_per_sec(), 0.0);
+    }
+}
@@ -1,75 +1,298 @@
-use crate::schema::{ColumnType, TableSchema, LANGUAGES};
+use super::options::ConvertOptions;
+use crate::parser::{RAW_JSON_COLUMN, SEARCH_TEXT_COLUMN};
+use crate::schema::{ColumnType, Index, TableSchema, ALL_VIEWS};
 
-/// Generate CREATE TABLE SQL for a table schema
-pub fn generate_create_table(schema: &TableSchema) -> String {
-    let mut sql = format!("CREATE TABLE {} (\n", schema.name);
+/// SQL database the DDL is generated for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    #[default]
+    Sqlite,
+    /// No `STRICT`/`WITHOUT ROWID` tables and no foreign keys, which DuckDB would
+    /// enforce on every insert; booleans get a native type
+    DuckDb,
+}
+
+impl Dialect {
+    /// Column type for a (non-localized) column type
+    fn sql_type(self, col_type: &ColumnType) -> &'static str {
+        match (self, col_type) {
+            (Dialect::Sqlite, ColumnType::Integer | ColumnType::Boolean) => "INTEGER",
+            (Dialect::Sqlite, ColumnType::Real) => "REAL",
+            (Dialect::DuckDb, ColumnType::Integer) => "BIGINT",
+            (Dialect::DuckDb, ColumnType::Real) => "DOUBLE",
+            (Dialect::DuckDb, ColumnType::Boolean) => "BOOLEAN",
+            (
+                _,
+                ColumnType::Text | ColumnType::Json | ColumnType::Timestamp | ColumnType::Localized,
+            ) => self.text_type(),
+        }
+    }
+
+    fn text_type(self) -> &'static str {
+        match self {
+            Dialect::Sqlite => "TEXT",
+            Dialect::DuckDb => "VARCHAR",
+        }
+    }
+}
+
+/// Concrete SQLite columns for a table as `(column_name, sql_type, nullable)`,
+/// expanding localized columns to one nullable TEXT column per language
+pub fn column_definitions(
+    schema: &TableSchema,
+    languages: &[&str],
+) -> Vec<(String, &'static str, bool)> {
+    column_definitions_for(schema, languages, Dialect::Sqlite)
+}
+
+/// Concrete columns for a table in the given dialect, as for `column_definitions`
+pub fn column_definitions_for(
+    schema: &TableSchema,
+    languages: &[&str],
+    dialect: Dialect,
+) -> Vec<(String, &'static str, bool)> {
     let mut columns = Vec::new();
 
     for col in schema.columns {
         match col.col_type {
             ColumnType::Localized => {
                 // Expand localized columns to per-language columns
-                for lang in LANGUAGES {
-                    let col_name = format!("{}_{}", col.name, lang);
-                    columns.push(format!("    {} TEXT", col_name));
+                for lang in languages {
+                    let nullable = !(col.require_en && *lang == "en");
+                    columns.push((
+                        format!("{}_{}", col.name, lang),
+                        dialect.text_type(),
+                        nullable,
+                    ));
                 }
             }
-            _ => {
-                let sql_type = match col.col_type {
-                    ColumnType::Integer => "INTEGER",
-                    ColumnType::Real => "REAL",
-                    ColumnType::Text => "TEXT",
-                    ColumnType::Boolean => "INTEGER",
-                    ColumnType::Json => "TEXT",
-                    ColumnType::Localized => unreachable!(),
-                };
-
-                let null_constraint = if !col.nullable { " NOT NULL" } else { "" };
-                let pk = if col.name == "id" { " PRIMARY KEY" } else { "" };
-
-                columns.push(format!(
-                    "    {} {}{}{}",
-                    col.name, sql_type, pk, null_constraint
-                ));
-            }
+            _ => columns.push((
+                col.name.to_string(),
+                dialect.sql_type(&col.col_type),
+                col.nullable,
+            )),
         }
     }
 
-    // Add foreign key constraints
-    for fk in schema.foreign_keys {
+    columns
+}
+
+/// Generate CREATE TABLE SQL for a table schema
+pub fn generate_create_table(schema: &TableSchema) -> String {
+    generate_create_table_with(schema, &ConvertOptions::default())
+}
+
+/// Generate CREATE TABLE SQL for a table schema, including option-dependent columns
+pub fn generate_create_table_with(schema: &TableSchema, options: &ConvertOptions) -> String {
+    generate_create_table_for(schema, options, Dialect::Sqlite)
+}
+
+/// Generate CREATE TABLE SQL for a table schema in the given dialect
+pub fn generate_create_table_for(
+    schema: &TableSchema,
+    options: &ConvertOptions,
+    dialect: Dialect,
+) -> String {
+    let naming = options.naming;
+    let text = dialect.text_type();
+    let mut sql = format!("CREATE TABLE {}{} (\n", if_not_exists(options), schema.name);
+    let mut columns = Vec::new();
+
+    for (name, sql_type, nullable) in column_definitions_for(schema, &options.languages(), dialect)
+    {
+        let null_constraint = if !nullable { " NOT NULL" } else { "" };
+        let pk = if name == "id" && schema.primary_key.is_empty() {
+            " PRIMARY KEY"
+        } else {
+            ""
+        };
+
+        // DuckDB rows are always appended with every value, so only SQLite declares it
+        let default = schema
+            .columns
+            .iter()
+            .find(|c| c.name == name)
+            .and_then(|c| c.default)
+            .filter(|_| dialect == Dialect::Sqlite)
+            .map(|d| format!(" DEFAULT {}", d.sql_literal()))
+            .unwrap_or_default();
+
+        columns.push(format!(
+            "    {} {}{}{}{}",
+            naming.column_name(&name),
+            sql_type,
+            pk,
+            null_constraint,
+            default
+        ));
+    }
+
+    if options.stores_raw_json(schema) {
+        columns.push(format!("    {} {}", RAW_JSON_COLUMN, text));
+    }
+
+    if options.stores_search_text(schema) {
+        columns.push(format!(
+            "    {} {}",
+            naming.column_name(SEARCH_TEXT_COLUMN),
+            text
+        ));
+    }
+
+    if !schema.primary_key.is_empty() {
+        let pk_columns: Vec<String> = schema
+            .primary_key
+            .iter()
+            .map(|c| naming.column_name(c))
+            .collect();
+        columns.push(format!("    PRIMARY KEY ({})", pk_columns.join(", ")));
+    }
+
+    // Add foreign key constraints, unless the referenced tables end up in other files
+    let foreign_keys = match dialect {
+        Dialect::Sqlite if !options.split_by_table => schema.foreign_keys,
+        _ => &[],
+    };
+    for fk in foreign_keys {
         columns.push(format!(
             "    FOREIGN KEY ({}) REFERENCES {}({})",
-            fk.column, fk.references_table, fk.references_column
+            naming.column_name(fk.column),
+            fk.references_table,
+            naming.column_name(fk.references_column)
         ));
     }
 
     sql.push_str(&columns.join(",\n"));
     sql.push_str("\n)");
 
+    let mut table_options = Vec::new();
+    if schema.without_rowid && dialect == Dialect::Sqlite {
+        table_options.push("WITHOUT ROWID");
+    }
+    if schema.strict && dialect == Dialect::Sqlite {
+        table_options.push("STRICT");
+    }
+    if !table_options.is_empty() {
+        sql.push(' ');
+        sql.push_str(&table_options.join(", "));
+    }
+
     sql
 }
 
 /// Generate CREATE INDEX statements from explicit index definitions
 pub fn generate_indexes(schema: &TableSchema) -> Vec<String> {
-    schema
-        .indexes
+    generate_indexes_with(schema, &ConvertOptions::default())
+}
+
+/// Generate CREATE INDEX statements, naming columns per the conversion options.
+/// Indexes declared more than once are created once (unique if any declaration is),
+/// and indexes the primary key's own index already serves are left out.
+pub fn generate_indexes_with(schema: &TableSchema, options: &ConvertOptions) -> Vec<String> {
+    let primary_key = schema.primary_key_columns();
+
+    let mut indexes: Vec<Index> = Vec::new();
+    for idx in schema.indexes {
+        match indexes.iter_mut().find(|i| i.columns == idx.columns) {
+            Some(existing) => existing.unique |= idx.unique,
+            None => indexes.push(idx.clone()),
+        }
+    }
+
+    indexes
         .iter()
+        // The primary key's index also serves lookups on its leading columns, but
+        // only enforces uniqueness over all of them
+        .filter(|idx| {
+            idx.columns != primary_key && (idx.unique || !primary_key.starts_with(idx.columns))
+        })
         .map(|idx| {
-            let cols = idx.columns.join(", ");
+            let cols = idx
+                .columns
+                .iter()
+                .map(|c| options.naming.column_name(c))
+                .collect::<Vec<_>>()
+                .join(", ");
             let unique = if idx.unique { "UNIQUE " } else { "" };
             let name_suffix = idx.columns.join("_");
             format!(
-                "CREATE {}INDEX idx_{}_{} ON {}({})",
-                unique, schema.name, name_suffix, schema.name, cols
+                "CREATE {}INDEX {}idx_{}_{} ON {}({})",
+                unique,
+                if_not_exists(options),
+                schema.name,
+                name_suffix,
+                schema.name,
+                cols
             )
         })
         .collect()
 }
 
+/// `IF NOT EXISTS ` when tables are created in an existing database (`--incremental`)
+fn if_not_exists(options: &ConvertOptions) -> &'static str {
+    if options.incremental {
+        "IF NOT EXISTS "
+    } else {
+        ""
+    }
+}
+
+/// Generate CREATE VIEW statements for views whose source tables are all included
+pub fn generate_views(tables: &[&TableSchema]) -> Vec<String> {
+    ALL_VIEWS
+        .iter()
+        .filter(|view| {
+            view.tables
+                .iter()
+                .all(|name| tables.iter().any(|t| t.name == *name))
+        })
+        .map(|view| format!("CREATE VIEW {} AS\n    {}", view.name, view.select))
+        .collect()
+}
+
+/// Generate the statements (re)creating `<table>_fts`, an FTS5 index over a table's
+/// localized `name` column with one column per language, whose `rowid` is the record
+/// `id`. The index holds its own copy of the names (`WITHOUT ROWID` tables can't serve
+/// as external content) and is filled once, so it must be rebuilt whenever the table
+/// changes. Empty for tables without a localized `name`.
+pub fn generate_fts(schema: &TableSchema, options: &ConvertOptions) -> Vec<String> {
+    let has_name = schema
+        .columns
+        .iter()
+        .any(|c| c.name == "name" && c.col_type == ColumnType::Localized);
+    if !has_name || schema.array_source.is_some() {
+        return Vec::new();
+    }
+
+    let fts = format!("{}_fts", schema.name);
+    let columns = options
+        .languages()
+        .iter()
+        .map(|lang| options.naming.column_name(&format!("name_{}", lang)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    vec![
+        format!("DROP TABLE IF EXISTS {}", fts),
+        format!("CREATE VIRTUAL TABLE {} USING fts5({})", fts, columns),
+        format!(
+            "INSERT INTO {fts}(rowid, {columns}) SELECT {id}, {columns} FROM {table}",
+            fts = fts,
+            columns = columns,
+            id = options.naming.column_name("id"),
+            table = schema.name
+        ),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::tables::TYPES;
+    use crate::schema::tables::{
+        BLUEPRINT_MATERIALS, BLUEPRINT_PRODUCTS, GROUPS, ICONS, MAP_SOLAR_SYSTEMS, MAP_STARS,
+        TYPES, TYPE_DOGMA_ATTRIBUTES, TYPE_MATERIALS,
+    };
+    use crate::schema::{Column, ALL_TABLES, LANGUAGES};
+    use crate::writer::Naming;
 
     #[test]
     fn test_generate_create_table() {
@@ -81,10 +304,361 @@ mod tests {
         assert!(sql.contains("FOREIGN KEY (group_id) REFERENCES groups(id)"));
     }
 
+    #[test]
+    fn test_generate_create_table_raw_json() {
+        assert!(!generate_create_table(&TYPES).contains("_raw"));
+
+        let options = ConvertOptions {
+            raw_json_tables: vec!["types".to_string()],
+            ..Default::default()
+        };
+        let sql = generate_create_table_with(&TYPES, &options);
+        assert!(sql.contains("_raw TEXT"));
+    }
+
+    #[test]
+    fn test_generate_create_table_search_text() {
+        let options = ConvertOptions {
+            search_text_tables: vec!["types".to_string()],
+            ..Default::default()
+        };
+        assert!(generate_create_table_with(&TYPES, &options).contains("search_text TEXT"));
+        assert!(!generate_create_table_with(&GROUPS, &options).contains("search_text"));
+
+        let options = ConvertOptions {
+            search_text_tables: vec!["type_dogma_attributes".to_string()],
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn test_generate_create_table_camel_naming() {
+        let options = ConvertOptions {
+            naming: Naming::Camel,
+            ..Default::default()
+        };
+        let sql = generate_create_table_with(&TYPES, &options);
+        assert!(sql.contains("id INTEGER PRIMARY KEY"));
+        assert!(sql.contains("nameEn TEXT"));
+        assert!(sql.contains("FOREIGN KEY (groupID) REFERENCES groups(id)"));
+
+        let indexes = generate_indexes_with(&TYPES, &options);
+        assert!(indexes
+            .iter()
+            .any(|i| i.contains("idx_types_group_id ON types(groupID)")));
+    }
+
+    #[test]
+    fn test_column_definitions_match_create_table() {
+        let definitions = column_definitions(&TYPES, LANGUAGES);
+        let sql = generate_create_table(&TYPES);
+
+        // Every column line in the DDL, in order, excluding constraints
+        let ddl_columns: Vec<&str> = sql
+            .lines()
+            .skip(1)
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| {
+                !line.starts_with("FOREIGN KEY")
+                    && !line.starts_with("PRIMARY KEY")
+                    && !line.starts_with(')')
+            })
+            .collect();
+        assert_eq!(ddl_columns.len(), definitions.len());
+
+        for ((name, sql_type, nullable), line) in definitions.iter().zip(&ddl_columns) {
+            let mut parts = line.split_whitespace();
+            assert_eq!(parts.next(), Some(name.as_str()));
+            assert_eq!(parts.next(), Some(*sql_type));
+            assert_eq!(!line.contains("NOT NULL"), *nullable, "{}", line);
+        }
+
+        assert!(definitions.contains(&("name_en".to_string(), "TEXT", false)));
+        assert!(definitions.contains(&("description_en".to_string(), "TEXT", true)));
+        assert!(definitions.contains(&("id".to_string(), "INTEGER", false)));
+        assert_eq!(
+            column_definitions(&TYPES, &["en"]).len() + 7 * 2,
+            definitions.len()
+        );
+    }
+
+    #[test]
+    fn test_generate_create_table_duckdb() {
+        let sql =
+            generate_create_table_for(&MAP_STARS, &ConvertOptions::default(), Dialect::DuckDb);
+        assert!(sql.contains("id BIGINT PRIMARY KEY"));
+        assert!(sql.contains("locked BOOLEAN"));
+        assert!(sql.contains("spectral_class VARCHAR"));
+        assert!(!sql.contains("FOREIGN KEY"));
+        assert!(!sql.contains("STRICT"));
+        assert!(!sql.contains("WITHOUT ROWID"));
+
+        let sql = generate_create_table_for(
+            &TYPE_DOGMA_ATTRIBUTES,
+            &ConvertOptions::default(),
+            Dialect::DuckDb,
+        );
+        assert!(sql.contains("PRIMARY KEY (type_id, attribute_id)"));
+    }
+
     #[test]
     fn test_generate_indexes() {
         let indexes = generate_indexes(&TYPES);
         assert!(indexes.iter().any(|i| i.contains("idx_types_group_id")));
         assert!(indexes.iter().any(|i| i.contains("idx_types_name_en")));
     }
+
+    #[test]
+    fn test_generate_indexes_declared_unique_and_deduplicated() {
+        static DECLARED: TableSchema = TableSchema {
+            name: "declared",
+            source_file: "declared.jsonl",
+            columns: &[
+                Column::required("type_id", ColumnType::Integer),
+                Column::required("activity", ColumnType::Text),
+                Column::required("material_id", ColumnType::Integer),
+            ],
+            foreign_keys: &[],
+            indexes: &[
+                Index::on(&["material_id"]),
+                Index::unique(&["material_id", "activity"]),
+                Index::on(&["material_id", "activity"]),
+                Index::on(&["type_id"]),
+                Index::unique(&["type_id"]),
+                Index::on(&["type_id", "activity", "material_id"]),
+            ],
+            child_tables: &[],
+            array_source: None,
+            strict: false,
+            without_rowid: false,
+            primary_key: &["type_id", "activity", "material_id"],
+        };
+
+        assert_eq!(
+            generate_indexes(&DECLARED),
+            vec![
+                "CREATE INDEX idx_declared_material_id ON declared(material_id)",
+                "CREATE UNIQUE INDEX idx_declared_material_id_activity ON declared(material_id, activity)",
+                "CREATE UNIQUE INDEX idx_declared_type_id ON declared(type_id)",
+            ]
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(&generate_create_table(&DECLARED))
+            .unwrap();
+        for index_sql in generate_indexes(&DECLARED) {
+            conn.execute_batch(&index_sql).unwrap();
+        }
+        let insert = "INSERT INTO declared VALUES (?, ?, ?)";
+        conn.execute(insert, rusqlite::params![1, "copying", 34])
+            .unwrap();
+        assert!(conn
+            .execute(insert, rusqlite::params![2, "copying", 34])
+            .is_err());
+    }
+
+    #[test]
+    fn test_generate_indexes_skip_primary_key_prefix() {
+        // type_id leads the (type_id, material_type_id) primary key
+        let indexes = generate_indexes(&TYPE_MATERIALS);
+        assert_eq!(
+            indexes,
+            vec!["CREATE INDEX idx_type_materials_material_type_id ON type_materials(material_type_id)"]
+        );
+    }
+
+    #[test]
+    fn test_generate_views() {
+        assert!(generate_views(&[&TYPES]).is_empty());
+
+        let views = generate_views(&[&TYPES, &BLUEPRINT_PRODUCTS]);
+        assert_eq!(views.len(), 1);
+        assert!(views[0].starts_with("CREATE VIEW manufacturing_sources AS"));
+        assert!(views[0].contains("activity = 'manufacturing'"));
+
+        let views = generate_views(&[&ICONS, &TYPES]);
+        assert_eq!(views.len(), 1);
+        assert!(views[0].starts_with("CREATE VIEW type_icons AS"));
+    }
+
+    /// Execute the DDL for every table against an in-memory database
+    fn assert_ddl_executes(options: &ConvertOptions) {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let tables: Vec<&TableSchema> = ALL_TABLES.to_vec();
+
+        for schema in &tables {
+            let sql = generate_create_table_with(schema, options);
+            conn.execute_batch(&sql)
+                .unwrap_or_else(|e| panic!("Invalid DDL for {}: {}\n{}", schema.name, e, sql));
+
+            for index_sql in generate_indexes_with(schema, options) {
+                conn.execute_batch(&index_sql).unwrap_or_else(|e| {
+                    panic!("Invalid index for {}: {}\n{}", schema.name, e, index_sql)
+                });
+            }
+        }
+
+        if options.views {
+            for view_sql in generate_views(&tables) {
+                conn.execute_batch(&view_sql)
+                    .unwrap_or_else(|e| panic!("Invalid view: {}\n{}", e, view_sql));
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_tables_generate_valid_sql() {
+        assert_ddl_executes(&ConvertOptions {
+            views: true,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_all_tables_generate_valid_sql_with_options() {
+        let every_base_table: Vec<String> = ALL_TABLES
+            .iter()
+            .filter(|t| t.array_source.is_none())
+            .map(|t| t.name.to_string())
+            .collect();
+
+        assert_ddl_executes(&ConvertOptions {
+            naming: Naming::Camel,
+            raw_json_tables: every_base_table,
+            search_text_tables: vec!["types".to_string()],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_generate_create_table_selected_languages() {
+        let options = ConvertOptions {
+            languages: vec!["de".to_string(), "en".to_string()],
+            ..Default::default()
+        };
+        let sql = generate_create_table_with(&TYPES, &options);
+        assert!(sql.contains("name_en TEXT NOT NULL"));
+        assert!(sql.contains("name_de TEXT"));
+        assert!(!sql.contains("name_fr"));
+        assert_ddl_executes(&options);
+
+        let options = ConvertOptions {
+            languages: vec!["de".to_string()],
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+        let options = ConvertOptions {
+            languages: vec!["en".to_string(), "xx".to_string()],
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn test_generate_create_table_strict_without_rowid() {
+        assert!(!generate_create_table(&GROUPS).contains("STRICT"));
+        assert!(generate_create_table(&TYPES).ends_with(") STRICT"));
+        assert!(generate_create_table(&MAP_STARS).ends_with(") WITHOUT ROWID, STRICT"));
+
+        // WITHOUT ROWID needs a primary key
+        for schema in ALL_TABLES.iter().filter(|t| t.without_rowid) {
+            assert!(
+                schema.columns.iter().any(|c| c.name == "id"),
+                "{} has no id column",
+                schema.name
+            );
+        }
+
+        // SQLite enforces the declared types of strict tables
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+        conn.execute_batch(&generate_create_table(&MAP_STARS))
+            .unwrap();
+        conn.execute(
+            "INSERT INTO map_stars (id, solar_system_id, locked, radius) VALUES (1, 2, 1, 3)",
+            [],
+        )
+        .unwrap();
+        assert!(conn
+            .execute(
+                "INSERT INTO map_stars (id, solar_system_id) VALUES (2, 'abc')",
+                []
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_generate_create_table_composite_primary_key() {
+        let sql = generate_create_table(&BLUEPRINT_MATERIALS);
+        assert!(sql.contains("    PRIMARY KEY (blueprint_id, activity, type_id),\n"));
+
+        let options = ConvertOptions {
+            naming: Naming::Camel,
+            ..Default::default()
+        };
+        let sql = generate_create_table_with(&TYPE_DOGMA_ATTRIBUTES, &options);
+        assert!(sql.contains("PRIMARY KEY (typeID, attributeID)"));
+
+        // Primary key columns exist, and an id column isn't a second primary key
+        for schema in ALL_TABLES.iter().filter(|t| !t.primary_key.is_empty()) {
+            for col in schema.primary_key {
+                assert!(
+                    schema.columns.iter().any(|c| c.name == *col),
+                    "{}.{} does not exist",
+                    schema.name,
+                    col
+                );
+            }
+            assert!(!generate_create_table(schema).contains("id INTEGER PRIMARY KEY"));
+        }
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+        conn.execute_batch(&generate_create_table(&TYPE_DOGMA_ATTRIBUTES))
+            .unwrap();
+        let insert =
+            "INSERT INTO type_dogma_attributes (type_id, attribute_id, value) VALUES (1, 2, 3.0)";
+        conn.execute(insert, []).unwrap();
+        assert!(conn.execute(insert, []).is_err());
+    }
+
+    #[test]
+    fn test_localized_require_en() {
+        let sql = generate_create_table(&TYPES);
+        assert!(sql.contains("name_en TEXT NOT NULL"));
+        assert!(sql.contains("name_de TEXT,"));
+        assert!(sql.contains("description_en TEXT,"));
+    }
+
+    #[test]
+    fn test_generate_fts() {
+        let options = ConvertOptions {
+            languages: vec!["en".to_string(), "fr".to_string()],
+            ..Default::default()
+        };
+        let statements = generate_fts(&TYPES, &options);
+        assert_eq!(
+            statements[1],
+            "CREATE VIRTUAL TABLE types_fts USING fts5(name_en, name_fr)"
+        );
+        assert_eq!(
+            statements[2],
+            "INSERT INTO types_fts(rowid, name_en, name_fr) SELECT id, name_en, name_fr FROM types"
+        );
+        assert!(!generate_fts(&MAP_SOLAR_SYSTEMS, &options).is_empty());
+        assert!(generate_fts(&ICONS, &options).is_empty());
+        assert!(generate_fts(&TYPE_DOGMA_ATTRIBUTES, &options).is_empty());
+    }
+
+    #[test]
+    fn test_column_default() {
+        let sql = generate_create_table(&TYPES);
+        assert!(sql.contains("    published INTEGER DEFAULT 0,\n"));
+        assert!(sql.contains("    group_id INTEGER,\n"));
+
+        let options = ConvertOptions::default();
+        let duckdb = generate_create_table_for(&TYPES, &options, Dialect::DuckDb);
+        assert!(duckdb.contains("    published BOOLEAN,\n"));
+    }
 }
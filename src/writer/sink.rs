@@ -0,0 +1,115 @@
+//! Output backends for converted tables
+
+use anyhow::Result;
+#[cfg(any(not(feature = "parquet"), not(feature = "duckdb")))]
+use std::path::Path;
+
+use super::source::{ReadProgress, SourceProvider};
+use crate::schema::TableSchema;
+use crate::ui::{ProgressEvent, Ui};
+
+/// Output format of a conversion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// A single SQLite database
+    #[default]
+    Sqlite,
+    /// One `.parquet` file per table in an output directory
+    Parquet,
+    /// A single DuckDB database
+    Duckdb,
+}
+
+/// Destination for converted tables, written one table at a time
+pub trait RecordSink {
+    /// Prepare storage for the tables about to be imported
+    fn create_tables(&mut self, tables: &[&TableSchema], ui: &mut impl Ui) -> Result<()>;
+
+    /// Import one table from its JSONL source, returning the number of rows written
+    fn import_table(
+        &mut self,
+        schema: &TableSchema,
+        source: &dyn SourceProvider,
+        ui: &mut impl Ui,
+    ) -> Result<u64>;
+
+    /// Complete the output once every table is imported
+    fn finalize(self, ui: &mut impl Ui) -> Result<()>
+    where
+        Self: Sized;
+}
+
+/// Convert JSONL files into `sink`, importing tables in the given (dependency) order
+pub fn convert_into(
+    mut sink: impl RecordSink,
+    source: &dyn SourceProvider,
+    tables: &[&TableSchema],
+    ui: &mut impl Ui,
+) -> Result<u64> {
+    sink.create_tables(tables, ui)?;
+
+    let mut total_records: u64 = 0;
+    for (i, schema) in tables.iter().enumerate() {
+        announce_table(tables, i, ui);
+        total_records += sink.import_table(schema, source, ui)?;
+    }
+
+    sink.finalize(ui)?;
+    Ok(total_records)
+}
+
+/// Log the start of a table import
+pub(crate) fn announce_table(tables: &[&TableSchema], index: usize, ui: &mut impl Ui) {
+    let schema = tables[index];
+    ui.log(format!(
+        "Importing table {}/{}: {}",
+        index + 1,
+        tables.len(),
+        schema.name
+    ));
+    ui.on_event(ProgressEvent::TableStarted {
+        name: schema.name,
+        index,
+        total_tables: tables.len(),
+    });
+}
+
+/// Show a table's import progress: the share of its source file read so far, labeled
+/// with the rows written (junction tables write several rows per line, so rows cannot
+/// serve as the measure)
+pub(crate) fn show_import_progress(
+    ui: &mut impl Ui,
+    schema: &TableSchema,
+    rows: u64,
+    progress: &ReadProgress,
+) {
+    ui.set_progress(
+        progress.read(),
+        progress.total(),
+        format!("{} ({} rows)", schema.name, rows),
+    );
+}
+
+/// Stand-in when built without Parquet support
+#[cfg(not(feature = "parquet"))]
+pub fn convert_to_parquet(
+    _source: &dyn SourceProvider,
+    _output_dir: &Path,
+    _tables: Vec<&TableSchema>,
+    _options: &super::ConvertOptions,
+    _ui: &mut impl Ui,
+) -> Result<u64> {
+    anyhow::bail!("Parquet output requires building with `--features parquet`")
+}
+
+/// Stand-in when built without DuckDB support
+#[cfg(not(feature = "duckdb"))]
+pub fn convert_to_duckdb(
+    _source: &dyn SourceProvider,
+    _output_db: &Path,
+    _tables: Vec<&TableSchema>,
+    _options: &super::ConvertOptions,
+    _ui: &mut impl Ui,
+) -> Result<u64> {
+    anyhow::bail!("DuckDB output requires building with `--features duckdb`")
+}
@@ -0,0 +1,489 @@
+//! Reading and parsing JSONL sources into rows, independently of the database,
+//! so tables can be parsed on worker threads while one thread writes (`--jobs`)
+
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Arc;
+use std::thread::{Scope, ScopedJoinHandle};
+
+use super::options::ConvertOptions;
+use super::profile::{Profiler, Stage, TableProfile};
+use crate::parser::{
+    for_each_junction_record_json, parse_record_json, record_key, ParsedRow, UnexpectedShape,
+};
+use crate::schema::{get_table, TableSchema};
+use crate::ui::Ui;
+
+/// Items sent from a parser thread per message
+const CHUNK_SIZE: usize = 1000;
+
+/// Messages buffered between a parser thread and the writer before the parser blocks
+const CHANNEL_CHUNKS: usize = 4;
+
+/// What parsing a source file yields, in file order
+pub enum SourceItem {
+    /// A non-empty line begins; `key` is its record key when duplicate keys are tracked
+    /// or the table is filtered by id (or by unpublished parents)
+    Line { key: Option<i64> },
+    /// A row parsed from the current line
+    Row(ParsedRow),
+    /// The current line had an unexpected shape and was skipped
+    Skipped {
+        line_index: usize,
+        error: anyhow::Error,
+    },
+    /// The current line failed to parse and was skipped (`continue_on_error`)
+    Failed {
+        line_index: usize,
+        error: anyhow::Error,
+    },
+}
+
+/// Receives parsed items; returning `false` stops parsing (e.g. `--limit` was reached)
+pub type ItemSink<'a> = dyn FnMut(SourceItem) -> Result<bool> + 'a;
+
+/// Where the JSONL source files of a conversion are read from: a directory (any path)
+/// or a zip/tar.gz archive read in place (`download::ArchiveSource`)
+pub trait SourceProvider {
+    /// Whether `source_file` (e.g. `types.jsonl`) is available
+    fn has_source(&self, source_file: &str) -> bool;
+
+    /// Open `source_file` for reading, along with how much of it has been read
+    fn open(&self, source_file: &str) -> Result<(Box<dyn BufRead + Send>, ReadProgress)>;
+}
+
+/// A directory of `.jsonl` (or `.jsonl.gz`) files
+impl<P: AsRef<Path> + ?Sized> SourceProvider for P {
+    fn has_source(&self, source_file: &str) -> bool {
+        source_path(self.as_ref(), source_file).is_some()
+    }
+
+    fn open(&self, source_file: &str) -> Result<(Box<dyn BufRead + Send>, ReadProgress)> {
+        open_source_with_progress(self.as_ref(), source_file)
+    }
+}
+
+/// Path of a table's source file in `input_dir`: the plain `.jsonl` file, or else a
+/// gzip-compressed `.jsonl.gz` sibling. `None` if neither exists.
+pub fn source_path(input_dir: &Path, source_file: &str) -> Option<PathBuf> {
+    let plain = input_dir.join(source_file);
+    if plain.is_file() {
+        return Some(plain);
+    }
+
+    let gzipped = input_dir.join(format!("{}.gz", source_file));
+    gzipped.is_file().then_some(gzipped)
+}
+
+/// Open a table's source file for reading, decompressing `.jsonl.gz` files
+pub fn open_source(input_dir: &Path, source_file: &str) -> Result<Box<dyn BufRead + Send>> {
+    Ok(open_source_with_progress(input_dir, source_file)?.0)
+}
+
+/// Open a table's source file like `open_source`, also returning how much of the file
+/// has been read. This stands in for a row total, which would need a pass of its own.
+pub fn open_source_with_progress(
+    input_dir: &Path,
+    source_file: &str,
+) -> Result<(Box<dyn BufRead + Send>, ReadProgress)> {
+    let file_path = source_path(input_dir, source_file)
+        .with_context(|| format!("Source file not found: {:?}", input_dir.join(source_file)))?;
+    let file =
+        File::open(&file_path).with_context(|| format!("Failed to open: {:?}", file_path))?;
+
+    // Compressed bytes for `.gz` files, so the total is simply the file size
+    let total = file.metadata()?.len();
+    let (file, progress) = ReadProgress::track(file, total);
+
+    let reader: Box<dyn BufRead + Send> = if file_path.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(BufReader::new(GzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+    Ok((reader, progress))
+}
+
+/// Bytes of a source file read so far, readable from any thread
+#[derive(Debug, Clone, Default)]
+pub struct ReadProgress {
+    read: Arc<AtomicU64>,
+    total: u64,
+}
+
+impl ReadProgress {
+    /// Wrap `reader`, counting the bytes read through it toward `total`
+    pub fn track<R: Read>(reader: R, total: u64) -> (CountingReader<R>, ReadProgress) {
+        let progress = ReadProgress {
+            read: Arc::new(AtomicU64::new(0)),
+            total,
+        };
+        let reader = CountingReader {
+            inner: reader,
+            read: progress.read.clone(),
+        };
+        (reader, progress)
+    }
+
+    /// Bytes read so far (read-ahead buffering is capped at the file size)
+    pub fn read(&self) -> u64 {
+        self.read.load(Ordering::Relaxed).min(self.total)
+    }
+
+    /// Size of the file in bytes
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+/// Counts the bytes read through it into a shared total (see `ReadProgress::track`)
+pub struct CountingReader<R> {
+    inner: R,
+    read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Record keys of unpublished parent records for junction tables with `published_only`,
+/// read once per parent source and shared by all junction tables of that parent
+#[derive(Default)]
+pub struct UnpublishedKeys {
+    by_parent: HashMap<&'static str, Arc<HashSet<i64>>>,
+}
+
+impl UnpublishedKeys {
+    /// Keys of the unpublished parents whose rows `schema` skips. `None` unless the
+    /// junction table's parent id references a table with a `published` column; if
+    /// that table's source is missing, nothing can be skipped and a warning is shown.
+    pub fn for_table(
+        &mut self,
+        source: &dyn SourceProvider,
+        schema: &TableSchema,
+        options: &ConvertOptions,
+        ui: &mut impl Ui,
+    ) -> Result<Option<Arc<HashSet<i64>>>> {
+        let Some(array_source) = schema
+            .array_source
+            .as_ref()
+            .filter(|_| options.published_only)
+        else {
+            return Ok(None);
+        };
+        let parent = schema
+            .foreign_keys
+            .iter()
+            .find(|fk| fk.column == array_source.parent_id_column())
+            .and_then(|fk| get_table(fk.references_table))
+            .filter(|parent| has_published_column(parent));
+        let Some(parent) = parent else {
+            return Ok(None);
+        };
+
+        if let Some(keys) = self.by_parent.get(parent.name) {
+            return Ok(Some(keys.clone()));
+        }
+        if !source.has_source(parent.source_file) {
+            ui.warn(format!(
+                "{}: {} not found, so rows of unpublished {} are kept",
+                schema.name, parent.source_file, parent.name
+            ));
+            return Ok(None);
+        }
+
+        let keys = Arc::new(read_unpublished_keys(source, parent)?);
+        self.by_parent.insert(parent.name, keys.clone());
+        Ok(Some(keys))
+    }
+}
+
+/// Record keys of the records in a table's source whose `published` flag is false
+fn read_unpublished_keys(
+    source: &dyn SourceProvider,
+    schema: &TableSchema,
+) -> Result<HashSet<i64>> {
+    let mut keys = HashSet::new();
+    let (reader, _) = source.open(schema.source_file)?;
+    for line in reader.lines() {
+        let line = line.context("Failed to read line")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Lines that fail to parse are reported when the parent table itself is imported
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if is_unpublished(&json) {
+            keys.extend(record_key(&json));
+        }
+    }
+    Ok(keys)
+}
+
+fn has_published_column(schema: &TableSchema) -> bool {
+    schema.columns.iter().any(|c| c.name == "published")
+}
+
+/// Whether a record's `published` flag is explicitly false. Records without one are
+/// kept, even though their stored flag defaults to 0.
+fn is_unpublished(json: &serde_json::Value) -> bool {
+    match json.get("published") {
+        Some(serde_json::Value::Bool(published)) => !published,
+        Some(value) => value.as_i64() == Some(0),
+        None => false,
+    }
+}
+
+/// Parse every line of a JSONL source, feeding items to `sink` until it returns `false`.
+/// Lines whose record key is in `skip_keys` are dropped (see `UnpublishedKeys`).
+pub fn parse_source(
+    reader: impl BufRead,
+    schema: &TableSchema,
+    options: &ConvertOptions,
+    skip_keys: Option<&HashSet<i64>>,
+    profiler: &Profiler,
+    sink: &mut ItemSink,
+) -> Result<()> {
+    let is_junction = schema.array_source.is_some();
+    let parse_options = options.parse_options(schema);
+    let track_keys = options.tracks_duplicate_keys();
+    let key_filter = options.key_filter(schema);
+    let skip_unpublished = options.published_only && has_published_column(schema);
+
+    let mut lines = reader.lines().enumerate();
+    while let Some((line_index, line)) = profiler.time(Stage::Read, || lines.next()) {
+        let line = line.context("Failed to read line")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Parsed once; the key and the rows are both read from this value
+        let parsed = profiler.time(Stage::Parse, || {
+            serde_json::from_str::<serde_json::Value>(&line).context("Failed to parse JSON")
+        });
+        let json = match parsed {
+            Ok(json) => json,
+            Err(e) => {
+                if !fail_line(e, line_index, schema, options, sink)? {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+
+        let key = if track_keys || key_filter.is_some() || skip_keys.is_some() {
+            record_key(&json)
+        } else {
+            None
+        };
+        if key_filter.is_some_and(|ids| !key.is_some_and(|k| ids.contains(&k)))
+            || skip_keys.is_some_and(|ids| key.is_some_and(|k| ids.contains(&k)))
+        {
+            continue;
+        }
+        if !sink(SourceItem::Line { key })? {
+            return Ok(());
+        }
+
+        if is_junction {
+            // Junction table: one JSON line produces multiple rows, streamed to the
+            // sink as they are built to bound memory per line
+            let mut open = true;
+            let mut emit = |row: ParsedRow| -> Result<()> {
+                if open {
+                    open = sink(SourceItem::Row(row))?;
+                }
+                Ok(())
+            };
+            let parsed = profiler.time(Stage::Parse, || {
+                for_each_junction_record_json(&json, schema, &mut emit)
+            });
+            match parsed {
+                Ok(()) if open => {}
+                Ok(()) => return Ok(()),
+                Err(e) if !options.strict && e.downcast_ref::<UnexpectedShape>().is_some() => {
+                    if !sink(SourceItem::Skipped {
+                        line_index,
+                        error: e,
+                    })? {
+                        return Ok(());
+                    }
+                }
+                Err(e) if options.continue_on_error => {
+                    if !sink(SourceItem::Failed {
+                        line_index,
+                        error: e,
+                    })? {
+                        return Ok(());
+                    }
+                }
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "Failed to import junction record in {} (line {})",
+                        schema.source_file,
+                        line_index + 1
+                    )))
+                }
+            }
+        } else {
+            // Regular table: one JSON line = one row
+            let parsed = profiler.time(Stage::Parse, || {
+                parse_record_json(&json, &line, schema, &parse_options)
+            });
+            let open = match parsed {
+                Ok(_) if skip_unpublished && is_unpublished(&json) => true,
+                Ok(row) => sink(SourceItem::Row(row))?,
+                Err(e) => fail_line(e, line_index, schema, options, sink)?,
+            };
+            if !open {
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle a line that failed to parse: with `continue_on_error` it is passed to `sink`
+/// and skipped (returning `sink`'s verdict), otherwise the import fails
+fn fail_line(
+    error: anyhow::Error,
+    line_index: usize,
+    schema: &TableSchema,
+    options: &ConvertOptions,
+    sink: &mut ItemSink,
+) -> Result<bool> {
+    if options.continue_on_error {
+        sink(SourceItem::Failed { line_index, error })
+    } else {
+        Err(error.context(format!(
+            "Failed to parse record in {} (line {})",
+            schema.source_file,
+            line_index + 1
+        )))
+    }
+}
+
+/// A source being parsed on a worker thread
+pub struct SourceParser<'scope> {
+    items: Receiver<Vec<SourceItem>>,
+    handle: ScopedJoinHandle<'scope, Result<Option<TableProfile>>>,
+    progress: ReadProgress,
+}
+
+impl<'scope> SourceParser<'scope> {
+    /// Start parsing the table's source file from `source` on a thread of `scope`,
+    /// dropping lines whose record key is in `skip_keys`
+    pub fn spawn<'env>(
+        scope: &'scope Scope<'scope, 'env>,
+        source: &dyn SourceProvider,
+        schema: &'env TableSchema,
+        options: &'env ConvertOptions,
+        skip_keys: Option<Arc<HashSet<i64>>>,
+    ) -> Result<Self> {
+        let (reader, progress) = source.open(schema.source_file)?;
+        let (tx, items) = sync_channel(CHANNEL_CHUNKS);
+
+        let handle = scope.spawn(move || {
+            let profiler = Profiler::new(options.profile);
+            let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+            let mut open = true;
+
+            parse_source(
+                reader,
+                schema,
+                options,
+                skip_keys.as_deref(),
+                &profiler,
+                &mut |item| {
+                    chunk.push(item);
+                    if chunk.len() >= CHUNK_SIZE {
+                        // The writer hangs up once it needs no more rows
+                        open = tx.send(std::mem::take(&mut chunk)).is_ok();
+                    }
+                    Ok(open)
+                },
+            )?;
+
+            if open && !chunk.is_empty() {
+                tx.send(chunk).ok();
+            }
+            Ok(profiler.finish())
+        });
+
+        Ok(Self {
+            items,
+            handle,
+            progress,
+        })
+    }
+
+    /// How far the parser has read into the source file. It runs ahead of the rows
+    /// drained so far by at most a few chunks.
+    pub fn progress(&self) -> ReadProgress {
+        self.progress.clone()
+    }
+
+    /// Feed parsed items to `sink` until the source is exhausted or `sink` returns `false`.
+    /// Returns the parser's own stage timings, if profiling.
+    pub fn drain(self, sink: &mut ItemSink) -> Result<Option<TableProfile>> {
+        'chunks: for chunk in &self.items {
+            for item in chunk {
+                if !sink(item)? {
+                    break 'chunks;
+                }
+            }
+        }
+
+        // Hang up first so a parser blocked on a full channel can finish
+        drop(self.items);
+        self.handle
+            .join()
+            .map_err(|_| anyhow!("Parser thread panicked"))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_read_progress_reaches_file_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = "{\"_key\": 1}\n{\"_key\": 2}\n".repeat(100);
+        std::fs::write(dir.path().join("plain.jsonl"), &content).unwrap();
+        let mut encoder = GzEncoder::new(
+            File::create(dir.path().join("packed.jsonl.gz")).unwrap(),
+            Compression::default(),
+        );
+        encoder.write_all(content.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let packed_size = std::fs::metadata(dir.path().join("packed.jsonl.gz"))
+            .unwrap()
+            .len();
+
+        for (file, size) in [
+            ("plain.jsonl", content.len() as u64),
+            ("packed.jsonl", packed_size),
+        ] {
+            let (reader, progress) = open_source_with_progress(dir.path(), file).unwrap();
+            assert_eq!(progress.total(), size, "{}", file);
+            assert_eq!(reader.lines().count(), 200, "{}", file);
+            assert_eq!(progress.read(), size, "{}", file);
+        }
+    }
+}
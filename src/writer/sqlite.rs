@@ -1,38 +1,149 @@
 use anyhow::{Context, Result};
-use rusqlite::Connection;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use chrono::{SecondsFormat, Utc};
+use rusqlite::{Connection, OptionalExtension, Transaction};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use super::schema_gen::{generate_create_table, generate_indexes};
-use crate::parser::{parse_junction_records, parse_record, ParsedRow};
-use crate::schema::{ColumnType, TableSchema, LANGUAGES};
-use crate::ui::Ui;
+use super::manifest::{manifest_path, Manifest};
+use super::options::ConvertOptions;
+use super::profile::{slowest_first, Profiler, Stage, TableProfile};
+use super::schema_gen::{
+    column_definitions, generate_create_table_with, generate_fts, generate_indexes_with,
+    generate_views,
+};
+use super::sink::{announce_table, show_import_progress, RecordSink};
+use super::source::{
+    parse_source, ItemSink, ReadProgress, SourceItem, SourceParser, SourceProvider, UnpublishedKeys,
+};
+use super::verify::foreign_key_reports;
+use crate::interrupt::Artifact;
+use crate::parser::{ParsedRow, SqlValue, RAW_JSON_COLUMN, SEARCH_TEXT_COLUMN};
+use crate::schema::TableSchema;
+use crate::ui::{format_size, ProgressEvent, Ui};
 
-const BATCH_SIZE: usize = 1000;
+/// Table holding key/value metadata about the conversion
+pub const META_TABLE: &str = "sde_meta";
+
+/// Maximum number of duplicate keys listed in a warning
+const MAX_REPORTED_KEYS: usize = 20;
+
+/// Derived flag columns on `types` and the id column whose presence they record
+const PRESENCE_FLAGS: &[(&str, &str)] = &[
+    ("has_icon", "icon_id"),
+    ("has_graphic", "graphic_id"),
+    ("has_sound", "sound_id"),
+];
+
+/// Columns `--denormalize` adds to `map_solar_systems`, with the table whose English
+/// name they copy and the solar system column referencing it
+const DENORMALIZED_NAMES: &[(&str, &str, &str)] = &[
+    (
+        "constellation_name_en",
+        "map_constellations",
+        "constellation_id",
+    ),
+    ("region_name_en", "map_regions", "region_id"),
+];
 
 pub struct SqliteWriter {
     conn: Connection,
+    /// Destination the database is moved to by `finalize`
+    output_path: PathBuf,
+    /// Temporary file the database is built in (`None` when updating it in place)
+    temp_path: Option<PathBuf>,
+    options: ConvertOptions,
+    /// Stage timings per imported table (only with `profile` enabled)
+    profiles: Vec<(&'static str, TableProfile)>,
+    /// Rows written per imported table
+    row_counts: Vec<(&'static str, u64)>,
+    /// Source lines skipped because they failed to parse (with `continue_on_error`)
+    failed_lines: u64,
+    /// Unpublished parent keys, shared by the junction tables of each parent
+    unpublished_keys: UnpublishedKeys,
+    /// Files of the temporary database, removed if the run is interrupted
+    _artifacts: Vec<Artifact>,
 }
 
 impl SqliteWriter {
     pub fn new(db_path: &Path) -> Result<Self> {
-        // Remove existing database if present
-        if db_path.exists() {
-            std::fs::remove_file(db_path).context("Failed to remove existing database")?;
+        Self::with_options(db_path, ConvertOptions::default())
+    }
+
+    /// Start building a database for `db_path`. It is written to `<db_path>.tmp` and only
+    /// replaces `db_path` in `finalize`, so a failed run leaves any existing database intact.
+    ///
+    /// With `options.incremental` the database at `db_path` is opened (or created) and
+    /// updated in place instead; each imported table is replaced in its own transaction.
+    pub fn with_options(db_path: &Path, options: ConvertOptions) -> Result<Self> {
+        if db_path == Path::new(":memory:") {
+            anyhow::bail!("Use convert_to_connection to build an in-memory database");
         }
+        let temp_path = (!options.incremental).then(|| temp_path_for(db_path));
+
+        let conn = match &temp_path {
+            Some(temp_path) => {
+                // Remove leftovers of an earlier interrupted run
+                remove_database_files(temp_path)
+                    .context("Failed to remove stale temporary database")?;
+                Connection::open(temp_path).context("Failed to create database")?
+            }
+            None => Connection::open(db_path)
+                .with_context(|| format!("Failed to open database {:?}", db_path))?,
+        };
 
-        let conn = Connection::open(db_path).context("Failed to create database")?;
+        Self::from_connection(conn, db_path.to_path_buf(), temp_path, options)
+    }
+
+    /// Start building a database in memory, retrieved with `into_connection`
+    pub fn in_memory(options: ConvertOptions) -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to create database")?;
+        Self::from_connection(conn, PathBuf::from(":memory:"), None, options)
+    }
+
+    fn from_connection(
+        conn: Connection,
+        output_path: PathBuf,
+        temp_path: Option<PathBuf>,
+        options: ConvertOptions,
+    ) -> Result<Self> {
+        // User pragmas first, so settings like page_size apply before any table exists
+        for (name, value) in &options.pragmas {
+            conn.execute_batch(&format!("PRAGMA {} = {};", name, value))
+                .with_context(|| format!("Failed to set PRAGMA {} = {}", name, value))?;
+        }
 
         // Optimize for bulk insert - defer FK checks until finalize
         conn.execute_batch(
             "PRAGMA foreign_keys = OFF;
              PRAGMA journal_mode = WAL;
-             PRAGMA synchronous = NORMAL;
-             PRAGMA cache_size = -64000;",
+             PRAGMA synchronous = NORMAL;",
         )?;
+        conn.execute_batch(&memory_pragmas(&options))
+            .context("Failed to set cache and mmap sizes")?;
+
+        let artifacts = temp_path
+            .iter()
+            .flat_map(|path| {
+                ["", "-wal", "-shm"].map(|suffix| {
+                    let mut name = path.as_os_str().to_os_string();
+                    name.push(suffix);
+                    Artifact::new(name)
+                })
+            })
+            .collect();
 
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            output_path,
+            temp_path,
+            options,
+            profiles: Vec::new(),
+            row_counts: Vec::new(),
+            failed_lines: 0,
+            unpublished_keys: UnpublishedKeys::default(),
+            _artifacts: artifacts,
+        })
     }
 
     /// Create all tables for the given schemas
@@ -40,12 +151,12 @@ impl SqliteWriter {
         ui.log(format!("Creating {} tables...", schemas.len()));
 
         for (i, schema) in schemas.iter().enumerate() {
-            let sql = generate_create_table(schema);
+            let sql = generate_create_table_with(schema, &self.options);
             self.conn
                 .execute(&sql, [])
                 .with_context(|| format!("Failed to create table: {}", schema.name))?;
 
-            for index_sql in generate_indexes(schema) {
+            for index_sql in generate_indexes_with(schema, &self.options) {
                 self.conn
                     .execute(&index_sql, [])
                     .with_context(|| format!("Failed to create index for: {}", schema.name))?;
@@ -61,127 +172,527 @@ impl SqliteWriter {
     pub fn import_table(
         &mut self,
         schema: &TableSchema,
-        input_dir: &Path,
-        line_count: u64,
+        source: &dyn SourceProvider,
         ui: &mut impl Ui,
     ) -> Result<u64> {
-        let file_path = input_dir.join(schema.source_file);
-
-        if !file_path.exists() {
-            ui.log(format!("{}: skipped (file not found)", schema.name));
+        if !source.has_source(schema.source_file) {
+            ui.warn(format!("{}: skipped (file not found)", schema.name));
             return Ok(0);
         }
 
-        let file =
-            File::open(&file_path).with_context(|| format!("Failed to open: {:?}", file_path))?;
-        let reader = BufReader::new(file);
+        let skip_keys = self
+            .unpublished_keys
+            .for_table(source, schema, &self.options, ui)?;
+        let (reader, progress) = source.open(schema.source_file)?;
+        let options = self.options.clone();
+        self.import_items(schema, progress, ui, |profiler, sink| {
+            parse_source(
+                reader,
+                schema,
+                &options,
+                skip_keys.as_deref(),
+                profiler,
+                sink,
+            )?;
+            Ok(None)
+        })
+    }
+
+    /// Import a table whose source is being parsed on another thread
+    /// (`None` if its source file does not exist)
+    fn import_parsed(
+        &mut self,
+        schema: &TableSchema,
+        parser: Option<SourceParser>,
+        ui: &mut impl Ui,
+    ) -> Result<u64> {
+        let Some(parser) = parser else {
+            ui.warn(format!("{}: skipped (file not found)", schema.name));
+            return Ok(0);
+        };
+
+        let progress = parser.progress();
+        self.import_items(schema, progress, ui, |_, sink| parser.drain(sink))
+    }
 
+    /// Write the items produced by `feed` into a table within one transaction.
+    /// `feed` returns any stage timings it collected on another thread.
+    #[tracing::instrument(skip_all, fields(table = schema.name, bytes = progress.total()))]
+    fn import_items(
+        &mut self,
+        schema: &TableSchema,
+        progress: ReadProgress,
+        ui: &mut impl Ui,
+        feed: impl FnOnce(&Profiler, &mut ItemSink) -> Result<Option<TableProfile>>,
+    ) -> Result<u64> {
         // Build insert statement
-        let columns = get_column_names(schema);
+        let columns = get_column_names(schema, &self.options);
+        let sql_columns: Vec<String> = columns.iter().map(|c| self.sql_column_name(c)).collect();
         let placeholders: Vec<&str> = columns.iter().map(|_| "?").collect();
+
+        // Rows to delete when a record key repeats and only the last line is kept
+        let dedupe_sql = self
+            .options
+            .dedupe_keys
+            .then(|| match &schema.array_source {
+                Some(source) => Some(source.parent_id_column()),
+                None => schema
+                    .columns
+                    .iter()
+                    .any(|c| c.name == "id")
+                    .then_some("id"),
+            })
+            .flatten()
+            .map(|col| {
+                format!(
+                    "DELETE FROM {} WHERE {} = ?",
+                    schema.name,
+                    self.sql_column_name(col)
+                )
+            });
+
+        // Repeated junction tuples (with `dedup_junctions`) and repeated record keys whose
+        // earlier rows aren't deleted are skipped, keeping the first row. Only primary key
+        // conflicts are; NOT NULL and CHECK failures still abort.
+        let primary_key = schema.primary_key_columns();
+        let skip_conflicts = ((self.options.dedup_junctions && schema.array_source.is_some())
+            || (self.options.tracks_duplicate_keys() && dedupe_sql.is_none()))
+            && !primary_key.is_empty();
+        let conflict = if skip_conflicts {
+            let key_columns: Vec<String> = primary_key
+                .iter()
+                .map(|c| self.sql_column_name(c))
+                .collect();
+            format!(" ON CONFLICT({}) DO NOTHING", key_columns.join(", "))
+        } else {
+            String::new()
+        };
         let insert_sql = format!(
-            "INSERT INTO {} ({}) VALUES ({})",
+            "INSERT INTO {} ({}) VALUES ({}){}",
             schema.name,
-            columns.join(", "),
-            placeholders.join(", ")
+            sql_columns.join(", "),
+            placeholders.join(", "),
+            conflict
         );
+        tracing::trace!(sql = %insert_sql, "prepared insert");
 
         let tx = self.conn.transaction()?;
-        let mut count: u64 = 0;
-        let mut batch: Vec<ParsedRow> = Vec::with_capacity(BATCH_SIZE);
+        if self.options.incremental {
+            // Rows of the previous build go in the same transaction as the new ones
+            tx.execute(&format!("DELETE FROM {}", schema.name), [])
+                .with_context(|| format!("Failed to clear table: {}", schema.name))?;
+        }
+
+        let started = Instant::now();
+        let profiler = Profiler::new(self.options.profile);
+        let mut import = TableImport {
+            schema,
+            tx,
+            insert_sql,
+            columns,
+            dedupe_sql,
+            skip_conflicts,
+            seen_keys: self.options.tracks_duplicate_keys().then(HashSet::new),
+            duplicate_keys: Vec::new(),
+            unknown_fields: self.options.report_unknown_fields.then(BTreeMap::new),
+            failed_lines: 0,
+            batch: Vec::with_capacity(self.options.batch_size()),
+            batch_size: self.options.batch_size(),
+            count: 0,
+            ignored_rows: 0,
+            progress,
+            row_limit: self.options.row_limit.unwrap_or(u64::MAX),
+            limited: false,
+        };
+
+        let parser_profile = feed(&profiler, &mut |item| import.accept(item, &profiler, ui))?;
+        self.failed_lines += import.failed_lines;
+        let count = import.finish(&profiler, ui)?;
+        tracing::debug!(rows = count, "table imported");
+
+        if let Some(profile) = profiler.finish() {
+            let mut profile = parser_profile.map_or(profile, |p| p + profile);
+            profile.elapsed = started.elapsed();
+            profile.rows = count;
+            self.profiles.push((schema.name, profile));
+        }
+        self.row_counts.push((schema.name, count));
+
+        Ok(count)
+    }
 
-        let is_junction = schema.array_source.is_some();
+    /// Source lines skipped so far because they failed to parse
+    pub fn failed_lines(&self) -> u64 {
+        self.failed_lines
+    }
 
-        for line in reader.lines() {
-            let line = line.context("Failed to read line")?;
-            if line.trim().is_empty() {
+    /// Log each foreign key with rows referencing a missing parent row, returning how
+    /// many such rows there are. Foreign keys are not enforced during the import, so
+    /// filtering out a parent table goes unnoticed otherwise.
+    pub fn check_foreign_keys(&self, ui: &mut impl Ui) -> Result<u64> {
+        let mut violations: u64 = 0;
+        for report in foreign_key_reports(&self.conn)? {
+            if report.orphans == 0 {
                 continue;
             }
+            ui.log(format!(
+                "{}.{}: {} row(s) reference a missing {} row",
+                report.table, report.column, report.orphans, report.references_table
+            ));
+            violations += report.orphans;
+        }
 
-            if is_junction {
-                // Junction table: one JSON line produces multiple rows
-                let rows = parse_junction_records(&line, schema).with_context(|| {
-                    format!("Failed to parse junction record in {}", schema.source_file)
-                })?;
+        if violations > 0 {
+            ui.warn(format!("{} foreign key violation(s)", violations));
+        }
+        Ok(violations)
+    }
 
-                for row in rows {
-                    batch.push(row);
+    /// Rows written for each imported table, in import order
+    pub fn row_counts(&self) -> &[(&'static str, u64)] {
+        &self.row_counts
+    }
 
-                    if batch.len() >= BATCH_SIZE {
-                        insert_batch(&tx, &insert_sql, &columns, &batch)?;
-                        count += batch.len() as u64;
-                        ui.set_progress(count, line_count, schema.name);
-                        batch.clear();
-                    }
-                }
-            } else {
-                // Regular table: one JSON line = one row
-                let row = parse_record(&line, schema)
-                    .with_context(|| format!("Failed to parse record in {}", schema.source_file))?;
+    /// Stage timings for each imported table, empty unless profiling is enabled
+    pub fn profiles(&self) -> &[(&'static str, TableProfile)] {
+        &self.profiles
+    }
 
-                batch.push(row);
+    /// Add indexed boolean columns to `types` recording whether its icon, graphic
+    /// and sound ids are present (indexes on the flags serve `IS NOT NULL` filters)
+    pub fn derive_presence_flags(&self, ui: &mut impl Ui) -> Result<()> {
+        ui.log("Deriving presence flags on types...");
 
-                if batch.len() >= BATCH_SIZE {
-                    insert_batch(&tx, &insert_sql, &columns, &batch)?;
-                    count += batch.len() as u64;
-                    ui.set_progress(count, line_count, schema.name);
-                    batch.clear();
-                }
+        for (flag, id_column) in PRESENCE_FLAGS {
+            let flag_column = self.sql_column_name(flag);
+            self.conn
+                .execute_batch(&format!(
+                    "ALTER TABLE types ADD COLUMN {flag} INTEGER NOT NULL DEFAULT 0;
+                     UPDATE types SET {flag} = {id} IS NOT NULL;
+                     CREATE INDEX idx_types_{name} ON types({flag});",
+                    flag = flag_column,
+                    id = self.sql_column_name(id_column),
+                    name = flag,
+                ))
+                .with_context(|| format!("Failed to derive types.{}", flag_column))?;
+        }
+
+        Ok(())
+    }
+
+    /// Add `constellation_name_en` and `region_name_en` to `map_solar_systems`, copied
+    /// from whichever of `map_constellations` and `map_regions` were imported
+    pub fn denormalize_map_names(&self, schemas: &[&TableSchema], ui: &mut impl Ui) -> Result<()> {
+        ui.log("Denormalizing region and constellation names onto map_solar_systems...");
+
+        for (name_column, parent, id_column) in DENORMALIZED_NAMES {
+            if !schemas.iter().any(|t| t.name == *parent) {
+                continue;
             }
+
+            let column = self.sql_column_name(name_column);
+            self.conn
+                .execute_batch(&format!(
+                    "ALTER TABLE map_solar_systems ADD COLUMN {column} TEXT;
+                     UPDATE map_solar_systems SET {column} = p.{name}
+                     FROM {parent} AS p WHERE p.id = map_solar_systems.{id};",
+                    column = column,
+                    name = self.sql_column_name("name_en"),
+                    parent = parent,
+                    id = self.sql_column_name(id_column),
+                ))
+                .with_context(|| format!("Failed to derive map_solar_systems.{}", column))?;
         }
 
-        // Insert remaining batch
-        if !batch.is_empty() {
-            insert_batch(&tx, &insert_sql, &columns, &batch)?;
-            count += batch.len() as u64;
+        Ok(())
+    }
+
+    /// Create `map_jumps`, one row per pair of solar systems joined by a stargate in
+    /// that direction (gates come in pairs, so every jump appears both ways)
+    pub fn build_jumps(&self, schemas: &[&TableSchema], ui: &mut impl Ui) -> Result<()> {
+        ui.log("Building map_jumps from map_stargates...");
+
+        let from = self.sql_column_name("from_system_id");
+        let to = self.sql_column_name("to_system_id");
+        let foreign_keys = if schemas.iter().any(|t| t.name == "map_solar_systems") {
+            format!(
+                ",
+                     FOREIGN KEY ({from}) REFERENCES map_solar_systems({id}),
+                     FOREIGN KEY ({to}) REFERENCES map_solar_systems({id})",
+                from = from,
+                to = to,
+                id = self.sql_column_name("id"),
+            )
+        } else {
+            String::new()
+        };
+
+        self.conn
+            .execute_batch(&format!(
+                "CREATE TABLE map_jumps (
+                     {from} INTEGER NOT NULL,
+                     {to} INTEGER NOT NULL,
+                     PRIMARY KEY ({from}, {to}){foreign_keys}
+                 ) WITHOUT ROWID;
+                 INSERT INTO map_jumps ({from}, {to})
+                 SELECT DISTINCT {system}, {destination} FROM map_stargates
+                 WHERE {system} IS NOT NULL AND {destination} IS NOT NULL;
+                 CREATE INDEX idx_map_jumps_to_system_id ON map_jumps({to});",
+                from = from,
+                to = to,
+                foreign_keys = foreign_keys,
+                system = self.sql_column_name("solar_system_id"),
+                destination = self.sql_column_name("destination_solar_system_id"),
+            ))
+            .context("Failed to build map_jumps")?;
+
+        let jumps: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM map_jumps", [], |row| row.get(0))?;
+        ui.log(format!("map_jumps: {} jumps", jumps));
+        Ok(())
+    }
+
+    /// Create convenience views for the imported tables
+    pub fn create_views(&self, schemas: &[&TableSchema], ui: &mut impl Ui) -> Result<()> {
+        let views = generate_views(schemas);
+        ui.log(format!("Creating {} views...", views.len()));
+
+        for sql in views {
+            self.conn
+                .execute(&sql, [])
+                .with_context(|| format!("Failed to create view: {}", sql))?;
         }
 
-        tx.commit()?;
-        ui.log(format!("{}: {} records", schema.name, count));
+        Ok(())
+    }
 
-        Ok(count)
+    /// Create and fill `<table>_fts` name search indexes for the imported tables with a
+    /// localized `name`
+    pub fn create_fts(&self, schemas: &[&TableSchema], ui: &mut impl Ui) -> Result<()> {
+        let indexed: Vec<_> = schemas
+            .iter()
+            .map(|schema| (schema.name, generate_fts(schema, &self.options)))
+            .filter(|(_, statements)| !statements.is_empty())
+            .collect();
+        ui.log(format!("Creating {} FTS5 tables...", indexed.len()));
+
+        for (name, statements) in indexed {
+            for sql in statements {
+                self.conn
+                    .execute(&sql, [])
+                    .with_context(|| format!("Failed to create {}_fts: {}", name, sql))?;
+            }
+        }
+
+        Ok(())
     }
 
-    /// Finalize the database (enable FKs, optimize, etc.)
-    pub fn finalize(self, ui: &mut impl Ui) -> Result<()> {
+    /// Record conversion metadata: generation time in UTC, the crate version and any
+    /// extra entries. Without a `build_number` entry (e.g. `convert` of local files)
+    /// `build_number` is NULL and `source` is `local`.
+    pub fn write_meta(&self, extra: &[(String, String)]) -> Result<()> {
+        self.conn.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (key TEXT PRIMARY KEY, value TEXT)",
+                META_TABLE
+            ),
+            [],
+        )?;
+
+        let generated_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut entries: Vec<(&str, Option<&str>)> = vec![
+            ("generated_at", Some(generated_at.as_str())),
+            ("crate_version", Some(env!("CARGO_PKG_VERSION"))),
+        ];
+        if !extra.iter().any(|(k, _)| k == "build_number") {
+            entries.push(("build_number", None));
+            entries.push(("source", Some("local")));
+        }
+        entries.extend(extra.iter().map(|(k, v)| (k.as_str(), Some(v.as_str()))));
+
+        let mut stmt = self.conn.prepare(&format!(
+            "INSERT OR REPLACE INTO {} (key, value) VALUES (?, ?)",
+            META_TABLE
+        ))?;
+        for (key, value) in entries {
+            stmt.execute(rusqlite::params![key, value])?;
+        }
+
+        Ok(())
+    }
+
+    /// SQL column name for a parsed row key
+    fn sql_column_name(&self, key: &str) -> String {
+        if key == RAW_JSON_COLUMN {
+            key.to_string()
+        } else {
+            self.options.naming.column_name(key)
+        }
+    }
+
+    /// Enable foreign keys and gather query planner statistics once all tables are imported
+    fn prepare_for_queries(&self, ui: &mut impl Ui) -> Result<()> {
         ui.log("Finalizing database...");
 
         // Enable foreign keys for future use
         self.conn.execute("PRAGMA foreign_keys = ON;", [])?;
         self.conn.execute("PRAGMA optimize;", [])?;
 
+        // Populate sqlite_stat1 so the first queries against the new indexes plan well
+        if !self.options.skip_analyze {
+            self.conn.execute("ANALYZE;", [])?;
+        }
+
+        Ok(())
+    }
+
+    /// Finish the database and hand over its connection (for `in_memory` databases)
+    pub fn into_connection(self, ui: &mut impl Ui) -> Result<Connection> {
+        self.prepare_for_queries(ui)?;
+        Ok(self.conn)
+    }
+
+    /// Finalize the database (enable FKs, optimize, etc.)
+    pub fn finalize(self, ui: &mut impl Ui) -> Result<()> {
+        self.prepare_for_queries(ui)?;
+        if self.options.vacuum {
+            self.vacuum(ui)?;
+        }
+
+        // Closing checkpoints the WAL into the main file so it can be moved on its own
+        self.conn
+            .close()
+            .map_err(|(_, e)| e)
+            .context("Failed to close database")?;
+
+        let Some(temp_path) = &self.temp_path else {
+            return Ok(());
+        };
+
+        // Stale WAL/SHM files of the old database must not be applied to the new one
+        remove_database_files(&self.output_path).context("Failed to remove existing database")?;
+        std::fs::rename(temp_path, &self.output_path)
+            .with_context(|| format!("Failed to move {:?} to {:?}", temp_path, self.output_path))?;
+
+        Ok(())
+    }
+
+    /// Rebuild the database file without free pages, logging the size before and after
+    fn vacuum(&self, ui: &mut impl Ui) -> Result<()> {
+        // Every table import commits its own transaction, so none should be open here
+        if !self.conn.is_autocommit() {
+            anyhow::bail!("Cannot VACUUM while a transaction is open");
+        }
+
+        // Pages can live in both the WAL and the main file; fold the WAL back in and
+        // truncate it before each measurement so nothing is counted twice
+        let checkpoint = || {
+            self.conn
+                .query_row("PRAGMA wal_checkpoint(TRUNCATE);", [], |_| Ok(()))
+        };
+        let path = self.temp_path.as_deref().unwrap_or(&self.output_path);
+        checkpoint()?;
+        let before = database_size(path);
+        ui.log("Vacuuming database...");
+        self.conn
+            .execute_batch("VACUUM;")
+            .context("VACUUM failed")?;
+        checkpoint()?;
+        let after = database_size(path);
+
+        ui.log(format!(
+            "Vacuumed database: {} -> {}",
+            format_size(before),
+            format_size(after)
+        ));
         Ok(())
     }
+
+    /// Temporary file the database is built in until `finalize`
+    /// (`None` when the database is updated in place)
+    pub fn temp_path(&self) -> Option<&Path> {
+        self.temp_path.as_deref()
+    }
 }
 
-/// Get column names for a schema, expanding localized columns
-fn get_column_names(schema: &TableSchema) -> Vec<String> {
-    let mut columns = Vec::new();
+/// `cache_size`/`mmap_size` pragmas for the options; zero or negative sizes keep
+/// SQLite's defaults
+fn memory_pragmas(options: &ConvertOptions) -> String {
+    let mut sql = String::new();
+    match options.cache_size_mb {
+        None => sql.push_str("PRAGMA cache_size = -64000;"),
+        // A negative cache_size is in KiB
+        Some(mb) if mb > 0 => sql.push_str(&format!("PRAGMA cache_size = -{};", mb * 1024)),
+        Some(_) => {}
+    }
+    if let Some(mb) = options.mmap_size_mb.filter(|&mb| mb > 0) {
+        sql.push_str(&format!("PRAGMA mmap_size = {};", mb * 1024 * 1024));
+    }
+    sql
+}
 
-    for col in schema.columns {
-        match col.col_type {
-            ColumnType::Localized => {
-                for lang in LANGUAGES {
-                    columns.push(format!("{}_{}", col.name, lang));
-                }
-            }
-            _ => {
-                columns.push(col.name.to_string());
-            }
+/// Path the database for `db_path` is built at (`<db_path>.tmp`)
+fn temp_path_for(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_os_string();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+/// Size of a database file plus its WAL, in bytes (missing files count as empty)
+fn database_size(db_path: &Path) -> u64 {
+    ["", "-wal"]
+        .into_iter()
+        .filter_map(|suffix| {
+            let mut name = db_path.as_os_str().to_os_string();
+            name.push(suffix);
+            std::fs::metadata(name).ok()
+        })
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Remove a database file along with its WAL and shared-memory files, if present
+fn remove_database_files(db_path: &Path) -> std::io::Result<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut name = db_path.as_os_str().to_os_string();
+        name.push(suffix);
+        match std::fs::remove_file(&name) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
         }
     }
+    Ok(())
+}
+
+/// Get parsed row keys for a schema, expanding localized columns
+fn get_column_names(schema: &TableSchema, options: &ConvertOptions) -> Vec<String> {
+    let mut columns: Vec<String> = column_definitions(schema, &options.languages())
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+
+    if options.stores_raw_json(schema) {
+        columns.push(RAW_JSON_COLUMN.to_string());
+    }
+
+    if options.stores_search_text(schema) {
+        columns.push(SEARCH_TEXT_COLUMN.to_string());
+    }
 
     columns
 }
 
-/// Insert a batch of rows into the database
+/// Insert a batch of rows into the database, returning how many were inserted
+/// (fewer than the batch when `ON CONFLICT DO NOTHING` skips duplicates)
 fn insert_batch(
     tx: &rusqlite::Transaction,
     sql: &str,
     columns: &[String],
     batch: &[ParsedRow],
-) -> Result<()> {
+) -> Result<u64> {
     let mut stmt = tx.prepare_cached(sql)?;
+    let mut inserted: u64 = 0;
 
     for row in batch {
         for (idx, col_name) in columns.iter().enumerate() {
@@ -192,47 +703,2051 @@ fn insert_batch(
                 .unwrap_or(crate::parser::SqlValue::Null);
             value.bind_to(idx + 1, &mut stmt)?;
         }
-        stmt.raw_execute()?;
+        inserted += stmt.raw_execute()? as u64;
     }
 
-    Ok(())
+    Ok(inserted)
+}
+
+impl RecordSink for SqliteWriter {
+    fn create_tables(&mut self, tables: &[&TableSchema], ui: &mut impl Ui) -> Result<()> {
+        SqliteWriter::create_tables(self, tables, ui)
+    }
+
+    fn import_table(
+        &mut self,
+        schema: &TableSchema,
+        source: &dyn SourceProvider,
+        ui: &mut impl Ui,
+    ) -> Result<u64> {
+        SqliteWriter::import_table(self, schema, source, ui)
+    }
+
+    fn finalize(self, ui: &mut impl Ui) -> Result<()> {
+        SqliteWriter::finalize(self, ui)
+    }
+}
+
+/// Rows of one table being written inside a transaction
+struct TableImport<'a> {
+    schema: &'a TableSchema,
+    tx: Transaction<'a>,
+    insert_sql: String,
+    columns: Vec<String>,
+    /// Deletes earlier rows of a repeated record key (with `dedupe_keys`)
+    dedupe_sql: Option<String>,
+    /// Whether inserts skip rows that repeat a primary key
+    skip_conflicts: bool,
+    seen_keys: Option<HashSet<i64>>,
+    duplicate_keys: Vec<i64>,
+    /// Unmapped JSON fields with the first record key they appeared in
+    /// (with `report_unknown_fields`)
+    unknown_fields: Option<BTreeMap<String, String>>,
+    /// Lines skipped because they failed to parse
+    failed_lines: u64,
+    batch: Vec<ParsedRow>,
+    /// Rows inserted per statement batch
+    batch_size: usize,
+    count: u64,
+    /// Rows `ON CONFLICT DO NOTHING` dropped as duplicates (with `dedup_junctions`)
+    ignored_rows: u64,
+    /// How much of the source file has been read, for the progress bar
+    progress: ReadProgress,
+    row_limit: u64,
+    /// Whether rows were dropped because of `row_limit`
+    limited: bool,
+}
+
+impl TableImport<'_> {
+    /// Handle one parsed item; returns `false` once no more rows are wanted
+    fn accept(&mut self, item: SourceItem, profiler: &Profiler, ui: &mut impl Ui) -> Result<bool> {
+        let pending = self.count + self.batch.len() as u64;
+
+        match item {
+            SourceItem::Line { .. } | SourceItem::Row(_) if pending >= self.row_limit => {
+                self.limited = true;
+                return Ok(false);
+            }
+            SourceItem::Line { key } => {
+                let Some(seen) = self.seen_keys.as_mut() else {
+                    return Ok(true);
+                };
+
+                if let Some(key) = key.filter(|k| !seen.insert(*k)) {
+                    self.duplicate_keys.push(key);
+
+                    if self.dedupe_sql.is_some() {
+                        // Flush pending rows so the earlier occurrence can be removed
+                        self.flush(profiler)?;
+                        self.delete_key(key, profiler)?;
+                    }
+                }
+            }
+            SourceItem::Row(mut row) => {
+                if let Some(unknown) = self.unknown_fields.as_mut() {
+                    for field in row.unknown_fields.drain(..) {
+                        unknown
+                            .entry(field)
+                            .or_insert_with(|| match row.values.get("id") {
+                                Some(SqlValue::Integer(id)) => id.to_string(),
+                                Some(SqlValue::Text(id)) => id.clone(),
+                                _ => "?".to_string(),
+                            });
+                    }
+                }
+                self.batch.push(row);
+
+                if self.batch.len() >= self.batch_size {
+                    self.flush(profiler)?;
+                    show_import_progress(ui, self.schema, self.count, &self.progress);
+                    ui.on_event(ProgressEvent::RowsInserted {
+                        name: self.schema.name,
+                        count: self.count,
+                    });
+                }
+            }
+            SourceItem::Skipped { line_index, error } => {
+                ui.warn(format!(
+                    "{}: skipped line {} of {}: {}",
+                    self.schema.name,
+                    line_index + 1,
+                    self.schema.source_file,
+                    error
+                ));
+            }
+            SourceItem::Failed { line_index, error } => {
+                self.failed_lines += 1;
+                ui.warn(format!(
+                    "{}: failed to parse line {} of {}: {:#}",
+                    self.schema.name,
+                    line_index + 1,
+                    self.schema.source_file,
+                    error
+                ));
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Insert the pending batch
+    fn flush(&mut self, profiler: &Profiler) -> Result<()> {
+        let inserted = profiler.time(Stage::Insert, || {
+            insert_batch(&self.tx, &self.insert_sql, &self.columns, &self.batch)
+        })?;
+        self.count += inserted;
+        self.ignored_rows += self.batch.len() as u64 - inserted;
+        self.batch.clear();
+        Ok(())
+    }
+
+    /// Delete the already inserted rows of a repeated record key
+    fn delete_key(&mut self, key: i64, profiler: &Profiler) -> Result<()> {
+        if let Some(sql) = &self.dedupe_sql {
+            let deleted = profiler.time(Stage::Insert, || self.tx.execute(sql, [key]))?;
+            self.count -= deleted as u64;
+        }
+        Ok(())
+    }
+
+    /// Insert the remaining rows, commit and report; returns the row count
+    fn finish(mut self, profiler: &Profiler, ui: &mut impl Ui) -> Result<u64> {
+        let schema = self.schema;
+
+        // Insert remaining batch
+        if !self.batch.is_empty() {
+            self.flush(profiler)?;
+            ui.on_event(ProgressEvent::RowsInserted {
+                name: schema.name,
+                count: self.count,
+            });
+        }
+
+        let count = self.count;
+        profiler.time(Stage::Insert, || self.tx.commit())?;
+        ui.on_event(ProgressEvent::TableFinished {
+            name: schema.name,
+            rows: count,
+        });
+        if self.limited {
+            ui.log(format!("{}: {} records (limited)", schema.name, count));
+        } else {
+            ui.log(format!("{}: {} records", schema.name, count));
+        }
+        if self.ignored_rows > 0 {
+            ui.log(format!(
+                "{}: ignored {} duplicate row(s)",
+                schema.name, self.ignored_rows
+            ));
+        }
+
+        let duplicate_keys = self.duplicate_keys;
+        if !duplicate_keys.is_empty() {
+            let mut listed: Vec<String> = duplicate_keys
+                .iter()
+                .take(MAX_REPORTED_KEYS)
+                .map(|k| k.to_string())
+                .collect();
+            if duplicate_keys.len() > MAX_REPORTED_KEYS {
+                listed.push(format!(
+                    "... ({} more)",
+                    duplicate_keys.len() - MAX_REPORTED_KEYS
+                ));
+            }
+            let action = if self.dedupe_sql.is_some() {
+                "kept last occurrence"
+            } else if self.skip_conflicts && schema.array_source.is_none() {
+                "kept first occurrence"
+            } else if self.skip_conflicts {
+                "skipped rows repeating a primary key"
+            } else {
+                "imported all occurrences"
+            };
+            ui.warn(format!(
+                "{}: {} duplicate key(s) in {} ({}): {}",
+                schema.name,
+                duplicate_keys.len(),
+                schema.source_file,
+                action,
+                listed.join(", ")
+            ));
+        }
+
+        if let Some(unknown) = self.unknown_fields.filter(|u| !u.is_empty()) {
+            let listed: Vec<String> = unknown
+                .iter()
+                .map(|(field, key)| format!("{} (e.g. _key={})", field, key))
+                .collect();
+            ui.warn(format!(
+                "{}: {} unmapped field(s) in {}: {}",
+                schema.name,
+                unknown.len(),
+                schema.source_file,
+                listed.join(", ")
+            ));
+        }
+
+        Ok(count)
+    }
+}
+
+/// Read a value from the `sde_meta` table of an existing database.
+/// Returns `None` if the database, the table or the key does not exist, or the value is NULL.
+pub fn read_meta(db_path: &Path, key: &str) -> Result<Option<String>> {
+    if !db_path.exists() {
+        return Ok(None);
+    }
+
+    let conn = Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {:?}", db_path))?;
+    let has_meta: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [META_TABLE],
+        |row| row.get(0),
+    )?;
+    if !has_meta {
+        return Ok(None);
+    }
+
+    Ok(conn
+        .query_row(
+            &format!("SELECT value FROM {} WHERE key = ?1", META_TABLE),
+            [key],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?
+        .flatten())
 }
 
-/// Convert JSONL files to SQLite with UI progress
+/// Convert JSONL files (from a directory or archive) to SQLite with UI progress
 pub fn convert_to_sqlite(
-    input_dir: &Path,
+    source: &dyn SourceProvider,
     output_db: &Path,
     tables: Vec<&TableSchema>,
     ui: &mut impl Ui,
 ) -> Result<u64> {
-    let mut writer = SqliteWriter::new(output_db)?;
+    convert_from_source(source, output_db, tables, &ConvertOptions::default(), ui)
+}
 
-    // Create all tables first
-    writer.create_tables(&tables, ui)?;
+/// Convert JSONL files to SQLite with UI progress and conversion options
+pub fn convert_with_options(
+    input_dir: &Path,
+    output_db: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+) -> Result<u64> {
+    convert_from_source(&input_dir, output_db, tables, options, ui)
+}
 
-    let mut total_records: u64 = 0;
+/// Convert JSONL files from a directory or archive to SQLite with conversion options
+pub fn convert_from_source(
+    source: &dyn SourceProvider,
+    output_db: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+) -> Result<u64> {
+    convert_when_ready(source, output_db, tables, options, ui, |_| {})
+}
 
-    for (i, schema) in tables.iter().enumerate() {
-        ui.log(format!(
-            "Importing table {}/{}: {}",
-            i + 1,
-            tables.len(),
-            schema.name
-        ));
+/// Convert JSONL files into an in-memory SQLite database and return its connection
+pub fn convert_to_connection(
+    input_dir: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+) -> Result<Connection> {
+    options.validate()?;
+    if options.incremental {
+        anyhow::bail!("--incremental needs a database file");
+    }
 
-        // Count lines for progress estimation
-        let file_path = input_dir.join(schema.source_file);
-        let line_count = if file_path.exists() {
-            BufReader::new(File::open(&file_path)?).lines().count() as u64
-        } else {
-            0
-        };
+    let mut writer = SqliteWriter::in_memory(options.clone())?;
+    import_all(&mut writer, &input_dir, &tables, options, ui, |_| {})?;
+    if writer.failed_lines() > 0 {
+        anyhow::bail!(
+            "{} source line(s) failed to parse and were skipped",
+            writer.failed_lines()
+        );
+    }
+    if options.check_fk {
+        let violations = writer.check_foreign_keys(ui)?;
+        if options.strict_fk && violations > 0 {
+            anyhow::bail!("{} foreign key violation(s)", violations);
+        }
+    }
+    writer.into_connection(ui)
+}
 
-        let count = writer.import_table(schema, input_dir, line_count, ui)?;
-        total_records += count;
+/// Convert JSONL files to SQLite, calling `wait_for_source` with each table's source
+/// file name before importing it (used to import files while others are still being extracted)
+pub fn convert_when_ready(
+    source: &dyn SourceProvider,
+    output_db: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+    wait_for_source: impl FnMut(&str),
+) -> Result<u64> {
+    options.validate()?;
+    if options.split_by_table {
+        return convert_split(source, output_db, tables, options, ui, wait_for_source);
     }
+    convert_database(source, output_db, tables, options, ui, wait_for_source)
+}
+
+/// Write each table to its own `<output_dir>/<table>.db` (`split_by_table`)
+fn convert_split(
+    source: &dyn SourceProvider,
+    output_dir: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+    mut wait_for_source: impl FnMut(&str),
+) -> Result<u64> {
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create {:?}", output_dir))?;
 
-    writer.finalize(ui)?;
+    let mut total_records: u64 = 0;
+    for schema in tables {
+        let db_path = output_dir.join(format!("{}.db", schema.name));
+        ui.log(format!("Writing {:?}", db_path));
+        total_records += convert_database(
+            source,
+            &db_path,
+            vec![schema],
+            options,
+            ui,
+            &mut wait_for_source,
+        )?;
+    }
 
     Ok(total_records)
 }
+
+/// Convert the tables into the single database at `output_db`
+fn convert_database(
+    source: &dyn SourceProvider,
+    output_db: &Path,
+    tables: Vec<&TableSchema>,
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+    wait_for_source: impl FnMut(&str),
+) -> Result<u64> {
+    let mut writer = SqliteWriter::with_options(output_db, options.clone())?;
+    let temp_path = writer.temp_path().map(Path::to_path_buf);
+
+    let result = import_all(&mut writer, source, &tables, options, ui, wait_for_source).and_then(
+        |total_records| {
+            let manifest = Manifest::new(options.build_number(), &tables, writer.row_counts());
+            let failed_lines = writer.failed_lines();
+            let fk_violations = if options.check_fk {
+                writer.check_foreign_keys(ui)?
+            } else {
+                0
+            };
+            writer.finalize(ui)?;
+            manifest.write(&manifest_path(output_db))?;
+
+            // The database is kept, but the run must not look successful
+            if failed_lines > 0 {
+                anyhow::bail!(
+                "{} source line(s) failed to parse and were skipped; {:?} was written without them",
+                failed_lines,
+                output_db
+            );
+            }
+            if options.strict_fk && fk_violations > 0 {
+                anyhow::bail!(
+                    "{} foreign key violation(s); {:?} was written with them",
+                    fk_violations,
+                    output_db
+                );
+            }
+            Ok(total_records)
+        },
+    );
+
+    // Leave any existing database untouched and drop the partial one. An incremental
+    // update keeps the tables whose transactions committed before the failure.
+    if let (Err(_), Some(temp_path)) = (&result, &temp_path) {
+        remove_database_files(temp_path).ok();
+    }
+
+    result
+}
+
+/// Import tables in order while up to `options.jobs` of them are parsed on worker
+/// threads ahead of the writer. Rows are still inserted one table at a time, in the
+/// given (dependency) order, by this thread's connection.
+fn import_tables_parallel(
+    writer: &mut SqliteWriter,
+    source: &dyn SourceProvider,
+    tables: &[&TableSchema],
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+    mut wait_for_source: impl FnMut(&str),
+) -> Result<u64> {
+    std::thread::scope(|scope| {
+        let mut parsing = VecDeque::new();
+        let mut next = 0;
+        let mut total_records: u64 = 0;
+
+        for (i, schema) in tables.iter().enumerate() {
+            // Keep the next `jobs` tables parsing
+            while next < tables.len() && next < i + options.jobs {
+                let upcoming = tables[next];
+                wait_for_source(upcoming.source_file);
+
+                let parser = if source.has_source(upcoming.source_file) {
+                    let skip_keys = writer
+                        .unpublished_keys
+                        .for_table(source, upcoming, options, ui)?;
+                    Some(SourceParser::spawn(
+                        scope, source, upcoming, options, skip_keys,
+                    )?)
+                } else {
+                    None
+                };
+                parsing.push_back(parser);
+                next += 1;
+            }
+
+            announce_table(tables, i, ui);
+            let parser = parsing.pop_front().flatten();
+            total_records += writer.import_parsed(schema, parser, ui)?;
+        }
+
+        Ok(total_records)
+    })
+}
+
+/// Create and fill all tables, then add derived columns, views and metadata
+fn import_all(
+    writer: &mut SqliteWriter,
+    source: &dyn SourceProvider,
+    tables: &[&TableSchema],
+    options: &ConvertOptions,
+    ui: &mut impl Ui,
+    mut wait_for_source: impl FnMut(&str),
+) -> Result<u64> {
+    // Create all tables first
+    writer.create_tables(tables, ui)?;
+
+    let total_records = if options.jobs > 1 {
+        import_tables_parallel(writer, source, tables, options, ui, wait_for_source)?
+    } else {
+        let mut total_records: u64 = 0;
+
+        for (i, schema) in tables.iter().enumerate() {
+            announce_table(tables, i, ui);
+            wait_for_source(schema.source_file);
+
+            total_records += writer.import_table(schema, source, ui)?;
+        }
+
+        total_records
+    };
+
+    if options.derive_presence_flags && tables.iter().any(|t| t.name == "types") {
+        writer.derive_presence_flags(ui)?;
+    }
+
+    if options.denormalize && tables.iter().any(|t| t.name == "map_solar_systems") {
+        writer.denormalize_map_names(tables, ui)?;
+    }
+
+    if options.build_jumps && tables.iter().any(|t| t.name == "map_stargates") {
+        writer.build_jumps(tables, ui)?;
+    }
+
+    if options.views {
+        writer.create_views(tables, ui)?;
+    }
+
+    if options.fts {
+        writer.create_fts(tables, ui)?;
+    }
+
+    if options.profile {
+        ui.log("Profile (time per table, slowest first):");
+        for (name, profile) in slowest_first(writer.profiles()) {
+            ui.log(format!("  {}: {}", name, profile));
+        }
+    }
+
+    writer.write_meta(&options.metadata)?;
+
+    Ok(total_records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{
+        CATEGORIES, DOGMA_EFFECTS, GROUPS, ICONS, MAP_CONSTELLATIONS, MAP_REGIONS,
+        MAP_SOLAR_SYSTEMS, MAP_STARGATES, TYPES, TYPE_DOGMA_ATTRIBUTES, TYPE_MATERIALS,
+    };
+    use crate::ui::SilentUi;
+
+    const DUPLICATED_TYPE_DOGMA: &str = r#"{"_key": 1, "dogmaAttributes": [{"attributeID": 10, "value": 1.0}, {"attributeID": 11, "value": 2.0}]}
+{"_key": 2, "dogmaAttributes": [{"attributeID": 10, "value": 3.0}]}
+{"_key": 1, "dogmaAttributes": [{"attributeID": 12, "value": 4.0}]}
+"#;
+
+    /// Convert the fixture, returning the temp dir (which owns the database) and the results
+    fn convert_type_dogma(
+        options: &ConvertOptions,
+    ) -> (tempfile::TempDir, Connection, u64, SilentUi) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("typeDogma.jsonl"), DUPLICATED_TYPE_DOGMA).unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let mut ui = SilentUi::new();
+        let count = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPE_DOGMA_ATTRIBUTES],
+            options,
+            &mut ui,
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        (dir, conn, count, ui)
+    }
+
+    fn attribute_ids(conn: &Connection, type_id: i64) -> Vec<i64> {
+        conn.prepare("SELECT attribute_id FROM type_dogma_attributes WHERE type_id = ? ORDER BY attribute_id")
+            .unwrap()
+            .query_map([type_id], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicate_keys_are_reported() {
+        let options = ConvertOptions {
+            warn_duplicate_keys: true,
+            ..Default::default()
+        };
+        let (_dir, conn, count, ui) = convert_type_dogma(&options);
+
+        assert_eq!(count, 4);
+        assert_eq!(attribute_ids(&conn, 1), vec![10, 11, 12]);
+        assert_eq!(ui.warning_count(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_keys_keep_first_row() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}
+{"_key": 7, "name": {"en": "Module"}}
+{"_key": 6, "name": {"en": "Ship (again)"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        // Without tracking, the repeated primary key fails the import
+        let result = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        );
+        assert!(result.is_err());
+
+        let options = ConvertOptions {
+            warn_duplicate_keys: true,
+            ..Default::default()
+        };
+        let mut ui = SilentUi::new();
+        let count =
+            convert_with_options(dir.path(), &db_path, vec![&CATEGORIES], &options, &mut ui)
+                .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(ui.warning_count(), 1);
+
+        let conn = Connection::open(&db_path).unwrap();
+        let name: String = conn
+            .query_row("SELECT name_en FROM categories WHERE id = 6", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(name, "Ship");
+    }
+
+    #[test]
+    fn test_dedupe_keys_keeps_last_occurrence() {
+        let options = ConvertOptions {
+            dedupe_keys: true,
+            ..Default::default()
+        };
+        let (_dir, conn, count, ui) = convert_type_dogma(&options);
+
+        assert_eq!(count, 2);
+        assert_eq!(attribute_ids(&conn, 1), vec![12]);
+        assert_eq!(attribute_ids(&conn, 2), vec![10]);
+        assert_eq!(ui.warning_count(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_keys_not_tracked_by_default() {
+        let (_dir, _conn, count, ui) = convert_type_dogma(&ConvertOptions::default());
+
+        assert_eq!(count, 4);
+        assert_eq!(ui.warning_count(), 0);
+    }
+
+    #[test]
+    fn test_unexpected_shape_skipped_unless_strict() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("typeDogma.jsonl"),
+            r#"{"_key": 1, "dogmaAttributes": {"attributeID": 10, "value": 1.0}}
+{"_key": 2, "dogmaAttributes": [{"attributeID": 10, "value": 3.0}]}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let mut ui = SilentUi::new();
+        let count = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPE_DOGMA_ATTRIBUTES],
+            &ConvertOptions::default(),
+            &mut ui,
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(ui.warning_count(), 1);
+
+        let options = ConvertOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let result = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPE_DOGMA_ATTRIBUTES],
+            &options,
+            &mut SilentUi::new(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dedup_junctions_ignores_repeated_tuples() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("out.db");
+        std::fs::write(
+            dir.path().join("typeMaterials.jsonl"),
+            r#"{"_key": 587, "materials": [{"materialTypeID": 34, "quantity": 10}, {"materialTypeID": 34, "quantity": 10}, {"materialTypeID": 35, "quantity": 5}]}"#,
+        )
+        .unwrap();
+
+        let result = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPE_MATERIALS],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        );
+        assert!(result.is_err());
+
+        let options = ConvertOptions {
+            dedup_junctions: true,
+            ..Default::default()
+        };
+        let count = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPE_MATERIALS],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_convert_when_ready_waits_for_each_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("out.db");
+
+        // The source file only appears once the importer asks for it
+        let mut requested = Vec::new();
+        let count = convert_when_ready(
+            &dir.path(),
+            &db_path,
+            vec![&TYPE_DOGMA_ATTRIBUTES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+            |source| {
+                requested.push(source.to_string());
+                std::fs::write(dir.path().join(source), DUPLICATED_TYPE_DOGMA).unwrap();
+            },
+        )
+        .unwrap();
+
+        assert_eq!(requested, vec!["typeDogma.jsonl"]);
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn test_profile_recorded_per_table() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("typeDogma.jsonl"), DUPLICATED_TYPE_DOGMA).unwrap();
+
+        let options = ConvertOptions {
+            profile: true,
+            ..Default::default()
+        };
+        let mut writer = SqliteWriter::with_options(&dir.path().join("out.db"), options).unwrap();
+        let mut ui = SilentUi::new();
+        writer
+            .create_tables(&[&TYPE_DOGMA_ATTRIBUTES], &mut ui)
+            .unwrap();
+        writer
+            .import_table(&TYPE_DOGMA_ATTRIBUTES, &dir.path(), &mut ui)
+            .unwrap();
+
+        let names: Vec<&str> = writer.profiles().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["type_dogma_attributes"]);
+        assert_eq!(writer.profiles()[0].1.rows, 4);
+    }
+
+    #[test]
+    fn test_continue_on_error_skips_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}
+{"_key": 7, "name": {"en": "Mod
+{"_key": 8, "name": {"en": "Charge"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let err = convert_to_sqlite(
+            &dir.path(),
+            &db_path,
+            vec![&CATEGORIES],
+            &mut SilentUi::new(),
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("categories.jsonl (line 2)"));
+        assert!(!db_path.exists());
+
+        let options = ConvertOptions {
+            continue_on_error: true,
+            ..Default::default()
+        };
+        let mut ui = SilentUi::new();
+        let err = convert_with_options(dir.path(), &db_path, vec![&CATEGORIES], &options, &mut ui)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("1 source line(s) failed to parse"));
+        assert_eq!(ui.warning_count(), 1);
+
+        // The remaining lines were still imported
+        let conn = Connection::open(&db_path).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_check_fk_reports_violations() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("groups.jsonl"),
+            r#"{"_key": 25, "categoryID": 6, "name": {"en": "Frigate"}}
+{"_key": 26, "categoryID": 99, "name": {"en": "Cruiser"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+        let tables = || vec![&CATEGORIES, &GROUPS];
+
+        let options = ConvertOptions {
+            check_fk: true,
+            ..Default::default()
+        };
+        let mut ui = SilentUi::new();
+        convert_with_options(dir.path(), &db_path, tables(), &options, &mut ui).unwrap();
+        assert_eq!(ui.warning_count(), 1);
+
+        // Strict: the database is still written, but the run fails
+        let options = ConvertOptions {
+            strict_fk: true,
+            ..options
+        };
+        let err = convert_with_options(
+            dir.path(),
+            &db_path,
+            tables(),
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("1 foreign key violation(s)"));
+        assert!(db_path.exists());
+    }
+
+    #[test]
+    fn test_finalize_analyzes_indexes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("groups.jsonl"),
+            r#"{"_key": 25, "name": {"en": "Frigate"}, "categoryID": 6}"#,
+        )
+        .unwrap();
+        let has_stats = |options: &ConvertOptions| -> bool {
+            let db_path = dir.path().join("out.db");
+            convert_with_options(
+                dir.path(),
+                &db_path,
+                vec![&GROUPS],
+                options,
+                &mut SilentUi::new(),
+            )
+            .unwrap();
+            Connection::open(&db_path)
+                .unwrap()
+                .query_row(
+                    "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'sqlite_stat1')",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+
+        assert!(has_stats(&ConvertOptions::default()));
+        assert!(!has_stats(&ConvertOptions {
+            skip_analyze: true,
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn test_split_by_table() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("groups.jsonl"),
+            r#"{"_key": 25, "categoryID": 6, "name": {"en": "Frigate"}}
+{"_key": 26, "categoryID": 6, "name": {"en": "Cruiser"}}
+"#,
+        )
+        .unwrap();
+        let out_dir = dir.path().join("split");
+
+        let options = ConvertOptions {
+            split_by_table: true,
+            ..Default::default()
+        };
+        let count = convert_with_options(
+            dir.path(),
+            &out_dir,
+            vec![&CATEGORIES, &GROUPS],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        assert_eq!(count, 3);
+
+        let conn = Connection::open(out_dir.join("groups.db")).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM groups", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 2);
+        // Only the one table, and no foreign key to categories in another file
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'categories'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 0);
+        let foreign_keys: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_foreign_key_list('groups')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(foreign_keys, 0);
+        assert!(out_dir.join("categories.db").exists());
+    }
+
+    #[test]
+    fn test_vacuum_shrinks_database() {
+        use std::fmt::Write as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut categories = String::new();
+        for id in 0..2000 {
+            writeln!(
+                categories,
+                r#"{{"_key": {}, "name": {{"en": "Category {} {}"}}}}"#,
+                id,
+                id,
+                "x".repeat(200)
+            )
+            .unwrap();
+        }
+        std::fs::write(dir.path().join("categories.jsonl"), categories).unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let mut ui = SilentUi::new();
+        let mut writer = SqliteWriter::new(&db_path).unwrap();
+        writer.create_tables(&[&CATEGORIES], &mut ui).unwrap();
+        writer
+            .import_table(&CATEGORIES, &dir.path(), &mut ui)
+            .unwrap();
+        writer
+            .conn
+            .execute("DELETE FROM categories WHERE id >= 10", [])
+            .unwrap();
+
+        let pages = |writer: &SqliteWriter| -> (i64, i64) {
+            let count = writer
+                .conn
+                .query_row("PRAGMA page_count", [], |row| row.get(0))
+                .unwrap();
+            let free = writer
+                .conn
+                .query_row("PRAGMA freelist_count", [], |row| row.get(0))
+                .unwrap();
+            (count, free)
+        };
+        let (pages_before, free_before) = pages(&writer);
+        assert!(free_before > 0);
+
+        writer.vacuum(&mut ui).unwrap();
+
+        let (pages_after, free_after) = pages(&writer);
+        assert_eq!(free_after, 0);
+        assert!(
+            pages_after < pages_before,
+            "{} -> {} pages",
+            pages_before,
+            pages_after
+        );
+        let page_size: i64 = writer
+            .conn
+            .query_row("PRAGMA page_size", [], |row| row.get(0))
+            .unwrap();
+        let size = std::fs::metadata(writer.temp_path().unwrap())
+            .unwrap()
+            .len();
+        assert_eq!(size, (pages_after * page_size) as u64);
+
+        let count: i64 = writer
+            .conn
+            .query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_convert_to_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}"#,
+        )
+        .unwrap();
+
+        let conn = convert_to_connection(
+            dir.path(),
+            vec![&CATEGORIES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        let name: String = conn
+            .query_row("SELECT name_en FROM categories WHERE id = 6", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(name, "Ship");
+
+        // Nothing was written next to the sources
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn test_fts_name_search() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("types.jsonl"),
+            r#"{"_key": 587, "name": {"en": "Rifter", "de": "Rifter"}, "groupID": 25}
+{"_key": 588, "name": {"en": "Reaper"}, "groupID": 25}
+{"_key": 11567, "name": {"en": "Avatar", "de": "Avatar-Titan"}, "groupID": 30}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let options = ConvertOptions {
+            fts: true,
+            languages: vec!["en".to_string(), "de".to_string()],
+            ..Default::default()
+        };
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let search = |query: &str| -> Vec<i64> {
+            conn.prepare("SELECT rowid FROM types_fts WHERE types_fts MATCH ?1 ORDER BY rowid")
+                .unwrap()
+                .query_map([query], |row| row.get(0))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap()
+        };
+        assert_eq!(search("rifter"), vec![587]);
+        assert_eq!(search("rea*"), vec![588]);
+        assert_eq!(search("name_de:titan"), vec![11567]);
+        assert!(search("drake").is_empty());
+    }
+
+    #[test]
+    fn test_type_reprocessing_view() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("types.jsonl"),
+            r#"{"_key": 34, "name": {"en": "Tritanium"}, "groupID": 18, "portionSize": 1}
+{"_key": 1230, "name": {"en": "Veldspar"}, "groupID": 462, "portionSize": 100}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("typeMaterials.jsonl"),
+            r#"{"_key": 1230, "materials": [{"materialTypeID": 34, "quantity": 400}]}"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let options = ConvertOptions {
+            views: true,
+            ..Default::default()
+        };
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPES, &TYPE_MATERIALS],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let row: (i64, i64, i64, i64, f64) = conn
+            .query_row(
+                "SELECT type_id, portion_size, material_type_id, quantity, quantity_per_unit
+                 FROM type_reprocessing",
+                [],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )
+            .unwrap();
+        assert_eq!(row, (1230, 100, 34, 400, 4.0));
+    }
+
+    #[test]
+    fn test_type_icons_view() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("icons.jsonl"),
+            r#"{"_key": 1, "iconFile": "res:/ui/texture/icons/1_64_1.png"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("types.jsonl"),
+            r#"{"_key": 587, "name": {"en": "Rifter"}, "groupID": 25, "iconID": 1}
+{"_key": 588, "name": {"en": "Reaper"}, "groupID": 25}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let options = ConvertOptions {
+            views: true,
+            ..Default::default()
+        };
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&ICONS, &TYPES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let rows: Vec<(i64, String, String)> = conn
+            .prepare("SELECT type_id, name_en, icon_file FROM type_icons")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            [(
+                587,
+                "Rifter".to_string(),
+                "res:/ui/texture/icons/1_64_1.png".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_report_unknown_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}, "newField": 1}
+{"_key": 7, "name": {"en": "Module"}, "newField": 2, "otherField": true}
+"#,
+        )
+        .unwrap();
+        let convert = |options: &ConvertOptions| {
+            let mut ui = SilentUi::new();
+            convert_with_options(
+                dir.path(),
+                &dir.path().join("out.db"),
+                vec![&CATEGORIES],
+                options,
+                &mut ui,
+            )
+            .unwrap();
+            ui.warning_count()
+        };
+
+        assert_eq!(convert(&ConvertOptions::default()), 0);
+        let options = ConvertOptions {
+            report_unknown_fields: true,
+            ..Default::default()
+        };
+        // One summary for the table, listing both fields
+        assert_eq!(convert(&options), 1);
+    }
+
+    #[test]
+    fn test_incremental_replaces_only_selected_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}
+{"_key": 7, "name": {"en": "Module"}}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("groups.jsonl"),
+            r#"{"_key": 25, "name": {"en": "Frigate"}, "categoryID": 6}"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+        convert_to_sqlite(
+            &dir.path(),
+            &db_path,
+            vec![&CATEGORIES, &GROUPS],
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        // The next build renames one category and drops the other
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ships"}}"#,
+        )
+        .unwrap();
+        let options = ConvertOptions {
+            incremental: true,
+            ..Default::default()
+        };
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        assert!(!temp_path_for(&db_path).exists());
+        let conn = Connection::open(&db_path).unwrap();
+        let categories: Vec<String> = conn
+            .prepare("SELECT name_en FROM categories ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(categories, ["Ships"]);
+        let groups: i64 = conn
+            .query_row("SELECT COUNT(*) FROM groups", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(groups, 1);
+    }
+
+    #[test]
+    fn test_json_column_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("dogmaEffects.jsonl"),
+            r#"{"_key": 11, "name": "loPower", "modifierInfo": [{"domain": "shipID", "func": "ItemModifier", "modifiedAttributeID": 15, "modifyingAttributeID": 30, "operation": 6}]}
+{"_key": 12, "name": "hiPower"}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        convert_to_sqlite(
+            &dir.path(),
+            &db_path,
+            vec![&DOGMA_EFFECTS],
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let modifier_info = |id: i64| -> Option<String> {
+            conn.query_row(
+                "SELECT modifier_info FROM dogma_effects WHERE id = ?",
+                [id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        let stored: serde_json::Value = serde_json::from_str(&modifier_info(11).unwrap()).unwrap();
+        assert_eq!(stored[0]["func"], "ItemModifier");
+        assert_eq!(stored[0]["modifiedAttributeID"], 15);
+        assert_eq!(modifier_info(12), None);
+
+        // SQLite's JSON functions can reach into the stored array
+        let attribute: i64 = conn
+            .query_row(
+                "SELECT json_extract(value, '$.modifyingAttributeID') \
+                 FROM dogma_effects, json_each(modifier_info) WHERE dogma_effects.id = 11",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(attribute, 30);
+    }
+
+    #[test]
+    fn test_denormalize_map_names() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mapRegions.jsonl"),
+            r#"{"_key": 10000002, "name": {"en": "The Forge"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("mapConstellations.jsonl"),
+            r#"{"_key": 20000020, "name": {"en": "Kimotoro"}, "regionID": 10000002}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("mapSolarSystems.jsonl"),
+            r#"{"_key": 30000142, "name": {"en": "Jita"}, "constellationID": 20000020, "regionID": 10000002}
+{"_key": 30000001, "name": {"en": "Tanoo"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let options = ConvertOptions {
+            denormalize: true,
+            ..Default::default()
+        };
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&MAP_REGIONS, &MAP_CONSTELLATIONS, &MAP_SOLAR_SYSTEMS],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let names = |id: i64| -> (Option<String>, Option<String>) {
+            conn.query_row(
+                "SELECT region_name_en, constellation_name_en FROM map_solar_systems WHERE id = ?",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            names(30000142),
+            (Some("The Forge".into()), Some("Kimotoro".into()))
+        );
+        assert_eq!(names(30000001), (None, None));
+    }
+
+    #[test]
+    fn test_build_jumps() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mapSolarSystems.jsonl"),
+            r#"{"_key": 30000142, "name": {"en": "Jita"}}
+{"_key": 30000144, "name": {"en": "Perimeter"}}
+"#,
+        )
+        .unwrap();
+        // Two gates each way between the same pair (as between some real systems)
+        std::fs::write(
+            dir.path().join("mapStargates.jsonl"),
+            r#"{"_key": 50001248, "solarSystemID": 30000142, "destinationSolarSystemID": 30000144}
+{"_key": 50001249, "solarSystemID": 30000144, "destinationSolarSystemID": 30000142}
+{"_key": 50001250, "solarSystemID": 30000142, "destinationSolarSystemID": 30000144}
+{"_key": 50001251, "solarSystemID": 30000144, "destinationSolarSystemID": 30000142}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let options = ConvertOptions {
+            build_jumps: true,
+            ..Default::default()
+        };
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&MAP_SOLAR_SYSTEMS, &MAP_STARGATES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let jumps: Vec<(i64, i64)> = conn
+            .prepare("SELECT from_system_id, to_system_id FROM map_jumps ORDER BY 1")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(jumps, vec![(30000142, 30000144), (30000144, 30000142)]);
+    }
+
+    #[test]
+    fn test_presence_flags_match_null_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("types.jsonl"),
+            r#"{"_key": 1, "name": {"en": "A"}, "iconID": 10, "graphicID": 20, "soundID": 30}
+{"_key": 2, "name": {"en": "B"}, "iconID": 11}
+{"_key": 3, "name": {"en": "C"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let options = ConvertOptions {
+            derive_presence_flags: true,
+            ..Default::default()
+        };
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let mismatches: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM types
+                 WHERE has_icon != (icon_id IS NOT NULL)
+                    OR has_graphic != (graphic_id IS NOT NULL)
+                    OR has_sound != (sound_id IS NOT NULL)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(mismatches, 0);
+
+        let with_icon: i64 = conn
+            .query_row("SELECT COUNT(*) FROM types WHERE has_icon", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(with_icon, 2);
+    }
+
+    #[test]
+    fn test_failed_conversion_keeps_existing_database() {
+        let (dir, conn, count, _) = convert_type_dogma(&ConvertOptions::default());
+        drop(conn);
+        let db_path = dir.path().join("out.db");
+
+        std::fs::write(
+            dir.path().join("typeDogma.jsonl"),
+            r#"{"_key": 1, "dogmaAttributes": {"attributeID": 10}}"#,
+        )
+        .unwrap();
+        let options = ConvertOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let result = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPE_DOGMA_ATTRIBUTES],
+            &options,
+            &mut SilentUi::new(),
+        );
+        assert!(result.is_err());
+
+        let conn = Connection::open(&db_path).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM type_dogma_attributes", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(rows as u64, count);
+        assert!(!temp_path_for(&db_path).exists());
+    }
+
+    #[test]
+    fn test_parallel_import_matches_serial() {
+        use std::fmt::Write as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut categories = String::new();
+        let mut groups = String::new();
+        let mut types = String::new();
+        let mut dogma = String::new();
+        for i in 0..20 {
+            writeln!(
+                categories,
+                r#"{{"_key": {}, "name": {{"en": "Category {}"}}}}"#,
+                i, i
+            )
+            .unwrap();
+        }
+        for i in 0..200 {
+            writeln!(
+                groups,
+                r#"{{"_key": {}, "categoryID": {}, "name": {{"en": "Group {}"}}}}"#,
+                i,
+                i % 20,
+                i
+            )
+            .unwrap();
+        }
+        for i in 0..5000 {
+            writeln!(
+                types,
+                r#"{{"_key": {}, "groupID": {}, "name": {{"en": "Type {}", "de": "Typ {}"}}}}"#,
+                i,
+                i % 200,
+                i,
+                i
+            )
+            .unwrap();
+            writeln!(
+                dogma,
+                r#"{{"_key": {}, "dogmaAttributes": [{{"attributeID": {}, "value": {}.0}}, {{"attributeID": {}, "value": 0.5}}]}}"#,
+                i % 4000,
+                i / 4000 * 2 + 1,
+                i,
+                i / 4000 * 2 + 2
+            )
+            .unwrap();
+        }
+        std::fs::write(dir.path().join("categories.jsonl"), categories).unwrap();
+        std::fs::write(dir.path().join("groups.jsonl"), groups).unwrap();
+        std::fs::write(dir.path().join("types.jsonl"), types).unwrap();
+        std::fs::write(dir.path().join("typeDogma.jsonl"), dogma).unwrap();
+
+        let tables = vec![&CATEGORIES, &GROUPS, &TYPES, &TYPE_DOGMA_ATTRIBUTES];
+        let run = |jobs: usize, extra: &ConvertOptions| {
+            let db_path = dir.path().join(format!("jobs{}.db", jobs));
+            let options = ConvertOptions {
+                jobs,
+                ..extra.clone()
+            };
+            let total = convert_with_options(
+                dir.path(),
+                &db_path,
+                tables.clone(),
+                &options,
+                &mut SilentUi::new(),
+            )
+            .unwrap();
+
+            let conn = Connection::open(&db_path).unwrap();
+            let counts: Vec<i64> = tables
+                .iter()
+                .map(|t| {
+                    conn.query_row(&format!("SELECT COUNT(*) FROM {}", t.name), [], |row| {
+                        row.get(0)
+                    })
+                    .unwrap()
+                })
+                .collect();
+            (total, counts)
+        };
+
+        for extra in [
+            ConvertOptions::default(),
+            ConvertOptions {
+                dedupe_keys: true,
+                row_limit: Some(3001),
+                ..Default::default()
+            },
+        ] {
+            let (serial_total, serial_counts) = run(1, &extra);
+            let (parallel_total, parallel_counts) = run(4, &extra);
+
+            assert_eq!(parallel_total, serial_total);
+            assert_eq!(parallel_counts, serial_counts);
+        }
+    }
+
+    /// Records structured progress events, ignoring everything else
+    #[derive(Default)]
+    struct EventUi {
+        events: Vec<ProgressEvent>,
+    }
+
+    impl Ui for EventUi {
+        fn set_phase(&mut self, _phase: crate::ui::Phase) {}
+        fn set_info(&mut self, _info: impl Into<String>) {}
+        fn set_progress(&mut self, _current: u64, _total: u64, _label: impl Into<String>) {}
+        fn clear_progress(&mut self) {}
+        fn log(&mut self, _message: impl Into<String>) {}
+        fn warn(&mut self, _message: impl Into<String>) {}
+        fn warning_count(&self) -> u64 {
+            0
+        }
+        fn confirm(&mut self, _prompt: impl Into<String>) -> bool {
+            false
+        }
+        fn on_event(&mut self, event: ProgressEvent) {
+            self.events.push(event);
+        }
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("typeDogma.jsonl"), DUPLICATED_TYPE_DOGMA).unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let mut ui = EventUi::default();
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES, &TYPE_DOGMA_ATTRIBUTES],
+            &ConvertOptions::default(),
+            &mut ui,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ui.events,
+            vec![
+                ProgressEvent::TableStarted {
+                    name: "categories",
+                    index: 0,
+                    total_tables: 2
+                },
+                ProgressEvent::RowsInserted {
+                    name: "categories",
+                    count: 1
+                },
+                ProgressEvent::TableFinished {
+                    name: "categories",
+                    rows: 1
+                },
+                ProgressEvent::TableStarted {
+                    name: "type_dogma_attributes",
+                    index: 1,
+                    total_tables: 2
+                },
+                ProgressEvent::RowsInserted {
+                    name: "type_dogma_attributes",
+                    count: 4
+                },
+                ProgressEvent::TableFinished {
+                    name: "type_dogma_attributes",
+                    rows: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_id_filter_cascades_to_junction_tables() {
+        let options = ConvertOptions {
+            id_filters: vec![("types".to_string(), [1].into())],
+            ..Default::default()
+        };
+        assert!(options.key_filter(&TYPES).is_some());
+        assert!(options.key_filter(&TYPE_DOGMA_ATTRIBUTES).is_some());
+        assert!(options.key_filter(&GROUPS).is_none());
+
+        let (_dir, conn, count, _) = convert_type_dogma(&options);
+        assert_eq!(count, 3);
+        assert_eq!(attribute_ids(&conn, 1), vec![10, 11, 12]);
+        assert!(attribute_ids(&conn, 2).is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}
+{"_key": 7, "name": {"en": "Module"}}
+"#,
+        )
+        .unwrap();
+        let options = ConvertOptions {
+            id_filters: vec![("categories".to_string(), [7, 8].into())],
+            ..Default::default()
+        };
+        let count = convert_with_options(
+            dir.path(),
+            &dir.path().join("out.db"),
+            vec![&CATEGORIES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+
+        let options = ConvertOptions {
+            id_filters: vec![("type_dogma_attributes".to_string(), [1].into())],
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn test_published_only_cascades_to_junction_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("types.jsonl"),
+            r#"{"_key": 1, "name": {"en": "Rifter"}, "groupID": 25, "published": true}
+{"_key": 2, "name": {"en": "Test Ship"}, "groupID": 25, "published": false}
+{"_key": 3, "name": {"en": "Unflagged"}, "groupID": 25}
+{"_key": 4, "name": {"en": "Slasher"}, "groupID": 25, "published": true}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("typeDogma.jsonl"),
+            r#"{"_key": 1, "dogmaAttributes": [{"attributeID": 10, "value": 1.0}]}
+{"_key": 2, "dogmaAttributes": [{"attributeID": 11, "value": 2.0}]}
+{"_key": 3, "dogmaAttributes": [{"attributeID": 12, "value": 3.0}]}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        for jobs in [1, 2] {
+            let options = ConvertOptions {
+                published_only: true,
+                jobs,
+                ..Default::default()
+            };
+            convert_with_options(
+                dir.path(),
+                &db_path,
+                vec![&TYPES, &TYPE_DOGMA_ATTRIBUTES],
+                &options,
+                &mut SilentUi::new(),
+            )
+            .unwrap();
+
+            let conn = Connection::open(&db_path).unwrap();
+            let type_ids: Vec<i64> = conn
+                .prepare("SELECT id FROM types ORDER BY id")
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap();
+            // Only an explicit `published: false` is skipped, although the stored flag
+            // of a record without one defaults to 0
+            assert_eq!(type_ids, vec![1, 3, 4]);
+            assert_eq!(attribute_ids(&conn, 1), vec![10]);
+            assert!(attribute_ids(&conn, 2).is_empty());
+            assert_eq!(attribute_ids(&conn, 3), vec![12]);
+        }
+
+        // Without the parent's source nothing can be skipped, which is reported
+        std::fs::remove_file(dir.path().join("types.jsonl")).unwrap();
+        let options = ConvertOptions {
+            published_only: true,
+            ..Default::default()
+        };
+        let mut ui = SilentUi::new();
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPE_DOGMA_ATTRIBUTES],
+            &options,
+            &mut ui,
+        )
+        .unwrap();
+        assert_eq!(ui.warning_count(), 1);
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(attribute_ids(&conn, 2), vec![11]);
+    }
+
+    #[test]
+    fn test_gzipped_source_file() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = std::fs::File::create(dir.path().join("typeDogma.jsonl.gz")).unwrap();
+        let mut encoder = GzEncoder::new(file, flate2::Compression::default());
+        encoder.write_all(DUPLICATED_TYPE_DOGMA.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let db_path = dir.path().join("out.db");
+        for jobs in [1, 2] {
+            let options = ConvertOptions {
+                jobs,
+                ..Default::default()
+            };
+            let count = convert_with_options(
+                dir.path(),
+                &db_path,
+                vec![&TYPE_DOGMA_ATTRIBUTES],
+                &options,
+                &mut SilentUi::new(),
+            )
+            .unwrap();
+            assert_eq!(count, 4);
+        }
+
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(attribute_ids(&conn, 1), vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn test_row_limit_applies_per_table_to_expanded_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("typeDogma.jsonl"), DUPLICATED_TYPE_DOGMA).unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}
+{"_key": 7, "name": {"en": "Module"}}
+{"_key": 8, "name": {"en": "Charge"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        let options = ConvertOptions {
+            row_limit: Some(3),
+            ..Default::default()
+        };
+        let count = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES, &TYPE_DOGMA_ATTRIBUTES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        assert_eq!(count, 6);
+
+        let options = ConvertOptions {
+            row_limit: Some(2),
+            ..Default::default()
+        };
+        let count = convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES, &TYPE_DOGMA_ATTRIBUTES],
+            &options,
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        assert_eq!(count, 4);
+
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(attribute_ids(&conn, 1), vec![10, 11]);
+        assert!(attribute_ids(&conn, 2).is_empty());
+    }
+
+    #[test]
+    fn test_meta_generated_at_is_utc_rfc3339() {
+        let (_dir, conn, _, _) = convert_type_dogma(&ConvertOptions::default());
+
+        let generated_at: String = conn
+            .query_row(
+                "SELECT value FROM sde_meta WHERE key = 'generated_at'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        assert!(generated_at.ends_with('Z'), "{}", generated_at);
+        assert!(chrono::DateTime::parse_from_rfc3339(&generated_at).is_ok());
+    }
+
+    #[test]
+    fn test_manifest_written_next_to_database() {
+        let (dir, _conn, _, _) = convert_type_dogma(&ConvertOptions::default());
+        let manifest: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("out.manifest.json")).unwrap(),
+        )
+        .unwrap();
+
+        assert!(manifest["build_number"].is_null());
+        assert!(manifest["generated_at"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(
+            manifest["tables"],
+            serde_json::json!([{"name": "type_dogma_attributes", "rows": 4}])
+        );
+
+        let options = ConvertOptions {
+            metadata: vec![("build_number".to_string(), "3064089".to_string())],
+            ..Default::default()
+        };
+        let (dir, _conn, _, _) = convert_type_dogma(&options);
+        let manifest: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("out.manifest.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest["build_number"], 3064089);
+    }
+
+    #[test]
+    fn test_meta_for_local_convert() {
+        let (_dir, conn, _, _) = convert_type_dogma(&ConvertOptions::default());
+        let meta: std::collections::HashMap<String, Option<String>> = conn
+            .prepare("SELECT key, value FROM sde_meta")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(meta["build_number"], None);
+        assert_eq!(meta["source"].as_deref(), Some("local"));
+        assert_eq!(
+            meta["crate_version"].as_deref(),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        assert!(meta["generated_at"].is_some());
+    }
+
+    #[test]
+    fn test_pragmas_apply_before_tables_are_created() {
+        let options = ConvertOptions {
+            pragmas: vec![
+                ("page_size".to_string(), "8192".to_string()),
+                ("auto_vacuum".to_string(), "FULL".to_string()),
+            ],
+            ..Default::default()
+        };
+        let (_dir, conn, _, _) = convert_type_dogma(&options);
+
+        let page_size: i64 = conn
+            .query_row("PRAGMA page_size", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(page_size, 8192);
+        let auto_vacuum: i64 = conn
+            .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(auto_vacuum, 1);
+
+        for (name, value) in [
+            ("page_size", "4096; DROP TABLE types"),
+            ("page_size", ""),
+            ("page_size = 1; --", "1"),
+            ("", "1"),
+        ] {
+            let options = ConvertOptions {
+                pragmas: vec![(name.to_string(), value.to_string())],
+                ..Default::default()
+            };
+            assert!(options.validate().is_err(), "{} = {}", name, value);
+        }
+    }
+
+    #[test]
+    fn test_batch_size_does_not_change_rows() {
+        let rows = |batch_size| {
+            let options = ConvertOptions {
+                batch_size,
+                ..Default::default()
+            };
+            let (_dir, conn, count, _) = convert_type_dogma(&options);
+            (count, attribute_ids(&conn, 1), attribute_ids(&conn, 2))
+        };
+
+        let expected = rows(None);
+        assert_eq!(rows(Some(1)), expected);
+        assert_eq!(rows(Some(3)), expected);
+        assert_eq!(rows(Some(10_000)), expected);
+        assert_eq!(rows(Some(0)), expected);
+    }
+
+    #[test]
+    fn test_cache_and_mmap_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let pragma = |options: ConvertOptions, name: &str| -> i64 {
+            let writer = SqliteWriter::with_options(&dir.path().join("out.db"), options).unwrap();
+            let sql = format!("PRAGMA {}", name);
+            writer.conn.query_row(&sql, [], |row| row.get(0)).unwrap()
+        };
+
+        assert_eq!(pragma(ConvertOptions::default(), "cache_size"), -64000);
+        assert_eq!(pragma(ConvertOptions::default(), "mmap_size"), 0);
+
+        let options = ConvertOptions {
+            cache_size_mb: Some(256),
+            mmap_size_mb: Some(16),
+            ..Default::default()
+        };
+        assert_eq!(pragma(options.clone(), "cache_size"), -256 * 1024);
+        assert_eq!(pragma(options, "mmap_size"), 16 * 1024 * 1024);
+
+        // Zero or negative keeps SQLite's defaults
+        let options = ConvertOptions {
+            cache_size_mb: Some(0),
+            mmap_size_mb: Some(-1),
+            ..Default::default()
+        };
+        assert_eq!(pragma(options.clone(), "cache_size"), -2000);
+        assert_eq!(pragma(options, "mmap_size"), 0);
+
+        let options = ConvertOptions {
+            mmap_size_mb: Some(i64::MAX),
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn test_read_meta() {
+        let options = ConvertOptions {
+            metadata: vec![
+                ("build_number".to_string(), "3064089".to_string()),
+                ("source".to_string(), "download".to_string()),
+            ],
+            ..Default::default()
+        };
+        let (dir, _conn, _, _) = convert_type_dogma(&options);
+        let db_path = dir.path().join("out.db");
+
+        assert_eq!(
+            read_meta(&db_path, "build_number").unwrap().as_deref(),
+            Some("3064089")
+        );
+        assert_eq!(read_meta(&db_path, "missing").unwrap(), None);
+        assert_eq!(
+            read_meta(&db_path, "source").unwrap(),
+            Some("download".into())
+        );
+        assert_eq!(
+            read_meta(&dir.path().join("absent.db"), "build_number").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_column_names_expands_localized() {
+        let columns = get_column_names(&CATEGORIES, &ConvertOptions::default());
+        assert!(columns.contains(&"name_en".to_string()));
+        assert!(!columns.contains(&"name".to_string()));
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::schema::ALL_TABLES;
+
+/// Orphaned-row count for one foreign key of an imported table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyReport {
+    pub table: String,
+    pub column: String,
+    pub references_table: String,
+    pub orphans: u64,
+}
+
+/// Check referential integrity of an existing database, opened read-only.
+///
+/// Returns one report per foreign key of every known table present in the
+/// database, in `ALL_TABLES` order. A reference to a table that was never
+/// imported counts every non-NULL value as orphaned.
+pub fn check_foreign_keys(db_path: &Path) -> Result<Vec<ForeignKeyReport>> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {:?}", db_path))?;
+    foreign_key_reports(&conn)
+}
+
+/// Foreign key reports for an open connection (see `check_foreign_keys`)
+pub(crate) fn foreign_key_reports(conn: &Connection) -> Result<Vec<ForeignKeyReport>> {
+    // Violations per (table, foreign key id)
+    let mut violations: HashMap<(String, i64), u64> = HashMap::new();
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        *violations.entry((row.get(0)?, row.get(3)?)).or_default() += 1;
+    }
+
+    let mut reports = Vec::new();
+    for schema in ALL_TABLES {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [schema.name],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            continue;
+        }
+
+        let mut stmt = conn.prepare(&format!("PRAGMA foreign_key_list({})", schema.name))?;
+        let keys = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // foreign_key_list lists the most recently declared key first
+        for (id, references_table, column) in keys.into_iter().rev() {
+            let orphans = violations
+                .get(&(schema.name.to_string(), id))
+                .copied()
+                .unwrap_or(0);
+            reports.push(ForeignKeyReport {
+                table: schema.name.to_string(),
+                column,
+                references_table,
+                orphans,
+            });
+        }
+    }
+
+    Ok(reports)
+}
+
+/// Rows of a table compared with the minimum given by `--expect`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowCountReport {
+    pub table: String,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl RowCountReport {
+    pub fn is_met(&self) -> bool {
+        self.actual >= self.expected
+    }
+}
+
+/// Count the rows of each expected table in an existing database, opened read-only.
+/// A table missing from the database has 0 rows.
+pub fn check_row_counts(
+    db_path: &Path,
+    expectations: &[(String, u64)],
+) -> Result<Vec<RowCountReport>> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {:?}", db_path))?;
+
+    let mut reports = Vec::new();
+    for (table, expected) in expectations {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [table],
+            |row| row.get(0),
+        )?;
+        let actual: u64 = if exists {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })?
+        } else {
+            0
+        };
+
+        reports.push(RowCountReport {
+            table: table.clone(),
+            expected: *expected,
+            actual,
+        });
+    }
+
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::tables::{CATEGORIES, GROUPS, TYPES};
+    use crate::ui::SilentUi;
+    use crate::writer::{convert_with_options, ConvertOptions};
+
+    #[test]
+    fn test_check_foreign_keys_counts_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("groups.jsonl"),
+            r#"{"_key": 25, "categoryID": 6, "name": {"en": "Frigate"}}
+{"_key": 26, "categoryID": 99, "name": {"en": "Cruiser"}}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("types.jsonl"),
+            r#"{"_key": 587, "groupID": 25, "name": {"en": "Rifter"}}
+{"_key": 588, "groupID": 404, "name": {"en": "Reaper"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+
+        // Groups were filtered out: every type's group_id is orphaned
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&TYPES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        let reports = check_foreign_keys(&db_path).unwrap();
+        let group_ref = reports
+            .iter()
+            .find(|r| r.column == "group_id")
+            .expect("types.group_id foreign key");
+        assert_eq!(group_ref.references_table, "groups");
+        assert_eq!(group_ref.orphans, 2);
+
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES, &GROUPS, &TYPES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+        let reports = check_foreign_keys(&db_path).unwrap();
+        let orphans = |table: &str, column: &str| {
+            reports
+                .iter()
+                .find(|r| r.table == table && r.column == column)
+                .map(|r| r.orphans)
+        };
+        assert_eq!(orphans("groups", "category_id"), Some(1));
+        assert_eq!(orphans("types", "group_id"), Some(1));
+    }
+
+    #[test]
+    fn test_check_row_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("categories.jsonl"),
+            r#"{"_key": 6, "name": {"en": "Ship"}}
+{"_key": 7, "name": {"en": "Module"}}
+"#,
+        )
+        .unwrap();
+        let db_path = dir.path().join("out.db");
+        convert_with_options(
+            dir.path(),
+            &db_path,
+            vec![&CATEGORIES],
+            &ConvertOptions::default(),
+            &mut SilentUi::new(),
+        )
+        .unwrap();
+
+        let reports = check_row_counts(
+            &db_path,
+            &[("categories".to_string(), 2), ("types".to_string(), 1)],
+        )
+        .unwrap();
+        assert_eq!(reports[0].actual, 2);
+        assert!(reports[0].is_met());
+        // Not imported at all
+        assert_eq!(reports[1].actual, 0);
+        assert!(!reports[1].is_met());
+    }
+}